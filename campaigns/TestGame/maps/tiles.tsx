@@ -0,0 +1,4 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.5" tiledversion="1.7.2" name="tiles" tilewidth="32" tileheight="32" tilecount="4" columns="2">
+ <image source="../sprite_sheets/tiles.png" width="64" height="64"/>
+</tileset>
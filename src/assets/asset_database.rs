@@ -3,12 +3,34 @@ use std::collections::hash_map::IterMut;
 
 use config::Config;
 use super::SpriteSheet;
-use super::audio::AudioClip;
+use super::Tilemap;
+use super::EntityPrefab;
+use super::Dialogue;
+use super::Encounter;
+use super::{CharacterClass, Character};
+use super::Item;
+use super::EventScript;
+use super::Quest;
+use super::Shop;
+use super::Script;
+use super::audio::{AudioClip, MusicPlaylist};
 
 pub enum AssetContainer {
     DoesNotExist, //does not exist
     Spritesheet(SpriteSheet),
     AudioClip(AudioClip),
+    Tilemap(Tilemap),
+    EntityPrefab(EntityPrefab),
+    Dialogue(Dialogue),
+    Encounter(Encounter),
+    CharacterClass(CharacterClass),
+    Character(Character),
+    Item(Item),
+    EventScript(EventScript),
+    Script(Script),
+    Quest(Quest),
+    Shop(Shop),
+    Music(MusicPlaylist),
 }
 
 
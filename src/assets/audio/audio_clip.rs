@@ -1,9 +1,13 @@
 use std::path::PathBuf;
 use std::fs::File;
 use std::io::BufReader;
-use rodio::Decoder; //Source
+use std::time::Duration;
+use rodio::{Decoder, Source};
+
+use super::AudioError;
 
 //used to determin which volume control in the settings should be applied to this clip
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ClipCategory {
     Voice,
     Music,
@@ -12,6 +16,7 @@ pub enum ClipCategory {
 
 
 //Holds info for playing a single audio file.
+#[derive(Clone)]
 pub struct AudioClip {
     path: PathBuf,
     clip_category: ClipCategory, //Used in volume control
@@ -26,10 +31,33 @@ impl AudioClip {
         }
     }
 
+    pub fn category(&self) -> ClipCategory {
+        self.clip_category
+    }
+
     //create a source 
-    pub fn make_source(&self) -> rodio::Decoder<BufReader<File>> {
-        let file = File::open(self.path.clone()).unwrap();
-        Decoder::new(BufReader::new(file)).unwrap()
+    pub fn make_source(&self) -> Result<Decoder<BufReader<File>>, AudioError> {
+        let file = File::open(&self.path).map_err(|err| AudioError::Io(self.path.clone(), err))?;
+        Decoder::new(BufReader::new(file)).map_err(|err| AudioError::Decode(self.path.clone(), err))
+    }
+
+    //checks the file can be played and finds how long it is. Some formats don't say so those
+    //  are decoded all the way through, which is why this is only done while loading.
+    pub fn validate(&self) -> Result<Duration, AudioError> {
+        let source = self.make_source()?;
+
+        let duration = match source.total_duration() {
+            Some(duration) => duration,
+            None => {
+                let samples_per_second = source.sample_rate() as f64 * f64::from(source.channels());
+                Duration::from_secs_f64(source.count() as f64 / samples_per_second.max(1.0))
+            }
+        };
+
+        match duration > Duration::from_secs(0) {
+            true => Ok(duration),
+            false => Err( AudioError::Empty(self.path.clone()) ),
+        }
     }
 
     //immediatly play the audio clip. No control over this for volume or anything else
@@ -40,3 +68,39 @@ impl AudioClip {
     }*/
 
 }
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a mono 16 bit wav file at 8000 samples a second
+    fn write_wav(name: &str, samples: usize) -> PathBuf {
+        let data_size = (samples * 2) as u32;
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        for value in [16u32, 1 | (1 << 16), 8000, 16000, 2 | (16 << 16)].iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        bytes.resize(bytes.len() + samples * 2, 0);
+
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn broken_files_are_errors_instead_of_panics() {
+        let clip = |path: PathBuf| AudioClip::new(path, ClipCategory::Effects);
+
+        assert_eq!(clip(write_wav("coffee_test_second.wav", 8000)).validate().ok(), Some(Duration::from_secs(1)));
+        assert!(matches!(clip(write_wav("coffee_test_silent.wav", 0)).validate(), Err(AudioError::Empty(_))));
+
+        assert!(matches!(clip(PathBuf::from("no/such/sound.ogg")).make_source(), Err(AudioError::Io(..))));
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use rodio::decoder::DecoderError;
+
+
+//Reasons an audio file couldn't be played. Logged while loading a campaign and when a file
+//  breaks while the game is running.
+#[derive(Debug)]
+pub enum AudioError {
+    Io(PathBuf, io::Error),
+    Decode(PathBuf, DecoderError),
+    Empty(PathBuf),
+}
+
+impl fmt::Display for AudioError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AudioError::Io(path, error) =>
+                write!(f, "Could not open audio file {}: {}", path.display(), error),
+            AudioError::Decode(path, error) =>
+                write!(f, "Could not decode audio file {}: {}", path.display(), error),
+            AudioError::Empty(path) =>
+                write!(f, "Audio file {} has no sound in it", path.display()),
+        }
+    }
+}
@@ -2,8 +2,20 @@
 mod audio_clip;
 pub use audio_clip::{AudioClip, ClipCategory};
 
+mod audio_error;
+pub use audio_error::AudioError;
+
 mod playlist;
-pub use playlist::Playlist;
+pub use playlist::{MusicPlaylist, MusicTrack, TrackQueue};
+
+mod music_manager;
+pub use music_manager::MusicManager;
+
+mod volume;
+pub use volume::{VolumeLevels, set_volume_levels, track_sink};
+
+mod sound_effects;
+pub use sound_effects::SoundEffects;
 
 
 
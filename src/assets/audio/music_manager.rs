@@ -0,0 +1,271 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rodio::{Sink, Source, Sample};
+
+use super::{ClipCategory, MusicPlaylist, TrackQueue, track_sink};
+
+
+//sections kept queued on a sink so the next one starts without a gap
+const QUEUED_SECTIONS: usize = 2;
+
+//playlists that were faded out and can still be picked up where they were left, like the map's
+//  music after a battle. Older ones are stopped.
+const MAX_SUSPENDED: usize = 2;
+
+
+//Plays the music game states ask for, above the states themselves so it carries on while they
+//  change. A new playlist crossfades with the one before it, and a playlist that was faded out
+//  recently carries on from where it stopped instead of starting over.
+pub struct MusicManager {
+    device: Option<rodio::Device>,
+    playing: Option<MusicChannel>,
+    fading_out: Vec<MusicChannel>,
+    suspended: Vec<MusicChannel>, //paused, most recently left last
+    fade_seconds: f32,
+}
+
+impl MusicManager {
+
+    pub fn new() -> MusicManager {
+        let device = super::get_audio_device();
+        if device.is_none() {
+            error!("[Audio/Music] No audio device was found.");
+        }
+
+        MusicManager {
+            device,
+            playing: None,
+            fading_out: vec![],
+            suspended: vec![],
+            fade_seconds: 0.0,
+        }
+    }
+
+    //starts fading to a playlist, nothing changes if it is already playing
+    pub fn play(&mut self, playlist: &MusicPlaylist) {
+        if self.playing.as_ref().map(|playing| playing.playlist.name == playlist.name).unwrap_or(false) {
+            return;
+        }
+
+        self.fade_seconds = playlist.crossfade;
+        if let Some(playing) = self.playing.take() {
+            self.fading_out.push(playing);
+        }
+
+        let resumed = take_channel(&mut self.fading_out, &playlist.name)
+                          .or_else(|| take_channel(&mut self.suspended, &playlist.name));
+
+        self.playing = Some( match resumed {
+            Some(channel) => {
+                channel.sink.play();
+                channel
+            },
+            None => MusicChannel::new(&self.device, playlist.clone()),
+        });
+    }
+
+    //moves fades along and keeps the playing music queued up
+    pub fn update(&mut self, delta_seconds: f32) {
+        let step = match self.fade_seconds > 0.0 {
+            true => delta_seconds / self.fade_seconds,
+            false => 1.0,
+        };
+
+        if let Some(playing) = &mut self.playing {
+            playing.fade(step);
+            playing.queue_ahead();
+        }
+
+        for channel in self.fading_out.iter_mut() {
+            channel.fade(-step);
+        }
+
+        //faded out music is paused so it can carry on later, there is nothing to carry on with silence
+        let (faded, fading): (Vec<MusicChannel>, Vec<MusicChannel>) =
+            self.fading_out.drain(..).partition(|channel| channel.volume <= 0.0);
+        self.fading_out = fading;
+
+        for channel in faded.into_iter().filter(|channel| !channel.playlist.tracks.is_empty()) {
+            channel.sink.pause();
+            self.suspended.retain(|suspended| suspended.playlist.name != channel.playlist.name);
+            self.suspended.push(channel);
+        }
+
+        if self.suspended.len() > MAX_SUSPENDED {
+            let stopped = self.suspended.len() - MAX_SUSPENDED;
+            self.suspended.drain(..stopped);
+        }
+    }
+}
+
+
+//takes out the channel playing a playlist
+fn take_channel(channels: &mut Vec<MusicChannel>, name: &str) -> Option<MusicChannel> {
+    let index = channels.iter().position(|channel| channel.playlist.name == name)?;
+    Some( channels.remove(index) )
+}
+
+
+//A playlist on its own sink. The fade is applied to what is queued rather than the sink so the
+//  music volume from the settings still reaches the sink.
+struct MusicChannel {
+    playlist: MusicPlaylist,
+    sink: Arc<Sink>,
+    tracks: TrackQueue,
+    gain: Arc<Mutex<f32>>, //shared with the queued sections
+    volume: f32, //how far faded in, from 0 to 1
+    stalled: bool, //none of the tracks could be played so it stops trying
+}
+
+impl MusicChannel {
+
+    fn new(device: &Option<rodio::Device>, playlist: MusicPlaylist) -> MusicChannel {
+        let sink = match device {
+            Some(device) => Sink::new(device),
+            None => Sink::new_idle().0,
+        };
+
+        //follows the music volume from the settings, even when it changes while playing
+        let sink = Arc::new(sink);
+        track_sink(&sink, ClipCategory::Music);
+
+        MusicChannel {
+            playlist,
+            sink,
+            tracks: TrackQueue::default(),
+            gain: Arc::new(Mutex::new(0.0)),
+            volume: 0.0,
+            stalled: false,
+        }
+    }
+
+    fn fade(&mut self, step: f32) {
+        self.volume = (self.volume + step).clamp(0.0, 1.0);
+        *self.gain.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = self.volume;
+    }
+
+    //queues sections until there is one waiting behind the one playing. Each track is tried
+    //  at most once per update so broken files can't hold up the game.
+    fn queue_ahead(&mut self) {
+        if self.stalled || self.playlist.tracks.is_empty() { return; }
+
+        let mut attempts = self.playlist.tracks.len();
+        while self.sink.len() < QUEUED_SECTIONS && attempts > 0 {
+            attempts -= 1;
+
+            let (index, start, end) = match self.tracks.next_section(&self.playlist) {
+                Some(section) => section,
+                None => return,
+            };
+
+            //a track that breaks while the game runs is skipped
+            let source = match self.playlist.tracks[index].clip.make_source() {
+                Ok(source) => source,
+                Err(err) => {
+                    warn!("[Audio/Music] {}", err);
+                    continue;
+                }
+            };
+
+            let gain = self.gain.clone();
+            self.sink.append(
+                Section::new(source, start, end)
+                    .amplify(0.0)
+                    .periodic_access(Duration::from_millis(5), move |section| {
+                        section.set_factor(*gain.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+                    })
+            );
+        }
+
+        if self.sink.empty() {
+            warn!("[Audio/Music] None of the tracks in {} could be played.", self.playlist.name);
+            self.stalled = true;
+        }
+    }
+}
+
+
+//Plays part of a source, from a start to an end in seconds. Used for the loops of music tracks.
+struct Section<S> {
+    input: S,
+    skip: usize, //samples left to skip before the start
+    remaining: Option<usize>, //samples left to play, None plays to the end
+}
+
+impl<S: Source> Section<S> where S::Item: Sample {
+
+    fn new(input: S, start: f32, end: Option<f32>) -> Section<S> {
+        let samples = |seconds: f32| (seconds.max(0.0) * input.sample_rate() as f32) as usize * usize::from(input.channels());
+        let skip = samples(start);
+
+        Section {
+            remaining: end.map(|end| samples(end).saturating_sub(skip)),
+            skip,
+            input,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Section<S> where S::Item: Sample {
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<S::Item> {
+        //skipping happens on the audio thread so queueing a loop doesn't hold up the game
+        while self.skip > 0 {
+            self.skip -= 1;
+            self.input.next()?;
+        }
+
+        match self.remaining {
+            Some(0) => None,
+            Some(remaining) => {
+                self.remaining = Some(remaining - 1);
+                self.input.next()
+            },
+            None => self.input.next(),
+        }
+    }
+}
+
+impl<S: Source> Source for Section<S> where S::Item: Sample {
+    fn current_frame_len(&self) -> Option<usize> {
+        match (self.input.current_frame_len(), self.remaining) {
+            (Some(frame), Some(remaining)) => Some( frame.min(remaining) ),
+            (frame, None) => frame,
+            (None, remaining) => remaining,
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn sections_play_from_start_to_end() {
+        //two channels at four samples a second, so each second is eight samples
+        let source = || SamplesBuffer::new(2, 4, (0..32).collect::<Vec<i16>>());
+
+        let section: Vec<i16> = Section::new(source(), 1.0, Some(2.5)).collect();
+        assert_eq!(section, (8..20).collect::<Vec<i16>>());
+
+        let to_end: Vec<i16> = Section::new(source(), 3.0, None).collect();
+        assert_eq!(to_end, (24..32).collect::<Vec<i16>>());
+    }
+}
@@ -1,44 +1,124 @@
 use super::audio_clip::AudioClip;
-use rodio::Sink;
 
 
-//intended for looping a music track
-pub struct Playlist {
-    music_tracks: Vec<AudioClip>,
-    audio_queue: Sink,
+//seconds one playlist takes to fade into the next when a music asset doesn't say
+const DEFAULT_CROSSFADE: f32 = 1.5;
+
+
+//A piece of music in a playlist. A track with a loop start plays through once and then repeats
+//  from the loop start to the loop end (or the end of the file) for as long as it is playing,
+//  so everything before the loop start is an intro that is only heard the first time.
+#[derive(Clone)]
+pub struct MusicTrack {
+    pub clip: AudioClip,
+    pub loop_start: Option<f32>, //seconds
+    pub loop_end: Option<f32>, //seconds
 }
 
-impl Playlist {
-
-    pub fn new(music_tracks: Vec<AudioClip>) -> Playlist {
-        let sink = 
-            match &super::get_audio_device() {
-                Some(device) => Sink::new( device ),
-                None => {
-                    error!("[Audio/Playlist] No audio device was found.");
-                    Sink::new_idle().0
-                },
-            };
-        
-
-        //TODO should pull from some sort of settings structure and get updated whenever that changes
-        sink.set_volume(0.2);
-
-        Playlist {
-            music_tracks,
-            audio_queue: sink,
+impl MusicTrack {
+
+    //a track that plays through once
+    pub fn new(clip: AudioClip) -> MusicTrack {
+        MusicTrack {
+            clip,
+            loop_start: None,
+            loop_end: None,
         }
     }
+}
+
 
-    //make sure the playlist is still looping
-    pub fn maintain_looping(&mut self) {
-        //if the current song is finished we take the next song, 
-        //  add it to the queue, and place it at the end of the playlist
-        if self.audio_queue.empty() {
-            let next = self.music_tracks.remove(0);
-            self.audio_queue.append(next.make_source());
-            self.music_tracks.push(next);
+//Music played one track after another and started again from the top once every track has
+//  played. Playlists are told apart by name so the music manager knows when the music changes.
+//  A playlist with no tracks is silence.
+#[derive(Clone)]
+pub struct MusicPlaylist {
+    pub name: String,
+    pub tracks: Vec<MusicTrack>,
+    pub crossfade: f32, //seconds taken to fade from the music before it
+}
+
+impl MusicPlaylist {
+
+    pub fn new(name: String, tracks: Vec<MusicTrack>) -> MusicPlaylist {
+        MusicPlaylist {
+            name,
+            tracks,
+            crossfade: DEFAULT_CROSSFADE,
         }
     }
 
+    //fades out whatever is playing. Named after what asked for it, like a map with no music.
+    pub fn silence(name: String) -> MusicPlaylist {
+        MusicPlaylist::new(name, vec![])
+    }
+}
+
+
+//The part of a track to play next and which track follows it. Kept apart from the sinks so a
+//  playlist can be stepped through without an audio device.
+#[derive(Default)]
+pub struct TrackQueue {
+    next_track: usize,
+    in_loop: bool, //the track's intro has played so only its loop is left
+}
+
+impl TrackQueue {
+
+    //index of the track to queue next and the seconds to play it from and up to
+    pub fn next_section(&mut self, playlist: &MusicPlaylist) -> Option<(usize, f32, Option<f32>)> {
+        if playlist.tracks.is_empty() {
+            return None;
+        }
+
+        let index = self.next_track % playlist.tracks.len();
+        let track = &playlist.tracks[index];
+
+        match track.loop_start {
+            //looping tracks never hand over to the next one
+            Some(loop_start) => {
+                let start = if self.in_loop { loop_start } else { 0.0 };
+                self.in_loop = true;
+                Some( (index, start, track.loop_end) )
+            },
+            None => {
+                self.next_track = index + 1;
+                Some( (index, 0.0, None) )
+            },
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::ClipCategory;
+
+    fn track(file: &str, loop_start: Option<f32>) -> MusicTrack {
+        MusicTrack {
+            loop_start,
+            ..MusicTrack::new( AudioClip::new(file.into(), ClipCategory::Music) )
+        }
+    }
+
+    #[test]
+    fn tracks_repeat_and_loops_skip_their_intro() {
+        let mut queue = TrackQueue::default();
+        let playlist = MusicPlaylist::new("town".to_string(), vec![ track("a.ogg", None), track("b.ogg", None) ]);
+
+        let order: Vec<usize> = (0..3).filter_map(|_| queue.next_section(&playlist)).map(|(index, _, _)| index).collect();
+        assert_eq!(order, vec![0, 1, 0]);
+
+        let mut queue = TrackQueue::default();
+        let mut playlist = MusicPlaylist::new("battle".to_string(), vec![ track("intro.ogg", Some(4.5)), track("b.ogg", None) ]);
+        playlist.tracks[0].loop_end = Some(60.0);
+
+        assert_eq!(queue.next_section(&playlist), Some((0, 0.0, Some(60.0))));
+        assert_eq!(queue.next_section(&playlist), Some((0, 4.5, Some(60.0))));
+        assert_eq!(queue.next_section(&playlist), Some((0, 4.5, Some(60.0))));
+
+        assert_eq!(TrackQueue::default().next_section(&MusicPlaylist::silence(String::new())), None);
+    }
 }
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use coffee::graphics::{Point, Rectangle};
+use rodio::{Sink, Source, source::ChannelVolume};
+
+use super::{ClipCategory, track_sink};
+use crate::assets::{AssetDatabase, AssetContainer};
+
+
+//most sounds that can play at once, any more are skipped until one finishes
+const MAX_SINKS: usize = 16;
+
+//most copies of the same clip that can play at once so a sound played every update doesn't
+//  drown out everything else
+const MAX_INSTANCES_PER_CLIP: usize = 3;
+
+
+//A sink from the pool and the clip last played on it.
+struct PooledSink {
+    sink: Arc<Sink>,
+    clip_name: String,
+}
+
+//Plays audio clips by name on demand, like footsteps, menu blips, and battle hits. Sinks are
+//  reused once their sound ends, each follows its clip's category volume, and sounds given a
+//  place on the map are panned and get quieter the further they are from the middle of the view.
+pub struct SoundEffects {
+    device: Option<rodio::Device>,
+    pool: Vec<PooledSink>,
+}
+
+impl SoundEffects {
+
+    pub fn new() -> SoundEffects {
+        let device = super::get_audio_device();
+        if device.is_none() {
+            error!("[Audio/Sound Effects] No audio device was found.");
+        }
+
+        SoundEffects::with_device(device)
+    }
+
+    //without a device sounds are still pooled and limited but never heard
+    pub fn with_device(device: Option<rodio::Device>) -> SoundEffects {
+        SoundEffects {
+            device,
+            pool: Vec::new(),
+        }
+    }
+
+    //plays a clip at full volume in both ears
+    pub fn play(&mut self, asset_database: &mut AssetDatabase, clip_name: &str) {
+        self.play_with(asset_database, clip_name, (1.0, 1.0));
+    }
+
+    //plays a clip coming from a place on the map, heard from the middle of the view
+    pub fn play_at(&mut self, asset_database: &mut AssetDatabase, clip_name: &str, position: Point, view: &Rectangle<f32>) {
+        //nothing is played when it is too far away to hear
+        if let Some(volume) = positional_volume(position, view) {
+            self.play_with(asset_database, clip_name, volume);
+        }
+    }
+
+    fn play_with(&mut self, asset_database: &mut AssetDatabase, clip_name: &str, (left, right): (f32, f32)) {
+        let clip = match asset_database.get_asset(&clip_name.to_string()) {
+            AssetContainer::AudioClip(clip) => clip,
+            _ => {
+                warn!("[Audio/Sound Effects] Requested audio clip {} does not exist.", clip_name);
+                return;
+            }
+        };
+
+        //a missing or broken file is skipped instead of stopping the game
+        let source = match clip.make_source() {
+            Ok(source) => source,
+            Err(err) => {
+                warn!("[Audio/Sound Effects] {}", err);
+                return;
+            }
+        };
+
+        if let Some(sink) = self.claim_sink(clip_name, clip.category()) {
+            sink.append( ChannelVolume::new(source.convert_samples::<f32>(), vec![left, right]) );
+        }
+    }
+
+    //an idle sink from the pool, or a new one while the pool has room. None when the clip is
+    //  already playing as many times as it can or every sink is busy.
+    fn claim_sink(&mut self, clip_name: &str, category: ClipCategory) -> Option<Arc<Sink>> {
+        let playing = self.pool.iter()
+            .filter(|pooled| pooled.clip_name == clip_name && !pooled.sink.empty())
+            .count();
+        if playing >= MAX_INSTANCES_PER_CLIP {
+            return None;
+        }
+
+        let index = match self.pool.iter().position(|pooled| pooled.sink.empty()) {
+            Some(index) => index,
+            None if self.pool.len() < MAX_SINKS => {
+                let sink = match &self.device {
+                    Some(device) => Sink::new(device),
+                    None => Sink::new_idle().0,
+                };
+                self.pool.push( PooledSink { sink: Arc::new(sink), clip_name: String::new() } );
+                self.pool.len() - 1
+            },
+            None => {
+                info!("[Audio/Sound Effects] Skipped {} as {} sounds are already playing.", clip_name, MAX_SINKS);
+                return None;
+            }
+        };
+
+        //the sink may have played another category before, this moves it to the clip's volume
+        let pooled = &mut self.pool[index];
+        pooled.clip_name = clip_name.to_string();
+        track_sink(&pooled.sink, category);
+
+        Some(pooled.sink.clone())
+    }
+}
+
+
+//left and right ear volumes for a sound at a place on the map. Sounds fade out over the width of
+//  the view and pan fully to one side half a view away. None once it can't be heard.
+fn positional_volume(position: Point, view: &Rectangle<f32>) -> Option<(f32, f32)> {
+    let hearing_distance = view.width.max(1.0);
+    let listener = Point::new(view.x + view.width / 2.0, view.y + view.height / 2.0);
+
+    let offset = position - listener;
+    let loudness = 1.0 - offset.norm() / hearing_distance;
+    if loudness <= 0.0 {
+        return None;
+    }
+
+    let pan = (offset.x / (hearing_distance / 2.0)).clamp(-1.0, 1.0);
+    Some( (loudness * (1.0 - pan.max(0.0)), loudness * (1.0 + pan.min(0.0))) )
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sounds_pan_and_fade_with_distance() {
+        let view = Rectangle { x: 0.0, y: 0.0, width: 200.0, height: 100.0 };
+
+        assert_eq!(positional_volume(Point::new(100.0, 50.0), &view), Some((1.0, 1.0)));
+        assert_eq!(positional_volume(Point::new(150.0, 50.0), &view), Some((0.375, 0.75)));
+        assert_eq!(positional_volume(Point::new(0.0, 50.0), &view), Some((0.5, 0.0)));
+        assert_eq!(positional_volume(Point::new(100.0, 250.0), &view), None);
+    }
+
+    #[test]
+    fn each_clip_plays_a_limited_number_of_times() {
+        let mut sound_effects = SoundEffects::with_device(None);
+
+        //idle sinks are never played so whatever is added to them keeps them busy
+        for _ in 0..MAX_INSTANCES_PER_CLIP {
+            let sink = sound_effects.claim_sink("hit", ClipCategory::Effects).unwrap();
+            sink.append( rodio::source::Empty::<f32>::new() );
+        }
+        assert!(sound_effects.claim_sink("hit", ClipCategory::Effects).is_none());
+
+        //a different clip gets a sink, and an idle one is reused instead of adding another
+        assert!(sound_effects.claim_sink("step", ClipCategory::Effects).is_some());
+        assert!(sound_effects.claim_sink("step", ClipCategory::Effects).is_some());
+        assert_eq!(sound_effects.pool.len(), MAX_INSTANCES_PER_CLIP + 1);
+    }
+}
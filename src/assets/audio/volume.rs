@@ -0,0 +1,95 @@
+use std::sync::{Arc, Weak, Mutex};
+
+use rodio::Sink;
+use serde::{Serialize, Deserialize};
+
+use super::ClipCategory;
+
+
+//How loud each category of audio plays, from 0 to 1. The master volume scales all the others.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeLevels {
+    pub master: f32,
+    pub music: f32,
+    pub voice: f32,
+    pub effects: f32,
+}
+
+//music starts quieter so it sits under voices and sound effects
+const DEFAULT_LEVELS: VolumeLevels = VolumeLevels { master: 1.0, music: 0.2, voice: 1.0, effects: 1.0 };
+
+impl Default for VolumeLevels {
+    fn default() -> VolumeLevels {
+        DEFAULT_LEVELS
+    }
+}
+
+impl VolumeLevels {
+
+    //volume a sink playing this category of clip should be set to
+    pub fn volume_for(&self, category: ClipCategory) -> f32 {
+        let level = match category {
+            ClipCategory::Music => self.music,
+            ClipCategory::Voice => self.voice,
+            ClipCategory::Effects => self.effects,
+        };
+
+        (self.master * level).clamp(0.0, 1.0)
+    }
+}
+
+
+//Sinks that are playing and what plays on them, so a volume change reaches audio that already
+//  started. Sinks are only held weakly and forgotten once whoever played them drops them.
+struct Mixer {
+    levels: VolumeLevels,
+    sinks: Vec<(Weak<Sink>, ClipCategory)>,
+}
+
+static MIXER: Mutex<Mixer> = Mutex::new( Mixer { levels: DEFAULT_LEVELS, sinks: Vec::new() } );
+
+//a panic while the mixer was held can't leave the volumes half changed so it is safe to carry on
+fn mixer() -> std::sync::MutexGuard<'static, Mixer> {
+    MIXER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+
+//changes the volumes and applies them to every sink still playing
+pub fn set_volume_levels(levels: VolumeLevels) {
+    let mut mixer = mixer();
+    mixer.levels = levels;
+
+    mixer.sinks.retain(|(sink, category)| match sink.upgrade() {
+        Some(sink) => { sink.set_volume(levels.volume_for(*category)); true },
+        None => false,
+    });
+}
+
+//sets a sink to its category's volume and keeps it there when the volumes change. Tracking a
+//  sink again moves it to the new category.
+pub fn track_sink(sink: &Arc<Sink>, category: ClipCategory) {
+    let mut mixer = mixer();
+    sink.set_volume(mixer.levels.volume_for(category));
+
+    let tracked = Arc::downgrade(sink);
+    mixer.sinks.retain(|(sink, _)| sink.strong_count() > 0 && !sink.ptr_eq(&tracked));
+    mixer.sinks.push( (tracked, category) );
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn master_volume_scales_every_category() {
+        let levels = VolumeLevels { master: 0.5, music: 0.4, voice: 1.0, effects: 0.0 };
+
+        assert_eq!(levels.volume_for(ClipCategory::Music), 0.2);
+        assert_eq!(levels.volume_for(ClipCategory::Voice), 0.5);
+        assert_eq!(levels.volume_for(ClipCategory::Effects), 0.0);
+        assert_eq!(VolumeLevels { master: 2.0, ..levels }.volume_for(ClipCategory::Voice), 1.0);
+    }
+}
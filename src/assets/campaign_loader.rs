@@ -1,4 +1,5 @@
-use std::path::{Path, PathBuf};
+use std::path::{Path, PathBuf, Component};
+use std::collections::HashMap;
 use std::ffi::OsStr;
 
 //For image loading and hooking into the Task system
@@ -6,7 +7,7 @@ use coffee::{
     load::Task,
     graphics::Image,
 };
-use coffee::graphics::Gpu;
+use coffee::graphics::{Gpu, Point, Vector, Rectangle};
 
 //For config file loading and parsing 
 use config::*;
@@ -16,13 +17,44 @@ extern crate walkdir;
 use walkdir::WalkDir;
 
 
-use super::{AssetDatabase, AssetContainer, SpriteSheet};
-use super::audio::{ClipCategory, AudioClip};
+use super::{AssetDatabase, AssetContainer, SpriteSheet, SpritePos, CampaignMetadata, Tilemap, TileLayer, MapObject};
+use super::{EntityPrefab, PrefabComponent};
+use super::{Dialogue, DialogueNode, DialogueChoice, Portrait, Expression, FlagEffect};
+use super::{Encounter, EncounterEnemy, Stats, StatGrowth, StatModifiers, CharacterClass, Character};
+use super::{Item, ItemCategory, ItemEffect, Equipment};
+use super::{EventScript, EventCommand, TriggerCondition, MapArrival};
+use super::{Script, sandboxed_engine};
+use super::{Quest, QuestStage, QuestObjective, QuestReward};
+use super::{Shop, ShopStock};
+use super::audio::{ClipCategory, AudioClip, MusicPlaylist, MusicTrack};
+use super::tiled_loader::load_tiled_map;
 
 
 //loads the metadata for each campaign so we can display the options
-//pub fn load_all_campaign_metadata(asset_db: &mut AssetDatabase) {//-> Config {
-//}
+pub fn load_all_campaign_metadata(campaigns_path: &str, gpu: &mut Gpu) -> Vec<CampaignMetadata> {
+    let mut campaigns = vec![];
+
+    //every folder directly under the campaigns folder is a potential campaign
+    for entry in WalkDir::new(campaigns_path).min_depth(1).max_depth(1)
+                 .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+                 .into_iter().filter_map(|e| e.ok()) {
+
+        if !entry.file_type().is_dir() { continue; }
+
+        let config_path = entry.path().join("campaign.yml");
+        if !config_path.is_file() {
+            warn!("[Asset Loading] Campaign folder {} has no campaign.yml file and will be skipped.",
+                  entry.path().to_str().unwrap_or("<error could not convert campaign path to str>"));
+            continue;
+        }
+
+        if let Some(metadata) = load_campaign_metadata(&config_path, gpu) {
+            campaigns.push(metadata);
+        }
+    }
+
+    campaigns
+}
 
 
 //loads all data for a given campaign
@@ -47,6 +79,19 @@ pub fn load_campaign_data(path: &str, gpu: &mut Gpu, asset_db: &mut AssetDatabas
         let asset_was_loaded = match config.get_str("type").unwrap_or("".to_string()).as_str() {
             "sprite sheet" => load_sprite_sheet(&config, &config_path, gpu, asset_db),
             "audio clip" => load_audio_clip(&config, &config_path, asset_db),
+            "tilemap" => load_tilemap(&config, &config_path, asset_db),
+            "tiled map" => load_tiled_map(&config, &config_path, gpu, asset_db),
+            "entity prefab" => load_entity_prefab(&config, &config_path, asset_db),
+            "dialogue" => load_dialogue(&config, &config_path, asset_db),
+            "encounter" => load_encounter(&config, &config_path, asset_db),
+            "class" => load_character_class(&config, &config_path, asset_db),
+            "character" => load_character(&config, &config_path, asset_db),
+            "item" => load_item(&config, &config_path, asset_db),
+            "event script" => load_event_script(&config, &config_path, asset_db),
+            "script" => load_script(&config, &config_path, asset_db),
+            "quest" => load_quest(&config, &config_path, asset_db),
+            "shop" => load_shop(&config, &config_path, asset_db),
+            "music" => load_music(&config, &config_path, asset_db),
             _ => {
                 warn!("[Asset Loading] 'Type' key does not exist or value is not supported. Config File Path: {}",
                        config_path.to_str().unwrap());
@@ -96,6 +141,53 @@ fn find_config_files(path: &str) -> Vec<PathBuf> {
 
 
 
+//assets refer to files relative to their config file. We join the two and remove any '..' parts
+//  so the same file always ends up with the same asset name no matter who refers to it.
+pub fn resolve_relative_path(config_path: &Path, file: &str) -> Option<PathBuf> {
+    let joined = config_path.parent()?.join(file);
+
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                //only step back over a normal folder, otherwise keep the '..' around
+                match resolved.components().next_back() {
+                    Some(Component::Normal(_)) => { resolved.pop(); },
+                    _ => resolved.push(".."),
+                }
+            },
+            other => resolved.push(other.as_os_str()),
+        }
+    }
+
+    Some(resolved)
+}
+
+
+//pulls a value out of a table from a config file and converts it to the type we need.
+//  The error message says which key was missing or malformed so it can be logged.
+pub(super) fn table_value<'de, T: serde::Deserialize<'de>>(table: &HashMap<String, Value>, key: &str) 
+   -> Result<T, String> {
+
+    match table.get(key) {
+        Some(value) => value.clone().try_into::<T>()
+                            .map_err(|err| format!("Value for key '{}' is invalid. {}", key, err)),
+        None => Err( format!("Key '{}' is missing", key) ),
+    }
+}
+
+
+//sprite positions count from 1, so a 0 would point outside the sprite sheet
+pub(super) fn valid_sprite_pos(position: SpritePos) -> Result<SpritePos, String> {
+    match position {
+        (0, _) | (_, 0) => Err( format!("Sprite position [{}, {}] is invalid, rows and columns start at 1",
+                                        position.0, position.1) ),
+        position => Ok(position),
+    }
+}
+
+
 //utility function to create a coffee error since it's a bit of a pain.
 fn make_coffee_err_from_str(msg: &str) -> coffee::Error {
     coffee::Error::IO(
@@ -104,9 +196,9 @@ fn make_coffee_err_from_str(msg: &str) -> coffee::Error {
 }
 
 //creates a task for loading a config file and it's resources
-fn load_config_task(file_path: &PathBuf) -> Task<Config> {
+fn load_config_task(file_path: &Path) -> Task<Config> {
     //needed so closure below can capture
-    let path = file_path.clone();
+    let path = file_path.to_path_buf();
                      
 
     Task::new(move || {
@@ -134,6 +226,68 @@ fn load_config_task(file_path: &PathBuf) -> Task<Config> {
 }
 
 
+//load a single campaign.yml file. Only the name is required, everything else is
+//  optional since it is only used to describe the campaign on the selection screen.
+fn load_campaign_metadata(config_path: &Path, gpu: &mut Gpu) -> Option<CampaignMetadata> {
+
+    let config =
+    match load_config_task(config_path).run(gpu) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("[Asset Loading] Could not load campaign file. Following error returned: {}", e);
+            return None;
+        },
+    };
+
+    let name = match config.get_str("name") {
+        Ok(name) => name,
+        Err(err) => {
+            warn!("[Asset Loading] {} {}. Error follows: {}",
+                  "Could not find required config value for campaign in config file",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                  err);
+            return None;
+        }
+    };
+
+    // title image is relative to the campaign.yml file just like other asset files.
+    let title_image = match config.get_str("title image").ok()
+                              .and_then(|file| resolve_relative_path(config_path, &file)) {
+        Some(image_path) => {
+            match Image::load( image_path.clone() ).run(gpu) {
+                Ok(image) => Some(image),
+                Err(err) => {
+                    warn!("[Asset Loading] Could not load title Image at {} related to config file {}. Following error returned: {}",
+                          image_path.to_str().unwrap_or("<error could not convert image path to str>"),
+                          config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                          err,
+                    );
+                    None
+                }
+            }
+        },
+        None => None,
+    };
+
+    Some(CampaignMetadata {
+        path: config_path.parent().map(|dir_path| dir_path.to_path_buf()).unwrap_or_default(),
+        name,
+        title_image,
+        description: config.get_str("description").unwrap_or_default(),
+        version: config.get_str("version").unwrap_or_default(),
+        author: config.get_str("author").unwrap_or_default(),
+        start_map: config.get_str("start map").ok(),
+        starting_items: config.get::<Vec<(String, u32)>>("starting items").unwrap_or_default(),
+        starting_gold: config.get::<u32>("starting gold").unwrap_or(0),
+        equipment_slots: config.get::<Vec<String>>("equipment slots")
+                               .unwrap_or_else(|_| vec!["weapon".to_string(), "armor".to_string(), "accessory".to_string()]),
+        starting_party: config.get::<Vec<String>>("starting party").unwrap_or_default(),
+        battle_music: config.get_str("battle music").ok(),
+        menu_music: config.get_str("menu music").ok(),
+    })
+}
+
+
 //load sprite sheets
 //TODO, maybe should make this return a task also?
 fn load_sprite_sheet(config: &Config, config_path: &PathBuf, 
@@ -163,9 +317,9 @@ fn load_sprite_sheet(config: &Config, config_path: &PathBuf,
     //process the file path and asset name to the right types
 
     // assume image path is given as relative to config path hence taking the parent as a starting point. 
-    let image_path = match config_path.parent() {
+    let image_path = match resolve_relative_path(config_path, &file.ok().expect("File value is missing while loading.")) {
            
-        Some(dir_path) => dir_path.join(file.ok().expect("File value is missing while loading.")),
+        Some(image_path) => image_path,
 
         //getting parent from path failed somehow. Shouldn't ever happen naturally.
         None => {
@@ -202,16 +356,30 @@ fn load_sprite_sheet(config: &Config, config_path: &PathBuf,
     };
                         
 
+    //sprites are found by dividing the image up so there has to be at least one row and column
+    let (rows, columns) = (rows.ok().expect("row convert error"), columns.ok().expect("column convert error"));
+    if rows < 1 || columns < 1 || rows > i64::from(u16::MAX) || columns > i64::from(u16::MAX) {
+        warn!("[Asset Loading] Sprite sheet rows and columns must be between 1 and {} in config file {}.",
+              u16::MAX,
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+        );
+        return false;
+    }
+
     //create sprite sheet, add animations, then add the new asset to the database
     let mut spritesheet = SpriteSheet::new( 
         image,
-        rows.ok().expect("row convert error") as u16, 
-        columns.ok().expect("column convert error") as u16, 
+        rows as u16, 
+        columns as u16, 
     );
         
     if animations.is_ok() {
         for (animation_name, tuple_list) in animations.ok().unwrap().iter() {
-            match tuple_list.clone().try_into::< Vec<(u16,u16)> >() {
+            let sprite_positions = tuple_list.clone().try_into::< Vec<(u16,u16)> >()
+                .map_err(|err| err.to_string())
+                .and_then(|positions| positions.into_iter().map(valid_sprite_pos).collect::<Result<Vec<_>, _>>());
+
+            match sprite_positions {
                 Ok(sprite_pos_array) => 
                     //TODO might want to do additional checking of data. 
                     //    No error is thrown for having an extra value regardless if it is an int or not.
@@ -273,9 +441,9 @@ fn load_audio_clip(config: &Config, config_path: &PathBuf, asset_db: &mut AssetD
     };
 
     // assume image path is given as relative to config path hence taking the parent as a starting point. 
-    let audio_path = match config_path.parent() {
+    let audio_path = match resolve_relative_path(config_path, &file.ok().expect("File value is missing while loading.")) {
            
-        Some(dir_path) => dir_path.join(file.ok().expect("File value is missing while loading.")),
+        Some(audio_path) => audio_path,
 
         //getting parent from path failed somehow. Shouldn't ever happen naturally.
         None => {
@@ -299,7 +467,1321 @@ fn load_audio_clip(config: &Config, config_path: &PathBuf, asset_db: &mut AssetD
 
     let audio_clip = AudioClip::new(audio_path, clip_category);
 
+    //a file that can't be played is left out so the game doesn't find out halfway through playing
+    if let Err(err) = audio_clip.validate() {
+        warn!("[Asset Loading] {}. Related to config file {}.",
+              err,
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+        );
+        return false;
+    }
+
     asset_db.add_asset(asset_name, AssetContainer::AudioClip(audio_clip));
     return true;
 }
 
+
+
+//load tilemaps
+fn load_tilemap(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let tileset = config.get_str("tileset");
+    let tile_size = config.get::<(f32, f32)>("tile size");
+    let layers = config.get_array("layers");
+
+    if name.is_err() || tileset.is_err() || tile_size.is_err() || layers.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for tilemap type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = tileset { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = tile_size { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = layers { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    // the tileset is a sprite sheet asset, which are named after their image path.
+    let tileset_name = match resolve_relative_path(config_path, &tileset.unwrap())
+                             .and_then(|path| path.into_os_string().into_string().ok()) {
+        Some(tileset_name) => tileset_name,
+        None => {
+            warn!("[Asset Loading] Could not resolve tileset path for tilemap in config file {}.",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"));
+            return false;
+        }
+    };
+
+    let (tile_width, tile_height) = tile_size.unwrap();
+    let mut tilemap = Tilemap::new(tile_width, tile_height);
+    tilemap.add_tileset(1, tileset_name);
+
+    //tiled maps give their music as a map property
+    if let Ok(music) = config.get_str("music") {
+        tilemap.properties.insert("music".to_string(), music);
+    }
+
+    for (layer_number, layer_value) in layers.unwrap().into_iter().enumerate() {
+        let layer_table = match layer_value.into_table() {
+            Ok(layer_table) => layer_table,
+            Err(err) => {
+                warn!("[Asset Loading] Layer {} is not a table in config file {}. Following error returned: {}",
+                      layer_number,
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                continue;
+            }
+        };
+
+        let layer_name = layer_table.get("name")
+                                    .and_then(|value| value.clone().into_str().ok())
+                                    .unwrap_or_else(|| format!("layer {}", layer_number));
+
+        //anything else given for the layer, like 'solid', is kept as a property
+        let properties: HashMap<String, String> = layer_table.iter()
+            .filter(|(key, _)| key.as_str() != "name" && key.as_str() != "tiles")
+            .filter_map(|(key, value)| value.clone().into_str().ok().map(|value| (key.clone(), value)))
+            .collect();
+
+        match layer_table.get("tiles").map(|value| value.clone().try_into::< Vec<Vec<u16>> >()) {
+            Some(Ok(tiles)) => tilemap.add_layer( TileLayer { tiles, properties } ),
+            Some(Err(err)) => {
+                warn!("[Asset Loading] Tiles for layer {} do not follow form {} in config file {}. Following error returned: {}",
+                      layer_name,
+                      "[ [index_1, ..., index_n], ..., [index_1, ..., index_n] ]",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                continue;
+            },
+            None => {
+                warn!("[Asset Loading] Layer {} is missing its tiles in config file {}.",
+                      layer_name,
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                );
+                continue;
+            },
+        }
+    }
+
+    //objects are optional
+    for (object_number, object_value) in config.get_array("objects").unwrap_or_default().into_iter().enumerate() {
+        match parse_map_object(object_value) {
+            Ok(object) => tilemap.objects.push(object),
+            Err(err) => {
+                warn!("[Asset Loading] Could not load object {} in config file {}. Error follows: {}",
+                      object_number,
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+            }
+        }
+    }
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::Tilemap(tilemap));
+    true
+}
+
+
+//objects in a yaml tilemap. Only the position is required. Any extra keys become properties.
+fn parse_map_object(value: Value) -> Result<MapObject, String> {
+    let table = value.into_table().map_err(|err| err.to_string())?;
+
+    let (x, y) = table_value::<(f32, f32)>(&table, "position")?;
+    let (width, height) = table_value::<(f32, f32)>(&table, "size").unwrap_or((0.0, 0.0));
+
+    let properties: HashMap<String, String> = table.iter()
+        .filter(|(key, _)| !["name", "type", "position", "size"].contains(&key.as_str()))
+        .filter_map(|(key, value)| value.clone().into_str().ok().map(|value| (key.clone(), value)))
+        .collect();
+
+    Ok(MapObject {
+        name: table_value::<String>(&table, "name").unwrap_or_default(),
+        kind: table_value::<String>(&table, "type").unwrap_or_default(),
+        area: Rectangle { x, y, width, height },
+        properties,
+    })
+}
+
+
+//load entity prefabs
+fn load_entity_prefab(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let components = config.get_table("components");
+
+    if name.is_err() || components.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for entity prefab type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = components { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    //a broken component is reported and skipped, the rest of the prefab is still usable
+    let mut prefab = EntityPrefab::new();
+    for (component_name, value) in components.unwrap() {
+        match parse_prefab_component(&component_name, value, config_path) {
+            Ok(component) => prefab.add_component(component),
+            Err(err) => {
+                warn!("{} {} {} {}. {} {}",
+                      "[Asset Loading]",
+                      "Could not load",
+                      component_name,
+                      "component for entity prefab type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+            }
+        }
+    }
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::EntityPrefab(prefab));
+    true
+}
+
+
+fn parse_prefab_component(component_name: &str, value: Value, config_path: &Path) 
+   -> Result<PrefabComponent, String> {
+
+    //components without settings can be written as an empty table or just 'true'
+    let table = value.clone().into_table().unwrap_or_default();
+
+    match component_name {
+        "position" => {
+            let (x, y) = value.try_into::<(f32, f32)>()
+                              .map_err(|err| format!("Position must be given as [x, y]. {}", err))?;
+            Ok( PrefabComponent::Position { map_pos: Point::new(x, y) } )
+        },
+
+        "visual" => {
+            // the sprite sheet is named after its image which is relative to this config file
+            let sprite_sheet = table_value::<String>(&table, "sprite sheet")?;
+            let sprite_sheet_name = resolve_relative_path(config_path, &sprite_sheet)
+                                        .and_then(|path| path.into_os_string().into_string().ok())
+                                        .ok_or("Could not resolve sprite sheet path")?;
+
+            Ok( PrefabComponent::Visual {
+                sprite_sheet_name,
+                sprite_location: valid_sprite_pos( table_value::<(u16, u16)>(&table, "sprite").unwrap_or((1, 1)) )?,
+            })
+        },
+
+        "animation" => Ok( PrefabComponent::Animation { frame_duration: table_value::<f32>(&table, "frame duration")? } ),
+
+        "player control" => Ok( PrefabComponent::PlayerControl { speed: table_value::<f32>(&table, "speed")? } ),
+
+        "movement" => Ok( PrefabComponent::Movement ),
+
+        "interaction" => Ok( PrefabComponent::Interaction { dialogue: table_value::<String>(&table, "dialogue")? } ),
+
+        "character" => Ok( PrefabComponent::Character {
+            character: table_value::<String>(&table, "character")?,
+            level: table_value::<u32>(&table, "level").ok(),
+        }),
+
+        "collider" => {
+            let (x, y) = table_value::<(f32, f32)>(&table, "offset").unwrap_or((0.0, 0.0));
+            let (width, height) = table_value::<(f32, f32)>(&table, "size")?;
+
+            Ok( PrefabComponent::Collider { offset: Vector::new(x, y), width, height } )
+        },
+
+        "trigger" => Ok( PrefabComponent::Trigger {
+            condition: TriggerCondition::parse(&table_value::<String>(&table, "on")?,
+                                               table_value::<String>(&table, "flag").ok().as_deref())?,
+            event: table_value::<String>(&table, "event")?,
+            size: table_value::<(f32, f32)>(&table, "size").unwrap_or((32.0, 32.0)),
+        }),
+
+        _ => Err( "Component type is not supported".to_string() ),
+    }
+}
+
+
+fn load_dialogue(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let start = config.get_str("start");
+    let nodes = config.get_table("nodes");
+
+    if name.is_err() || start.is_err() || nodes.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for dialogue type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = start { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = nodes { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    //node names are table keys which get lowercased, so links to them are lowercased as well
+    let mut dialogue = Dialogue::new(start.unwrap().to_lowercase());
+
+    for (node_id, value) in nodes.unwrap() {
+        match parse_dialogue_node(value, config_path) {
+            Ok(node) => dialogue.add_node(node_id, node),
+            Err(err) => {
+                warn!("{} {} {} {} {}. {}",
+                      "[Asset Loading]",
+                      "Could not load node",
+                      node_id,
+                      "for dialogue type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                return false;
+            }
+        }
+    }
+
+    //broken links end the conversation early, so point them out now instead
+    let mut links: Vec<&String> = vec![&dialogue.start];
+    for node in dialogue.nodes.values() {
+        links.extend(node.next.iter().chain(node.otherwise.iter()));
+        links.extend(node.choices.iter().filter_map(|choice| choice.next.as_ref()));
+    }
+
+    for link in links.into_iter().filter(|link| dialogue.node(link).is_none()) {
+        warn!("[Asset Loading] Dialogue node {} does not exist but is linked to in config file {}.",
+              link,
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+        );
+    }
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::Dialogue(dialogue));
+    true
+}
+
+
+fn parse_dialogue_node(value: Value, config_path: &Path) -> Result<DialogueNode, String> {
+    let table = value.into_table().map_err(|err| err.to_string())?;
+
+    let portrait = match table.get("portrait") {
+        Some(portrait) => {
+            let portrait = portrait.clone().into_table().map_err(|err| format!("Bad portrait. {}", err))?;
+
+            // the sprite sheet is named after its image which is relative to this config file
+            let sprite_sheet = table_value::<String>(&portrait, "sprite sheet")?;
+            let sprite_sheet_name = resolve_relative_path(config_path, &sprite_sheet)
+                                        .and_then(|path| path.into_os_string().into_string().ok())
+                                        .ok_or("Could not resolve portrait sprite sheet path")?;
+
+            Some( Portrait {
+                sprite_sheet_name,
+                sprite_location: match portrait.get("sprite") {
+                    Some(_) => valid_sprite_pos( table_value::<(u16, u16)>(&portrait, "sprite")? )?,
+                    None => (1, 1),
+                },
+            })
+        },
+        None => None,
+    };
+
+    let mut choices = vec![];
+    if let Some(choice_values) = table.get("choices") {
+        for choice in choice_values.clone().into_array().map_err(|err| format!("Bad choices. {}", err))? {
+            let choice = choice.into_table().map_err(|err| format!("Bad choice. {}", err))?;
+
+            choices.push( DialogueChoice {
+                text: table_value::<String>(&choice, "text")?,
+                next: optional_link(&choice, "next"),
+                conditions: conditions(&choice, "conditions")?,
+                effects: flag_effects(&choice, "effects")?,
+            });
+        }
+    }
+
+    Ok( DialogueNode {
+        speaker: table_value::<String>(&table, "speaker").unwrap_or_default(),
+        text: table_value::<String>(&table, "text")?,
+        portrait,
+        choices,
+        next: optional_link(&table, "next"),
+        conditions: conditions(&table, "conditions")?,
+        otherwise: optional_link(&table, "otherwise"),
+        effects: flag_effects(&table, "effects")?,
+    })
+}
+
+
+//name of another dialogue node, lowercased to match the node names
+fn optional_link(table: &HashMap<String, Value>, key: &str) -> Option<String> {
+    table_value::<String>(table, key).ok().map(|link| link.to_lowercase())
+}
+
+
+//list of conditions like "met villager" or "gold >= 50", missing lists are empty
+fn conditions(table: &HashMap<String, Value>, key: &str) -> Result<Vec<Expression>, String> {
+    match table.get(key) {
+        Some(_) => table_value::<Vec<String>>(table, key)?
+                      .iter()
+                      .map(|text| Expression::parse(text))
+                      .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+//list of flag changes like "flag", "!flag", or "gold = gold - 50", missing lists are empty
+fn flag_effects(table: &HashMap<String, Value>, key: &str) -> Result<Vec<FlagEffect>, String> {
+    match table.get(key) {
+        Some(_) => table_value::<Vec<String>>(table, key)?
+                      .iter()
+                      .map(|text| FlagEffect::parse(text))
+                      .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+
+fn load_encounter(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let enemies = config.get_array("enemies");
+
+    if name.is_err() || enemies.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for encounter type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = enemies { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let mut encounter = Encounter {
+        enemies: vec![],
+        can_flee: config.get_bool("can flee").unwrap_or(true),
+        music: config.get_str("music").ok(),
+    };
+
+    for (index, value) in enemies.unwrap().into_iter().enumerate() {
+        match parse_enemy(value) {
+            Ok(enemy) => encounter.enemies.push(enemy),
+            Err(err) => {
+                warn!("{} {} {} {} {}. {}",
+                      "[Asset Loading]",
+                      "Could not load enemy",
+                      index + 1,
+                      "for encounter type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                return false;
+            }
+        }
+    }
+
+    if encounter.enemies.is_empty() {
+        warn!("[Asset Loading] Encounter in config file {} has no enemies.",
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"));
+        return false;
+    }
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::Encounter(encounter));
+    true
+}
+
+
+fn parse_enemy(value: Value) -> Result<EncounterEnemy, String> {
+    let table = value.into_table().map_err(|err| err.to_string())?;
+
+    Ok( EncounterEnemy {
+        character: table_value::<String>(&table, "character")?,
+        level: table_value::<u32>(&table, "level").ok(),
+    })
+}
+
+
+fn load_character_class(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let stats = config.get_table("stats");
+
+    if name.is_err() || stats.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for class type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = stats { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    //each stat is given as [value at level 1, growth per level]
+    let stats = stats.unwrap();
+    let stat = |key: &str| -> Result<(u32, f32), String> {
+        match stats.get(key) {
+            Some(_) => table_value::<(u32, f32)>(&stats, key),
+            None => Ok((0, 0.0)),
+        }
+    };
+
+    let parsed_stats = (|| -> Result<(Stats, StatGrowth), String> {
+        let (max_hp, hp_growth) = stat("hp")?;
+        let (max_mp, mp_growth) = stat("mp")?;
+        let (attack, attack_growth) = stat("attack")?;
+        let (defense, defense_growth) = stat("defense")?;
+        let (magic, magic_growth) = stat("magic")?;
+        let (speed, speed_growth) = stat("speed")?;
+
+        Ok((
+            Stats { max_hp: max_hp.max(1), max_mp, attack, defense, magic, speed },
+            StatGrowth { 
+                max_hp: hp_growth, max_mp: mp_growth, attack: attack_growth, 
+                defense: defense_growth, magic: magic_growth, speed: speed_growth,
+            },
+        ))
+    })();
+
+    let (level_one, growth) = match parsed_stats {
+        Ok(parsed_stats) => parsed_stats,
+        Err(err) => {
+            warn!("[Asset Loading] Stats must be given as [level 1 value, growth per level] in config file {}. {}",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                  err,
+            );
+            return false;
+        }
+    };
+
+    let max_level = config.get::<u32>("max level").unwrap_or(99).max(1);
+
+    //experience is either listed for every level or worked out from [base, exponent]
+    let experience_table = match config.get::<Vec<u32>>("experience") {
+        Ok(table) => table,
+        Err(_) => {
+            let (base, exponent) = config.get::<(f32, f32)>("experience curve").unwrap_or((10.0, 1.5));
+            CharacterClass::experience_curve(base, exponent, max_level)
+        }
+    };
+
+    let class = CharacterClass {
+        level_one,
+        growth,
+        growth_curve: config.get::<f32>("growth curve").unwrap_or(1.0),
+        experience_table,
+        max_level,
+    };
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::CharacterClass(class));
+    true
+}
+
+
+fn load_character(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let class = config.get_str("class");
+
+    if name.is_err() || class.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for character type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = class { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let sprite_pos = match valid_sprite_pos( config.get::<(u16, u16)>("sprite").unwrap_or((1, 1)) ) {
+        Ok(sprite_pos) => sprite_pos,
+        Err(err) => {
+            warn!("[Asset Loading] Bad sprite in character config file {}. {}",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                  err,
+            );
+            return false;
+        }
+    };
+
+    // the sprite sheet is named after its image which is relative to this config file
+    let sprite = config.get_str("sprite sheet").ok()
+        .and_then(|sprite_sheet| resolve_relative_path(config_path, &sprite_sheet))
+        .and_then(|path| path.into_os_string().into_string().ok())
+        .map(|sprite_sheet_name| (sprite_sheet_name, sprite_pos));
+
+    let name = name.unwrap();
+    let character = Character {
+        name: name.clone(),
+        class: class.unwrap(),
+        level: config.get::<u32>("level").unwrap_or(1).max(1),
+        sprite,
+        experience_reward: config.get::<u32>("experience").unwrap_or(0),
+        loot: config.get::<Vec<String>>("loot").unwrap_or_default(),
+        equipment: config.get::<HashMap<String, String>>("equipment").unwrap_or_default(),
+    };
+
+    asset_db.add_asset(name, AssetContainer::Character(character));
+    true
+}
+
+
+//loads an item the party can carry
+fn load_item(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+
+    if let Err(err) = name {
+        warn!("{} {} {}. {} {}",
+              "[Asset Loading]",
+              "Could not find required config value for item type in config file",
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+              "Error follows: ",
+              err,
+        );
+
+        return false //config missing required values
+    }
+
+    let category = match config.get_str("category") {
+        Ok(category) => match ItemCategory::parse(category.as_str()) {
+            Some(category) => category,
+            None => {
+                warn!("[Asset Loading] {} {}. {}",
+                      "Item category is not supported in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      "Supported categories are consumable, key item, equipment, and material.",
+                );
+                return false;
+            }
+        },
+        Err(_) => ItemCategory::Consumable,
+    };
+
+    let effect = match config.get_table("effect") {
+        Ok(effect) => {
+            let parsed_effect = (|| -> Result<ItemEffect, String> {
+                Ok( ItemEffect {
+                    restore_hp: if effect.contains_key("restore hp") { table_value::<u32>(&effect, "restore hp")? } else { 0 },
+                    restore_mp: if effect.contains_key("restore mp") { table_value::<u32>(&effect, "restore mp")? } else { 0 },
+                    revive: if effect.contains_key("revive") { table_value::<bool>(&effect, "revive")? } else { false },
+                })
+            })();
+
+            match parsed_effect {
+                Ok(effect) => Some(effect),
+                Err(err) => {
+                    warn!("[Asset Loading] Item effect could not be read in config file {}. {}",
+                          config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                          err,
+                    );
+                    return false;
+                }
+            }
+        },
+        Err(_) => None,
+    };
+
+    let equipment = match config.get_table("equipment") {
+        Ok(equipment) => match parse_equipment(&equipment) {
+            Ok(equipment) => Some(equipment),
+            Err(err) => {
+                warn!("[Asset Loading] Item equipment could not be read in config file {}. {}",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                return false;
+            }
+        },
+        Err(_) => None,
+    };
+
+    let icon_pos = match valid_sprite_pos( config.get::<(u16, u16)>("icon").unwrap_or((1, 1)) ) {
+        Ok(icon_pos) => icon_pos,
+        Err(err) => {
+            warn!("[Asset Loading] Bad icon in item config file {}. {}",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                  err,
+            );
+            return false;
+        }
+    };
+
+    // the sprite sheet is named after its image which is relative to this config file
+    let icon = config.get_str("icon sheet").ok()
+        .and_then(|sprite_sheet| resolve_relative_path(config_path, &sprite_sheet))
+        .and_then(|path| path.into_os_string().into_string().ok())
+        .map(|sprite_sheet_name| (sprite_sheet_name, icon_pos));
+
+    //key items are one of a kind unless the campaign says otherwise
+    let default_stack_limit = if category == ItemCategory::KeyItem { 1 } else { 99 };
+
+    let name = name.unwrap();
+    let item = Item {
+        name: name.clone(),
+        description: config.get_str("description").unwrap_or_default(),
+        icon,
+        category,
+        stack_limit: config.get::<u32>("stack limit").unwrap_or(default_stack_limit).max(1),
+        price: config.get::<u32>("price").unwrap_or(0),
+        effect,
+        equipment,
+    };
+
+    asset_db.add_asset(name, AssetContainer::Item(item));
+    true
+}
+
+
+//the slot an item is worn in, who can wear it, and how it changes their stats
+fn parse_equipment(table: &HashMap<String, Value>) -> Result<Equipment, String> {
+    let classes = match table.get("classes") {
+        Some(_) => table_value::<Vec<String>>(table, "classes")?,
+        None => vec![],
+    };
+
+    let stats = match table.get("stats") {
+        Some(_) => table_value::<HashMap<String, i32>>(table, "stats")?,
+        None => HashMap::new(),
+    };
+    let stat = |key: &str| stats.get(key).copied().unwrap_or(0);
+
+    Ok( Equipment {
+        slot: table_value::<String>(table, "slot")?,
+        modifiers: StatModifiers {
+            max_hp: stat("hp"),
+            max_mp: stat("mp"),
+            attack: stat("attack"),
+            defense: stat("defense"),
+            magic: stat("magic"),
+            speed: stat("speed"),
+        },
+        classes,
+    })
+}
+
+
+//loads a script of commands run by triggers
+fn load_event_script(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let commands = config.get_array("commands");
+
+    if name.is_err() || commands.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for event script type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = commands { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let mut script = EventScript {
+        commands: vec![],
+        once: config.get_bool("once").unwrap_or(false),
+    };
+
+    for (index, value) in commands.unwrap().into_iter().enumerate() {
+        match parse_event_command(value, config_path) {
+            Ok(command) => script.commands.push(command),
+            Err(err) => {
+                warn!("{} {} {} {} {}. {}",
+                      "[Asset Loading]",
+                      "Could not load command",
+                      index + 1,
+                      "for event script type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                return false;
+            }
+        }
+    }
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::EventScript(script));
+    true
+}
+
+
+//each command is a table with a single key naming the command, like "wait: 1.5"
+fn parse_event_command(value: Value, config_path: &Path) -> Result<EventCommand, String> {
+    let table = value.into_table().map_err(|err| err.to_string())?;
+
+    if table.len() != 1 {
+        return Err( "Each command needs exactly one key naming it".to_string() );
+    }
+    let (command, _) = table.iter().next().unwrap();
+
+    //commands with settings use a table, the rest just give their value
+    let settings = table[command].clone().into_table().unwrap_or_default();
+
+    match command.as_str() {
+        "show dialogue" => Ok( EventCommand::ShowDialogue(table_value::<String>(&table, command)?) ),
+
+        "move entity" => {
+            let (x, y) = table_value::<(f32, f32)>(&settings, "to")?;
+            Ok( EventCommand::MoveEntity {
+                entity: table_value::<String>(&settings, "entity")?,
+                to: Point::new(x, y),
+                speed: table_value::<f32>(&settings, "speed").unwrap_or(60.0).max(1.0),
+            })
+        },
+
+        "set flag" => Ok( EventCommand::SetFlag(FlagEffect::parse(&table_value::<String>(&table, command)?)?) ),
+
+        //either just the item's name or the item and how many
+        "give item" => match table_value::<String>(&table, command) {
+            Ok(item) => Ok( EventCommand::GiveItem { item, count: 1 } ),
+            Err(_) => Ok( EventCommand::GiveItem {
+                item: table_value::<String>(&settings, "item")?,
+                count: table_value::<u32>(&settings, "count").unwrap_or(1),
+            }),
+        },
+
+        "start battle" => Ok( EventCommand::StartBattle(table_value::<String>(&table, command)?) ),
+
+        //either just the map's name or the map and where the party arrives, a 'position' or the
+        //  name of an object on the new map to 'arrive at'
+        "change map" => match table_value::<String>(&table, command) {
+            Ok(map) => Ok( EventCommand::ChangeMap { map, arrival: MapArrival::MapSpawn } ),
+            Err(_) => {
+                let position = table_value::<(f32, f32)>(&settings, "position").ok();
+                let object = table_value::<String>(&settings, "arrive at").ok();
+
+                Ok( EventCommand::ChangeMap {
+                    map: table_value::<String>(&settings, "map")?,
+                    arrival: match (position, object) {
+                        (Some((x, y)), _) => MapArrival::Position(Point::new(x, y)),
+                        (None, Some(object)) => MapArrival::Object(object),
+                        (None, None) => MapArrival::MapSpawn,
+                    },
+                })
+            },
+        },
+
+        // audio clips are named after their file which is relative to this config file. Either just
+        //  the file or the 'sound' and the entity it comes from 'at' so it is heard from there
+        "play sound" => {
+            let (clip, at) = match table_value::<String>(&table, command) {
+                Ok(clip) => (clip, None),
+                Err(_) => (table_value::<String>(&settings, "sound")?, table_value::<String>(&settings, "at").ok()),
+            };
+            let clip_name = resolve_relative_path(config_path, &clip)
+                                .and_then(|path| path.into_os_string().into_string().ok())
+                                .ok_or("Could not resolve sound path")?;
+
+            Ok( EventCommand::PlaySound { clip: clip_name, at } )
+        },
+
+        "wait" => Ok( EventCommand::Wait(table_value::<f32>(&table, command)?.max(0.0)) ),
+
+        "run script" => Ok( EventCommand::RunScript(table_value::<String>(&table, command)?) ),
+
+        "start quest" => Ok( EventCommand::StartQuest(table_value::<String>(&table, command)?) ),
+
+        "open shop" => Ok( EventCommand::OpenShop(table_value::<String>(&table, command)?) ),
+
+        _ => Err( format!("'{}' is not a supported command", command) ),
+    }
+}
+
+
+fn load_script(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let file = config.get_str("file");
+
+    if name.is_err() || file.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for script type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = file { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let script_path = match resolve_relative_path(config_path, &file.unwrap()) {
+        Some(script_path) => script_path,
+        None => {
+            warn!("{} {}",
+                  "[Asset Loading] Parent missing from config path when processing",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+            );
+            return false;
+        },
+    };
+
+    let source = match std::fs::read_to_string(&script_path) {
+        Ok(source) => source,
+        Err(err) => {
+            warn!("[Asset Loading] Could not read script {} related to config file {}. Following error returned: {}",
+                  script_path.display(),
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                  err,
+            );
+            return false;
+        }
+    };
+
+    //syntax errors come with the line they are on
+    let mut ast = match sandboxed_engine().compile(&source) {
+        Ok(ast) => ast,
+        Err(err) => {
+            warn!("[Asset Loading] Script {} has an error: {}", script_path.display(), err);
+            return false;
+        }
+    };
+    ast.set_source(script_path.to_string_lossy().to_string());
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::Script( Script { file: script_path, ast } ));
+    true
+}
+
+
+//loads a quest made of stages of objectives
+fn load_quest(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let stages = config.get_array("stages");
+
+    if name.is_err() || stages.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for quest type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = stages { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let name = name.unwrap();
+    let mut quest = Quest {
+        title: config.get_str("title").unwrap_or_else(|_| name.clone()),
+        description: config.get_str("description").unwrap_or_default(),
+        stages: vec![],
+        reward: QuestReward::default(),
+    };
+
+    for (index, value) in stages.unwrap().into_iter().enumerate() {
+        match parse_quest_stage(value) {
+            Ok(stage) => quest.stages.push(stage),
+            Err(err) => {
+                warn!("{} {} {} {} {}. {}",
+                      "[Asset Loading]",
+                      "Could not load stage",
+                      index + 1,
+                      "for quest type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                return false;
+            }
+        }
+    }
+
+    if quest.stages.is_empty() {
+        warn!("[Asset Loading] Quest in config file {} has no stages.",
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"));
+        return false;
+    }
+
+    //rewards are optional
+    if let Ok(reward) = config.get_table("reward") {
+        let parsed_reward = (|| -> Result<QuestReward, String> {
+            Ok( QuestReward {
+                items: if reward.contains_key("items") { table_value::<Vec<(String, u32)>>(&reward, "items")? } else { vec![] },
+                experience: if reward.contains_key("experience") { table_value::<u32>(&reward, "experience")? } else { 0 },
+                effects: flag_effects(&reward, "effects")?,
+            })
+        })();
+
+        match parsed_reward {
+            Ok(reward) => quest.reward = reward,
+            Err(err) => {
+                warn!("[Asset Loading] Quest reward could not be read in config file {}. {}",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+                return false;
+            }
+        }
+    }
+
+    asset_db.add_asset(name, AssetContainer::Quest(quest));
+    true
+}
+
+
+fn parse_quest_stage(value: Value) -> Result<QuestStage, String> {
+    let table = value.into_table().map_err(|err| err.to_string())?;
+
+    let objectives = table.get("objectives")
+                          .ok_or("Key 'objectives' is missing")?
+                          .clone().into_array().map_err(|err| format!("Bad objectives. {}", err))?
+                          .into_iter()
+                          .map(parse_quest_objective)
+                          .collect::<Result<Vec<_>, _>>()?;
+
+    if objectives.is_empty() {
+        return Err( "A stage needs at least one objective".to_string() );
+    }
+
+    Ok( QuestStage {
+        description: table_value::<String>(&table, "description").unwrap_or_default(),
+        objectives,
+    })
+}
+
+
+//each objective is a table with a single key naming it like event commands, "talk to: villager"
+fn parse_quest_objective(value: Value) -> Result<QuestObjective, String> {
+    let table = value.into_table().map_err(|err| err.to_string())?;
+
+    if table.len() != 1 {
+        return Err( "Each objective needs exactly one key naming it".to_string() );
+    }
+    let (objective, _) = table.iter().next().unwrap();
+
+    //objectives with settings use a table, the rest just give their value
+    let settings = table[objective].clone().into_table().unwrap_or_default();
+
+    match objective.as_str() {
+        "talk to" => Ok( QuestObjective::TalkTo(table_value::<String>(&table, objective)?) ),
+
+        //either just the name or the name and how many
+        "collect" => match table_value::<String>(&table, objective) {
+            Ok(item) => Ok( QuestObjective::Collect { item, count: 1 } ),
+            Err(_) => Ok( QuestObjective::Collect {
+                item: table_value::<String>(&settings, "item")?,
+                count: table_value::<u32>(&settings, "count").unwrap_or(1).max(1),
+            }),
+        },
+
+        "defeat" => match table_value::<String>(&table, objective) {
+            Ok(character) => Ok( QuestObjective::Defeat { character, count: 1 } ),
+            Err(_) => Ok( QuestObjective::Defeat {
+                character: table_value::<String>(&settings, "character")?,
+                count: table_value::<u32>(&settings, "count").unwrap_or(1).max(1),
+            }),
+        },
+
+        //either just the region or the region and the map it is on
+        "reach" => match table_value::<String>(&table, objective) {
+            Ok(region) => Ok( QuestObjective::Reach { region, map: None } ),
+            Err(_) => Ok( QuestObjective::Reach {
+                region: table_value::<String>(&settings, "region")?,
+                map: table_value::<String>(&settings, "map").ok(),
+            }),
+        },
+
+        _ => Err( format!("'{}' is not a supported objective", objective) ),
+    }
+}
+
+
+//loads a shop and the items it sells
+fn load_shop(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let stock = config.get_array("stock");
+
+    if name.is_err() || stock.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for shop type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = stock { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let name = name.unwrap();
+    let mut shop = Shop {
+        title: config.get_str("title").unwrap_or_else(|_| name.clone()),
+        stock: vec![],
+        buy_multiplier: config.get::<f32>("buy multiplier").unwrap_or(1.0).max(0.0),
+        sell_multiplier: config.get::<f32>("sell multiplier").unwrap_or(0.5).max(0.0),
+    };
+
+    //a broken entry is reported and skipped, the rest of the stock can still be sold
+    for (index, value) in stock.unwrap().into_iter().enumerate() {
+        match parse_shop_stock(value) {
+            Ok(stock) => shop.stock.push(stock),
+            Err(err) => {
+                warn!("{} {} {} {} {}. {}",
+                      "[Asset Loading]",
+                      "Could not load stock entry",
+                      index + 1,
+                      "for shop type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+            }
+        }
+    }
+
+    asset_db.add_asset(name, AssetContainer::Shop(shop));
+    true
+}
+
+
+//either just the item's name or the 'item' and how many the shop has, its 'limit'
+fn parse_shop_stock(value: Value) -> Result<ShopStock, String> {
+    if let Ok(item) = value.clone().into_str() {
+        return Ok( ShopStock { item, limit: None } );
+    }
+
+    let table = value.into_table().map_err(|err| err.to_string())?;
+    Ok( ShopStock {
+        item: table_value::<String>(&table, "item")?,
+        limit: match table.get("limit") {
+            Some(_) => Some( table_value::<u32>(&table, "limit")? ),
+            None => None,
+        },
+    })
+}
+
+
+//load music playlists
+fn load_music(config: &Config, config_path: &Path, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let tracks = config.get_array("tracks");
+
+    if name.is_err() || tracks.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for music type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = tracks { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let mut playlist = MusicPlaylist::new(name.unwrap(), vec![]);
+    if let Ok(crossfade) = config.get::<f32>("crossfade") {
+        playlist.crossfade = crossfade.max(0.0);
+    }
+
+    //a broken track is reported and skipped, the rest of the playlist can still play
+    for (index, value) in tracks.unwrap().into_iter().enumerate() {
+        match parse_music_track(value, config_path).and_then(check_music_track) {
+            Ok(track) => playlist.tracks.push(track),
+            Err(err) => {
+                warn!("{} {} {} {} {}. {}",
+                      "[Asset Loading]",
+                      "Could not load track",
+                      index + 1,
+                      "for music type in config file",
+                      config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                      err,
+                );
+            }
+        }
+    }
+
+    if playlist.tracks.is_empty() {
+        warn!("[Asset Loading] Music in config file {} has no tracks that can be played.",
+              config_path.to_str().unwrap_or("<error could not convert config path to str>"));
+        return false;
+    }
+
+    asset_db.add_asset(playlist.name.clone(), AssetContainer::Music(playlist));
+    true
+}
+
+
+//either just the file, relative to the config file, or the 'file' and the seconds its 'loop
+//  start' and 'loop end' are at
+fn parse_music_track(value: Value, config_path: &Path) -> Result<MusicTrack, String> {
+    let (file, table) = match value.clone().into_str() {
+        Ok(file) => (file, HashMap::new()),
+        Err(_) => {
+            let table = value.into_table().map_err(|err| err.to_string())?;
+            (table_value::<String>(&table, "file")?, table)
+        }
+    };
+
+    let path = resolve_relative_path(config_path, &file).ok_or("Could not resolve track path")?;
+    let mut track = MusicTrack::new( AudioClip::new(path, ClipCategory::Music) );
+
+    track.loop_start = match table.get("loop start") {
+        Some(_) => Some( table_value::<f32>(&table, "loop start")?.max(0.0) ),
+        None => None,
+    };
+    track.loop_end = match table.get("loop end") {
+        Some(_) => Some( table_value::<f32>(&table, "loop end")? ),
+        None => None,
+    };
+
+    match (track.loop_start, track.loop_end) {
+        (None, Some(_)) => Err( "A loop end needs a loop start".to_string() ),
+        (Some(start), Some(end)) if end <= start => Err( "The loop has to end after it starts".to_string() ),
+        _ => Ok(track),
+    }
+}
+
+//the track's file has to play and its loop has to start before the file ends
+fn check_music_track(track: MusicTrack) -> Result<MusicTrack, String> {
+    let duration = track.clip.validate().map_err(|err| err.to_string())?.as_secs_f32();
+
+    match track.loop_start {
+        Some(loop_start) if loop_start >= duration =>
+            Err( format!("The loop starts at {} seconds but the track is only {:.1} seconds long", loop_start, duration) ),
+        _ => Ok(track),
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_path_is_next_to_the_config() {
+        assert_eq!(resolve_relative_path(Path::new("campaign/maps/town.yml"), "town.tmx"),
+                   Some(PathBuf::from("campaign/maps/town.tmx")));
+    }
+
+    #[test]
+    fn relative_path_steps_out_of_folders() {
+        assert_eq!(resolve_relative_path(Path::new("campaign/maps/town.yml"), "./../images/./town.png"),
+                   Some(PathBuf::from("campaign/images/town.png")));
+    }
+
+    #[test]
+    fn relative_path_keeps_leading_parent_folders() {
+        assert_eq!(resolve_relative_path(Path::new("town.yml"), "../../shared/town.png"),
+                   Some(PathBuf::from("../../shared/town.png")));
+    }
+
+    #[test]
+    fn relative_path_needs_a_parent() {
+        assert_eq!(resolve_relative_path(Path::new(""), "town.png"), None);
+    }
+
+    fn command(yaml: &str) -> Result<EventCommand, String> {
+        let mut config = Config::new();
+        config.merge(File::from_str(yaml, FileFormat::Yaml)).unwrap();
+        parse_event_command(config.get::<Value>("command").unwrap(), Path::new("events/test.yml"))
+    }
+
+    #[test]
+    fn change_map_arrives_where_asked() {
+        let arrival = |yaml| match command(yaml) {
+            Ok(EventCommand::ChangeMap { map, arrival }) => { assert_eq!(map, "house"); arrival },
+            _ => panic!("should be a change map command"),
+        };
+
+        assert_eq!(arrival("command: { change map: house }"), MapArrival::MapSpawn);
+        assert_eq!(arrival("command: { change map: { map: house, position: [3, 4] } }"),
+                   MapArrival::Position(Point::new(3.0, 4.0)));
+        assert_eq!(arrival("command: { change map: { map: house, arrive at: front door } }"),
+                   MapArrival::Object("front door".to_string()));
+    }
+
+    #[test]
+    fn sounds_can_come_from_an_entity() {
+        let sound = |yaml| match command(yaml) {
+            Ok(EventCommand::PlaySound { clip, at }) => (clip, at),
+            _ => panic!("should be a play sound command"),
+        };
+
+        let clip: String = Path::new("events").join("door.ogg").to_str().unwrap().to_string();
+        assert_eq!(sound("command: { play sound: door.ogg }"), (clip.clone(), None));
+        assert_eq!(sound("command: { play sound: { sound: door.ogg, at: villager } }"),
+                   (clip, Some("villager".to_string())));
+    }
+
+    fn objective(yaml: &str) -> Result<QuestObjective, String> {
+        let mut config = Config::new();
+        config.merge(File::from_str(yaml, FileFormat::Yaml)).unwrap();
+        parse_quest_objective(config.get::<Value>("objective").unwrap())
+    }
+
+    #[test]
+    fn quest_objectives_take_a_name_or_settings() {
+        assert_eq!(objective("objective: { talk to: villager }"), Ok(QuestObjective::TalkTo("villager".to_string())));
+        assert_eq!(objective("objective: { collect: Potion }"),
+                   Ok(QuestObjective::Collect { item: "Potion".to_string(), count: 1 }));
+        assert_eq!(objective("objective: { defeat: { character: Slime, count: 3 } }"),
+                   Ok(QuestObjective::Defeat { character: "Slime".to_string(), count: 3 }));
+        assert_eq!(objective("objective: { reach: { region: old well, map: test map } }"),
+                   Ok(QuestObjective::Reach { region: "old well".to_string(), map: Some("test map".to_string()) }));
+        assert!(objective("objective: { fly to: moon }").is_err());
+    }
+
+    fn stock(yaml: &str) -> Result<ShopStock, String> {
+        let mut config = Config::new();
+        config.merge(File::from_str(yaml, FileFormat::Yaml)).unwrap();
+        parse_shop_stock(config.get::<Value>("stock").unwrap())
+    }
+
+    #[test]
+    fn shop_stock_can_be_limited() {
+        assert_eq!(stock("stock: Potion"), Ok(ShopStock { item: "Potion".to_string(), limit: None }));
+        assert_eq!(stock("stock: { item: Iron Sword, limit: 1 }"),
+                   Ok(ShopStock { item: "Iron Sword".to_string(), limit: Some(1) }));
+        assert!(stock("stock: { limit: 2 }").is_err());
+    }
+
+    fn music_track(yaml: &str) -> Result<(Option<f32>, Option<f32>), String> {
+        let mut config = Config::new();
+        config.merge(File::from_str(yaml, FileFormat::Yaml)).unwrap();
+        parse_music_track(config.get::<Value>("track").unwrap(), Path::new("music/town.yml"))
+            .map(|track| (track.loop_start, track.loop_end))
+    }
+
+    #[test]
+    fn music_tracks_can_loop_part_of_the_file() {
+        assert_eq!(music_track("track: town.ogg"), Ok((None, None)));
+        assert_eq!(music_track("track: { file: town.ogg, loop start: 4.5 }"), Ok((Some(4.5), None)));
+        assert_eq!(music_track("track: { file: town.ogg, loop start: 4.5, loop end: 60 }"), Ok((Some(4.5), Some(60.0))));
+        assert!(music_track("track: { file: town.ogg, loop end: 60 }").is_err());
+        assert!(music_track("track: { file: town.ogg, loop start: 10, loop end: 5 }").is_err());
+    }
+}
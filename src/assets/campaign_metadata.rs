@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use coffee::graphics::Image;
+
+
+//Describes a campaign so the player can pick it before any of its assets are loaded.
+//  Built from the campaign.yml file at the root of each campaign folder.
+pub struct CampaignMetadata {
+    pub path: PathBuf, //folder the campaign.yml file was found in
+    pub name: String,
+    pub title_image: Option<Image>,
+    pub description: String,
+    pub version: String,
+    pub author: String,
+    pub start_map: Option<String>, //name of the tilemap asset the player starts on
+    pub starting_items: Vec<(String, u32)>, //item names and how many the party starts with
+    pub starting_gold: u32, //gold the party starts with
+    pub equipment_slots: Vec<String>, //places characters can wear equipment, like weapon or armor
+    pub starting_party: Vec<String>, //entity prefabs that join the player's character in the party
+    pub battle_music: Option<String>, //name of the music asset for battles that don't have their own
+    pub menu_music: Option<String>, //name of the music asset played while a menu is open over the map
+}
+
+impl CampaignMetadata {
+
+    //path handed to load_campaign_data when the campaign is started
+    pub fn path_str(&self) -> &str {
+        self.path.to_str().unwrap_or("")
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use super::SpritePos;
+
+
+//The numbers that decide how well someone fights.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Stats {
+    pub max_hp: u32,
+    pub max_mp: u32,
+    pub attack: u32,
+    pub defense: u32,
+    pub magic: u32,
+    pub speed: u32,
+}
+
+
+//Changes made to stats by something like a piece of equipment. Can be negative.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct StatModifiers {
+    pub max_hp: i32,
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub magic: i32,
+    pub speed: i32,
+}
+
+impl StatModifiers {
+
+    pub fn add(&self, other: &StatModifiers) -> StatModifiers {
+        StatModifiers {
+            max_hp: self.max_hp + other.max_hp,
+            max_mp: self.max_mp + other.max_mp,
+            attack: self.attack + other.attack,
+            defense: self.defense + other.defense,
+            magic: self.magic + other.magic,
+            speed: self.speed + other.speed,
+        }
+    }
+
+    //how much better or worse these modifiers are than others
+    pub fn compared_to(&self, other: &StatModifiers) -> StatModifiers {
+        StatModifiers {
+            max_hp: self.max_hp - other.max_hp,
+            max_mp: self.max_mp - other.max_mp,
+            attack: self.attack - other.attack,
+            defense: self.defense - other.defense,
+            magic: self.magic - other.magic,
+            speed: self.speed - other.speed,
+        }
+    }
+
+    //stats after the modifiers. Nothing goes below 0 and max hp stays at least 1.
+    pub fn apply_to(&self, stats: Stats) -> Stats {
+        let modify = |stat: u32, modifier: i32| (stat as i64 + modifier as i64).max(0) as u32;
+
+        Stats {
+            max_hp: modify(stats.max_hp, self.max_hp).max(1),
+            max_mp: modify(stats.max_mp, self.max_mp),
+            attack: modify(stats.attack, self.attack),
+            defense: modify(stats.defense, self.defense),
+            magic: modify(stats.magic, self.magic),
+            speed: modify(stats.speed, self.speed),
+        }
+    }
+
+    //short summary like "Attack +4, Speed -1" for menus
+    pub fn describe(&self) -> String {
+        let modifiers = [("HP", self.max_hp), ("MP", self.max_mp), ("Attack", self.attack),
+                         ("Defense", self.defense), ("Magic", self.magic), ("Speed", self.speed)];
+
+        modifiers.iter()
+            .filter(|(_, value)| *value != 0)
+            .map(|(stat, value)| format!("{} {:+}", stat, value))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+
+//How much each stat grows per level gained. Fractions add up over several levels.
+#[derive(Clone, Copy, Default)]
+pub struct StatGrowth {
+    pub max_hp: f32,
+    pub max_mp: f32,
+    pub attack: f32,
+    pub defense: f32,
+    pub magic: f32,
+    pub speed: f32,
+}
+
+
+//A kind of character, like a fighter or a slime. Decides stats at every level and how much
+//  experience each level takes.
+#[derive(Clone)]
+pub struct CharacterClass {
+    pub level_one: Stats, //stats at level 1
+    pub growth: StatGrowth,
+    pub growth_curve: f32, //exponent on the levels gained. 1.0 grows evenly, higher grows faster later on
+    pub experience_table: Vec<u32>, //total experience needed to reach level 2, 3, ... and so on
+    pub max_level: u32,
+}
+
+impl CharacterClass {
+
+    pub fn stats_at(&self, level: u32) -> Stats {
+        let gained = (level.clamp(1, self.max_level.max(1)) - 1) as f32;
+        let scale = gained.powf(self.growth_curve);
+        let grow = |base: u32, growth: f32| base + (growth * scale).round().max(0.0) as u32;
+
+        Stats {
+            max_hp: grow(self.level_one.max_hp, self.growth.max_hp),
+            max_mp: grow(self.level_one.max_mp, self.growth.max_mp),
+            attack: grow(self.level_one.attack, self.growth.attack),
+            defense: grow(self.level_one.defense, self.growth.defense),
+            magic: grow(self.level_one.magic, self.growth.magic),
+            speed: grow(self.level_one.speed, self.growth.speed),
+        }
+    }
+
+    //experience table worked out from a curve instead of listed level by level. Reaching level
+    //  n takes base * (n - 1) ^ exponent experience in total.
+    pub fn experience_curve(base: f32, exponent: f32, max_level: u32) -> Vec<u32> {
+        (2..=max_level).map(|level| (base * ((level - 1) as f32).powf(exponent)).round() as u32).collect()
+    }
+
+    //total experience needed to reach a level, None if the level can't be reached
+    pub fn experience_for_level(&self, level: u32) -> Option<u32> {
+        if level <= 1 { return Some(0); }
+        if level > self.max_level { return None; }
+
+        self.experience_table.get(level as usize - 2).copied()
+    }
+
+    //highest level a total amount of experience reaches
+    pub fn level_for_experience(&self, experience: u32) -> u32 {
+        let mut level = 1;
+        while let Some(needed) = self.experience_for_level(level + 1) {
+            if experience < needed { break; }
+            level += 1;
+        }
+        level
+    }
+}
+
+
+//Someone a campaign puts in the party or in an encounter, described by their class and level.
+#[derive(Clone)]
+pub struct Character {
+    pub name: String,
+    pub class: String, //name of the class asset
+    pub level: u32,
+    pub sprite: Option<(String, SpritePos)>, //sprite sheet name and sprite shown in battles and menus
+    pub experience_reward: u32, //given to the party when beaten
+    pub loot: Vec<String>, //names of items dropped when beaten
+    pub equipment: HashMap<String, String>, //item worn in each equipment slot from the start
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(experience_table: Vec<u32>, max_level: u32) -> CharacterClass {
+        CharacterClass {
+            level_one: Stats { max_hp: 20, max_mp: 5, attack: 4, defense: 3, magic: 2, speed: 5 },
+            growth: StatGrowth { max_hp: 5.0, max_mp: 1.5, attack: 1.0, defense: 0.5, magic: 0.0, speed: 1.0 },
+            growth_curve: 1.0,
+            experience_table,
+            max_level,
+        }
+    }
+
+    #[test]
+    fn level_one_stats_are_unchanged() {
+        let class = class(vec![10, 30], 3);
+        assert_eq!(class.stats_at(1), class.level_one);
+        assert_eq!(class.stats_at(0), class.level_one);
+    }
+
+    #[test]
+    fn stats_grow_per_level_and_stop_at_max_level() {
+        let class = class(vec![10, 30], 3);
+
+        assert_eq!(class.stats_at(3), Stats { max_hp: 30, max_mp: 8, attack: 6, defense: 4, magic: 2, speed: 7 });
+        assert_eq!(class.stats_at(50), class.stats_at(3));
+    }
+
+    #[test]
+    fn growth_curve_makes_later_levels_grow_faster() {
+        let mut class = class(vec![], 10);
+        class.growth_curve = 2.0;
+
+        assert_eq!(class.stats_at(2).max_hp, 25);
+        assert_eq!(class.stats_at(4).max_hp, 20 + 5 * 9);
+    }
+
+    #[test]
+    fn level_comes_from_the_experience_table() {
+        let class = class(vec![10, 30, 60], 4);
+
+        assert_eq!(class.level_for_experience(0), 1);
+        assert_eq!(class.level_for_experience(9), 1);
+        assert_eq!(class.level_for_experience(10), 2);
+        assert_eq!(class.level_for_experience(59), 3);
+        assert_eq!(class.level_for_experience(60), 4);
+    }
+
+    #[test]
+    fn level_comes_from_the_experience_curve() {
+        let table = CharacterClass::experience_curve(10.0, 2.0, 4);
+        assert_eq!(table, vec![10, 40, 90]);
+
+        let class = class(table, 4);
+        assert_eq!(class.level_for_experience(39), 2);
+        assert_eq!(class.level_for_experience(40), 3);
+    }
+
+    #[test]
+    fn level_is_capped_at_max_level() {
+        //entries past the max level are never used
+        let class = class(vec![10, 30, 60], 3);
+
+        assert_eq!(class.level_for_experience(1_000_000), 3);
+        assert_eq!(class.experience_for_level(4), None);
+    }
+
+    #[test]
+    fn short_experience_table_caps_the_level() {
+        let class = class(vec![10], 99);
+        assert_eq!(class.level_for_experience(1_000_000), 2);
+    }
+}
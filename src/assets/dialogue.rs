@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use super::{SpritePos, Expression, FlagEffect};
+
+
+//picture of the speaker shown next to the text
+pub struct Portrait {
+    pub sprite_sheet_name: String,
+    pub sprite_location: SpritePos,
+}
+
+
+//An answer the player can give. Hidden unless its conditions are met.
+pub struct DialogueChoice {
+    pub text: String,
+    pub next: Option<String>, //node to go to, None ends the conversation
+    pub conditions: Vec<Expression>,
+    pub effects: Vec<FlagEffect>,
+}
+
+
+//One line of a conversation. When its conditions aren't met the conversation moves on to the
+//  'otherwise' node instead, which lets a conversation change based on what already happened.
+pub struct DialogueNode {
+    pub speaker: String,
+    pub text: String,
+    pub portrait: Option<Portrait>,
+    pub choices: Vec<DialogueChoice>,
+    pub next: Option<String>, //used when there are no choices, None ends the conversation
+    pub conditions: Vec<Expression>,
+    pub otherwise: Option<String>,
+    pub effects: Vec<FlagEffect>, //applied when the node is shown
+}
+
+
+//A branching conversation made of nodes that link to each other by name.
+pub struct Dialogue {
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+impl Dialogue {
+
+    pub fn new(start: String) -> Dialogue {
+        Dialogue {
+            start,
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, id: String, node: DialogueNode) {
+        self.nodes.insert(id, node);
+    }
+
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.get(id)
+    }
+}
@@ -0,0 +1,14 @@
+//An enemy in an encounter. The level falls back to the character's own level when not given.
+#[derive(Clone)]
+pub struct EncounterEnemy {
+    pub character: String, //name of the character asset
+    pub level: Option<u32>,
+}
+
+
+//A group of enemies the party fights together in one battle.
+pub struct Encounter {
+    pub enemies: Vec<EncounterEnemy>,
+    pub can_flee: bool,
+    pub music: Option<String>, //name of the music asset, the campaign's battle music when not given
+}
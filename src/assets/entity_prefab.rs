@@ -0,0 +1,38 @@
+use coffee::graphics::{Point, Vector};
+
+use super::{SpritePos, TriggerCondition};
+
+
+//Data for one component of a prefab. Mirrors the components in the ecs module without
+//  depending on them so assets stay independent of the world.
+#[derive(Clone)]
+pub enum PrefabComponent {
+    Position { map_pos: Point },
+    Visual { sprite_sheet_name: String, sprite_location: SpritePos },
+    Animation { frame_duration: f32 }, //seconds
+    PlayerControl { speed: f32 }, //pixels per second
+    Movement,
+    Interaction { dialogue: String }, //name of the dialogue started when the player interacts
+    Character { character: String, level: Option<u32> }, //character asset giving the entity stats
+    Collider { offset: Vector, width: f32, height: f32 },
+    Trigger { condition: TriggerCondition, event: String, size: (f32, f32) }, //event is the script to run
+}
+
+
+//A description of an entity that can be spawned into the world any number of times.
+pub struct EntityPrefab {
+    pub components: Vec<PrefabComponent>,
+}
+
+impl EntityPrefab {
+
+    pub fn new() -> EntityPrefab {
+        EntityPrefab {
+            components: vec![],
+        }
+    }
+
+    pub fn add_component(&mut self, component: PrefabComponent) {
+        self.components.push(component);
+    }
+}
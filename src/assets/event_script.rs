@@ -0,0 +1,82 @@
+use coffee::graphics::Point;
+
+use super::{Expression, FlagEffect};
+
+
+//One step of an event script. Entities are picked out by name, "leader" is the party leader.
+#[derive(Clone)]
+pub enum EventCommand {
+    ShowDialogue(String), //name of the dialogue asset, the script waits until it ends
+    MoveEntity { entity: String, to: Point, speed: f32 }, //speed in pixels per second
+    SetFlag(FlagEffect),
+    GiveItem { item: String, count: u32 },
+    StartBattle(String), //name of the encounter asset
+    ChangeMap { map: String, arrival: MapArrival },
+    PlaySound { clip: String, at: Option<String> }, //name of the audio clip asset and the entity it comes from
+    Wait(f32), //seconds
+    RunScript(String), //name of the script asset, what it asks for runs before the next command
+    StartQuest(String), //name of the quest asset
+    OpenShop(String), //name of the shop asset, the script waits until the shop is closed
+}
+
+
+//Where the party ends up on a map it moves to.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MapArrival {
+    MapSpawn, //the new map's player spawn, the party keeps its place when it has none
+    Position(Point),
+    Object(String), //the position of the named object on the new map, like the door back out
+}
+
+
+//What makes a trigger start its event script.
+#[derive(Clone)]
+pub enum TriggerCondition {
+    Enter, //the party leader walks into the trigger's area
+    Interact, //the player interacts with the trigger like they would talk to someone
+    MapLoad, //the map the trigger is on is loaded
+    FlagChange(Expression), //the condition becomes true when a flag changes
+}
+
+impl TriggerCondition {
+
+    //written as "enter", "interact", "map load" or "flag change", the last one also needs a
+    //  condition on flags like "met villager" or "gold >= 50"
+    pub fn parse(on: &str, flag: Option<&str>) -> Result<TriggerCondition, String> {
+        match on.trim().to_lowercase().as_str() {
+            "enter" => Ok(TriggerCondition::Enter),
+            "interact" => Ok(TriggerCondition::Interact),
+            "map load" => Ok(TriggerCondition::MapLoad),
+            "flag change" => match flag {
+                Some(flag) => Ok( TriggerCondition::FlagChange(Expression::parse(flag)?) ),
+                None => Err( "Flag change triggers need a 'flag'".to_string() ),
+            },
+            other => Err( format!("'{}' is not a trigger condition", other) ),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TriggerCondition::Enter => "enter",
+            TriggerCondition::Interact => "interact",
+            TriggerCondition::MapLoad => "map load",
+            TriggerCondition::FlagChange(_) => "flag change",
+        }
+    }
+}
+
+
+//A list of commands run one after another when a trigger goes off. Scripts marked 'once' only
+//  ever run a single time, which is remembered with a flag so it survives saving.
+pub struct EventScript {
+    pub commands: Vec<EventCommand>,
+    pub once: bool,
+}
+
+impl EventScript {
+
+    //flag set once a 'once' script has finished
+    pub fn done_flag(script_name: &str) -> String {
+        format!("event done: {}", script_name)
+    }
+}
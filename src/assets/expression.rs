@@ -0,0 +1,576 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+
+//guards against expressions nested so deep that parsing them would overflow the stack
+const MAX_DEPTH: usize = 64;
+
+
+//A value kept in the game's flags. Flags that were never set read as false.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Variable {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+impl Variable {
+
+    //false, 0 and empty text are false, everything else is true
+    pub fn is_true(&self) -> bool {
+        match self {
+            Variable::Bool(value) => *value,
+            Variable::Int(value) => *value != 0,
+            Variable::Text(value) => !value.is_empty(),
+        }
+    }
+
+    //true and false count as 1 and 0 so flags can be added up
+    fn as_int(&self) -> Result<i64, String> {
+        match self {
+            Variable::Bool(value) => Ok(i64::from(*value)),
+            Variable::Int(value) => Ok(*value),
+            Variable::Text(value) => Err( format!("\"{}\" is not a number", value) ),
+        }
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Variable::Bool(value) => write!(f, "{}", value),
+            Variable::Int(value) => write!(f, "{}", value),
+            Variable::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<bool> for Variable {
+    fn from(value: bool) -> Variable { Variable::Bool(value) }
+}
+
+impl From<i64> for Variable {
+    fn from(value: i64) -> Variable { Variable::Int(value) }
+}
+
+impl From<&str> for Variable {
+    fn from(value: &str) -> Variable { Variable::Text(value.to_string()) }
+}
+
+
+//What expressions can look up about the game while they are worked out.
+pub trait ExpressionScope {
+    fn variable(&self, name: &str) -> Option<Variable>;
+    fn item_count(&self, item: &str) -> u32;
+}
+
+
+//A condition or value written in yaml, like "met villager && !has_item(old key)" or
+//  "gold >= 50". Names stand for flags and may contain spaces. Supports && || ! == != < <= > >=
+//  + - * / and parentheses, numbers, "text", true and false, and the functions has_item(item)
+//  and item_count(item).
+#[derive(Clone, Debug)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Value(Variable),
+    Flag(String),
+    Call(Function, String),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Function {
+    HasItem,
+    ItemCount,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    And, Or,
+    Equal, NotEqual, Less, LessEqual, Greater, GreaterEqual,
+    Add, Subtract, Multiply, Divide,
+}
+
+impl Expression {
+
+    pub fn parse(text: &str) -> Result<Expression, String> {
+        let tokens = tokenize(text).map_err(|err| format!("Could not read \"{}\". {}", text, err))?;
+
+        let mut parser = Parser { tokens, position: 0, depth: 0 };
+        let root = parser.or().map_err(|err| format!("Could not read \"{}\". {}", text, err))?;
+
+        match parser.tokens.get(parser.position) {
+            None => Ok( Expression { source: text.trim().to_string(), root } ),
+            Some(token) => Err( format!("Could not read \"{}\". Did not expect {} there", text, token) ),
+        }
+    }
+
+    //a fixed value, used for effects like "!flag" that don't need parsing
+    pub fn value(value: Variable) -> Expression {
+        Expression {
+            source: value.to_string(),
+            root: Node::Value(value),
+        }
+    }
+
+    //the text the expression was read from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn evaluate(&self, scope: &dyn ExpressionScope) -> Result<Variable, String> {
+        evaluate(&self.root, scope)
+    }
+
+    //whether the expression works out true. One that can't be worked out, like comparing text
+    //  to a number, is logged and counts as false.
+    pub fn holds(&self, scope: &dyn ExpressionScope) -> bool {
+        match self.evaluate(scope) {
+            Ok(value) => value.is_true(),
+            Err(err) => {
+                warn!("[Flags] Condition \"{}\" could not be checked. {}.", self.source, err);
+                false
+            }
+        }
+    }
+}
+
+
+//A change made to a flag once something happens. Written as "flag" to set it, "!flag" to clear
+//  it, or "flag = expression" to give it a value, like "gold = gold - 50".
+#[derive(Clone, Debug)]
+pub struct FlagEffect {
+    pub flag: String,
+    pub value: Expression,
+}
+
+impl FlagEffect {
+
+    pub fn parse(text: &str) -> Result<FlagEffect, String> {
+        let text = text.trim();
+
+        let (flag, value) = match assignment(text) {
+            Some(split) => (text[..split].trim(), Expression::parse(&text[split + 1..])?),
+            None => match text.strip_prefix('!') {
+                Some(flag) => (flag.trim(), Expression::value(Variable::Bool(false))),
+                None => (text, Expression::value(Variable::Bool(true))),
+            },
+        };
+
+        if flag.is_empty() {
+            return Err( format!("\"{}\" does not name a flag to change", text) );
+        }
+
+        Ok( FlagEffect { flag: flag.to_string(), value } )
+    }
+}
+
+//where the single '=' of "flag = value" is, as opposed to one that is part of == != <= >=
+fn assignment(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).find(|&index| {
+        bytes[index] == b'='
+            && bytes.get(index + 1) != Some(&b'=')
+            && (index == 0 || !b"=!<>".contains(&bytes[index - 1]))
+    })
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    Text(String),
+    Name(String),
+    Open, Close, Comma,
+    Not,
+    Operator(Operator),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+            Token::Name(name) => write!(f, "'{}'", name),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+            Token::Not => write!(f, "'!'"),
+            Token::Operator(operator) => write!(f, "'{}'", match operator {
+                Operator::And => "&&", Operator::Or => "||",
+                Operator::Equal => "==", Operator::NotEqual => "!=",
+                Operator::Less => "<", Operator::LessEqual => "<=",
+                Operator::Greater => ">", Operator::GreaterEqual => ">=",
+                Operator::Add => "+", Operator::Subtract => "-",
+                Operator::Multiply => "*", Operator::Divide => "/",
+            }),
+        }
+    }
+}
+
+
+//splits an expression into tokens. Names run on through spaces so flags like "met villager"
+//  can be written as they are.
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        let (token, length) = match (c, next) {
+            (' ', _) | ('\t', _) => { index += 1; continue; },
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            (',', _) => (Token::Comma, 1),
+            ('&', Some('&')) => (Token::Operator(Operator::And), 2),
+            ('|', Some('|')) => (Token::Operator(Operator::Or), 2),
+            ('=', Some('=')) => (Token::Operator(Operator::Equal), 2),
+            ('!', Some('=')) => (Token::Operator(Operator::NotEqual), 2),
+            ('<', Some('=')) => (Token::Operator(Operator::LessEqual), 2),
+            ('>', Some('=')) => (Token::Operator(Operator::GreaterEqual), 2),
+            ('!', _) => (Token::Not, 1),
+            ('<', _) => (Token::Operator(Operator::Less), 1),
+            ('>', _) => (Token::Operator(Operator::Greater), 1),
+            ('+', _) => (Token::Operator(Operator::Add), 1),
+            ('-', _) => (Token::Operator(Operator::Subtract), 1),
+            ('*', _) => (Token::Operator(Operator::Multiply), 1),
+            ('/', _) => (Token::Operator(Operator::Divide), 1),
+
+            ('"', _) => {
+                let end = chars[index + 1..].iter().position(|c| *c == '"')
+                                            .ok_or("Text is missing its closing quote")?;
+                let text: String = chars[index + 1..index + 1 + end].iter().collect();
+                (Token::Text(text), end + 2)
+            },
+
+            (c, _) if c.is_ascii_digit() => {
+                let length = chars[index..].iter().take_while(|c| c.is_ascii_digit()).count();
+                let digits: String = chars[index..index + length].iter().collect();
+                let number = digits.parse::<i64>().map_err(|_| format!("{} is too big", digits))?;
+                (Token::Number(number), length)
+            },
+
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let length = chars[index..].iter()
+                                 .take_while(|c| c.is_alphanumeric() || ['_', ' ', ':', '\'', '.'].contains(c))
+                                 .count();
+                let name: String = chars[index..index + length].iter().collect();
+                (Token::Name(name.trim_end().to_string()), length)
+            },
+
+            (c, _) => return Err( format!("Did not expect '{}'", c) ),
+        };
+
+        tokens.push(token);
+        index += length;
+    }
+
+    Ok(tokens)
+}
+
+
+//recursive descent parser, each level binds tighter than the one before it
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    //parses operators of one level, left to right
+    fn binary(&mut self, operators: &[Operator], operand: fn(&mut Parser) -> Result<Node, String>) -> Result<Node, String> {
+        let mut node = operand(self)?;
+
+        while let Some(Token::Operator(operator)) = self.peek().cloned() {
+            if !operators.contains(&operator) { break; }
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(operand(self)?));
+        }
+
+        Ok(node)
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH { return Err( "It is nested too deeply".to_string() ); }
+
+        let node = self.binary(&[Operator::Or], Parser::and);
+        self.depth -= 1;
+        node
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        self.binary(&[Operator::And], Parser::comparison)
+    }
+
+    //comparisons don't chain, "a < b < c" is an error
+    fn comparison(&mut self) -> Result<Node, String> {
+        let left = self.sum()?;
+
+        match self.peek().cloned() {
+            Some(Token::Operator(operator)) if [Operator::Equal, Operator::NotEqual, Operator::Less, Operator::LessEqual,
+                                                Operator::Greater, Operator::GreaterEqual].contains(&operator) => {
+                self.position += 1;
+                Ok( Node::Binary(operator, Box::new(left), Box::new(self.sum()?)) )
+            },
+            _ => Ok(left),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        self.binary(&[Operator::Add, Operator::Subtract], Parser::product)
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        self.binary(&[Operator::Multiply, Operator::Divide], Parser::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        match self.peek() {
+            Some(Token::Not) => { self.position += 1; Ok( Node::Not(Box::new(self.nested(Parser::unary)?)) ) },
+            Some(Token::Operator(Operator::Subtract)) => {
+                self.position += 1;
+                Ok( Node::Negate(Box::new(self.nested(Parser::unary)?)) )
+            },
+            _ => self.primary(),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Parser) -> Result<Node, String>) -> Result<Node, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH { return Err( "It is nested too deeply".to_string() ); }
+
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok( Node::Value(Variable::Int(number)) ),
+            Some(Token::Text(text)) => Ok( Node::Value(Variable::Text(text)) ),
+
+            Some(Token::Open) => {
+                let node = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(node),
+                    _ => Err( "A '(' is missing its ')'".to_string() ),
+                }
+            },
+
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" => Ok( Node::Value(Variable::Bool(true)) ),
+                "false" => Ok( Node::Value(Variable::Bool(false)) ),
+                _ if self.peek() == Some(&Token::Open) => self.call(&name),
+                _ => Ok( Node::Flag(name) ),
+            },
+
+            Some(token) => Err( format!("Did not expect {} there", token) ),
+            None => Err( "It ends too early".to_string() ),
+        }
+    }
+
+    //functions take a single name, written plainly or in quotes
+    fn call(&mut self, name: &str) -> Result<Node, String> {
+        let function = match name {
+            "has_item" => Function::HasItem,
+            "item_count" => Function::ItemCount,
+            _ => return Err( format!("'{}' is not a function", name) ),
+        };
+
+        self.position += 1;
+        let argument = match self.next() {
+            Some(Token::Name(argument)) | Some(Token::Text(argument)) => argument,
+            _ => return Err( format!("{} needs the name of an item", name) ),
+        };
+
+        match self.next() {
+            Some(Token::Close) => Ok( Node::Call(function, argument) ),
+            _ => Err( format!("{} takes a single name followed by ')'", name) ),
+        }
+    }
+}
+
+
+fn evaluate(node: &Node, scope: &dyn ExpressionScope) -> Result<Variable, String> {
+    match node {
+        Node::Value(value) => Ok(value.clone()),
+        Node::Flag(name) => Ok( scope.variable(name).unwrap_or(Variable::Bool(false)) ),
+
+        Node::Call(Function::HasItem, item) => Ok( Variable::Bool(scope.item_count(item) > 0) ),
+        Node::Call(Function::ItemCount, item) => Ok( Variable::Int(i64::from(scope.item_count(item))) ),
+
+        Node::Not(inner) => Ok( Variable::Bool(!evaluate(inner, scope)?.is_true()) ),
+        Node::Negate(inner) => evaluate(inner, scope)?.as_int()?
+                                   .checked_neg().map(Variable::Int).ok_or_else(|| "The number is too big".to_string()),
+
+        //the right side is only looked at when it matters
+        Node::Binary(Operator::And, left, right) =>
+            Ok( Variable::Bool(evaluate(left, scope)?.is_true() && evaluate(right, scope)?.is_true()) ),
+        Node::Binary(Operator::Or, left, right) =>
+            Ok( Variable::Bool(evaluate(left, scope)?.is_true() || evaluate(right, scope)?.is_true()) ),
+
+        Node::Binary(operator, left, right) => {
+            let left = evaluate(left, scope)?;
+            let right = evaluate(right, scope)?;
+            apply_operator(*operator, &left, &right)
+        },
+    }
+}
+
+fn apply_operator(operator: Operator, left: &Variable, right: &Variable) -> Result<Variable, String> {
+    use std::cmp::Ordering;
+
+    //text only compares and joins with other text
+    let ordering = match (left, right) {
+        (Variable::Text(left), Variable::Text(right)) => left.cmp(right),
+        (Variable::Text(_), _) | (_, Variable::Text(_)) => match operator {
+            Operator::Equal => return Ok( Variable::Bool(false) ),
+            Operator::NotEqual => return Ok( Variable::Bool(true) ),
+            Operator::Add => return Ok( Variable::Text(format!("{}{}", left, right)) ),
+            _ => return Err( format!("Can't use text and a number together like that ({} and {})", left, right) ),
+        },
+        _ => left.as_int()?.cmp(&right.as_int()?),
+    };
+
+    let too_big = || "The number is too big".to_string();
+
+    match operator {
+        Operator::Equal => Ok( Variable::Bool(ordering == Ordering::Equal) ),
+        Operator::NotEqual => Ok( Variable::Bool(ordering != Ordering::Equal) ),
+        Operator::Less => Ok( Variable::Bool(ordering == Ordering::Less) ),
+        Operator::LessEqual => Ok( Variable::Bool(ordering != Ordering::Greater) ),
+        Operator::Greater => Ok( Variable::Bool(ordering == Ordering::Greater) ),
+        Operator::GreaterEqual => Ok( Variable::Bool(ordering != Ordering::Less) ),
+
+        Operator::Add => match (left, right) {
+            (Variable::Text(left), Variable::Text(right)) => Ok( Variable::Text(format!("{}{}", left, right)) ),
+            _ => left.as_int()?.checked_add(right.as_int()?).map(Variable::Int).ok_or_else(too_big),
+        },
+        Operator::Subtract => left.as_int()?.checked_sub(right.as_int()?).map(Variable::Int).ok_or_else(too_big),
+        Operator::Multiply => left.as_int()?.checked_mul(right.as_int()?).map(Variable::Int).ok_or_else(too_big),
+        Operator::Divide => match right.as_int()? {
+            0 => Err( "Can't divide by zero".to_string() ),
+            divisor => left.as_int()?.checked_div(divisor).map(Variable::Int).ok_or_else(too_big),
+        },
+
+        Operator::And | Operator::Or => unreachable!("handled before both sides are worked out"),
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct TestScope {
+        flags: HashMap<String, Variable>,
+        items: HashMap<String, u32>,
+    }
+
+    impl ExpressionScope for TestScope {
+        fn variable(&self, name: &str) -> Option<Variable> { self.flags.get(name).cloned() }
+        fn item_count(&self, item: &str) -> u32 { self.items.get(item).copied().unwrap_or(0) }
+    }
+
+    fn scope() -> TestScope {
+        let flags = vec![("met villager", Variable::Bool(true)), ("gold", Variable::Int(75)),
+                         ("hero", Variable::Text("Sara".to_string()))];
+        TestScope {
+            flags: flags.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+            items: vec![("potion".to_string(), 2), ("old key".to_string(), 1)].into_iter().collect(),
+        }
+    }
+
+    fn check(text: &str) -> Variable {
+        Expression::parse(text).unwrap().evaluate(&scope()).unwrap()
+    }
+
+    #[test]
+    fn flags_with_spaces_and_not() {
+        assert_eq!(check("met villager"), Variable::Bool(true));
+        assert_eq!(check("!met villager"), Variable::Bool(false));
+        assert_eq!(check("never set"), Variable::Bool(false));
+        assert_eq!(check("!never set && met villager"), Variable::Bool(true));
+    }
+
+    #[test]
+    fn items_and_numbers() {
+        assert_eq!(check("has_item(potion) && gold >= 50"), Variable::Bool(true));
+        assert_eq!(check("has_item(\"old key\") && gold >= 100"), Variable::Bool(false));
+        assert_eq!(check("has_item(ether) || item_count(old key) == 1"), Variable::Bool(true));
+        assert_eq!(check("gold - 25 * 2"), Variable::Int(25));
+        assert_eq!(check("(gold - 25) * 2"), Variable::Int(100));
+        assert_eq!(check("-gold / 2"), Variable::Int(-37));
+    }
+
+    #[test]
+    fn text_compares_with_text() {
+        assert_eq!(check("hero == \"Sara\""), Variable::Bool(true));
+        assert_eq!(check("hero != 3"), Variable::Bool(true));
+        assert_eq!(check("\"Lady \" + hero"), Variable::Text("Lady Sara".to_string()));
+        assert!(Expression::parse("hero > 3").unwrap().evaluate(&scope()).is_err());
+        assert!(!Expression::parse("hero > 3").unwrap().holds(&scope()));
+    }
+
+    #[test]
+    fn bad_expressions_are_rejected() {
+        for text in ["", "gold >=", "(met villager", "gold \"five\"", "teleport(home)", "has_item()", "a < b < c", "\"open",
+                     "gold @ 5"].iter() {
+            assert!(Expression::parse(text).is_err(), "{} should not parse", text);
+        }
+        assert!(Expression::parse(&"(".repeat(1000)).is_err());
+        assert!(Expression::parse("1 / 0").unwrap().evaluate(&scope()).is_err());
+    }
+
+    #[test]
+    fn effects_set_clear_and_assign() {
+        let effect = FlagEffect::parse("met villager").unwrap();
+        assert_eq!((effect.flag.as_str(), effect.value.evaluate(&scope()).unwrap()), ("met villager", Variable::Bool(true)));
+
+        let effect = FlagEffect::parse("!met villager").unwrap();
+        assert_eq!((effect.flag.as_str(), effect.value.evaluate(&scope()).unwrap()), ("met villager", Variable::Bool(false)));
+
+        let effect = FlagEffect::parse("gold = gold - 50").unwrap();
+        assert_eq!((effect.flag.as_str(), effect.value.evaluate(&scope()).unwrap()), ("gold", Variable::Int(25)));
+
+        let effect = FlagEffect::parse("rich = gold >= 50").unwrap();
+        assert_eq!(effect.value.evaluate(&scope()).unwrap(), Variable::Bool(true));
+
+        assert!(FlagEffect::parse("= 5").is_err());
+        assert!(FlagEffect::parse("").is_err());
+    }
+
+    #[test]
+    fn saved_values_keep_their_type() {
+        let values: HashMap<String, Variable> = serde_json::from_str(r#"{"a": true, "b": 12, "c": "text"}"#).unwrap();
+        assert_eq!(values["a"], Variable::Bool(true));
+        assert_eq!(values["b"], Variable::Int(12));
+        assert_eq!(values["c"], Variable::Text("text".to_string()));
+    }
+}
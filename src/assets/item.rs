@@ -0,0 +1,80 @@
+use super::{SpritePos, StatModifiers};
+
+
+//What kind of thing an item is. Decides where it can be used.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ItemCategory {
+    Consumable, //used up when used
+    KeyItem, //needed by the story, can't be used up or sold
+    Equipment,
+    Material,
+}
+
+impl ItemCategory {
+
+    pub fn parse(category: &str) -> Option<ItemCategory> {
+        match category {
+            "consumable" => Some(ItemCategory::Consumable),
+            "key item" => Some(ItemCategory::KeyItem),
+            "equipment" => Some(ItemCategory::Equipment),
+            "material" => Some(ItemCategory::Material),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ItemCategory::Consumable => "Consumable",
+            ItemCategory::KeyItem => "Key Item",
+            ItemCategory::Equipment => "Equipment",
+            ItemCategory::Material => "Material",
+        }
+    }
+}
+
+
+//What happens to the character an item is used on.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ItemEffect {
+    pub restore_hp: u32,
+    pub restore_mp: u32,
+    pub revive: bool, //can only be used on knocked out characters and brings them back
+}
+
+
+//How an item is worn. Only characters whose class is listed can wear it, anyone can when no
+//  classes are listed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Equipment {
+    pub slot: String, //one of the campaign's equipment slots
+    pub modifiers: StatModifiers,
+    pub classes: Vec<String>, //names of class assets
+}
+
+impl Equipment {
+
+    pub fn allows_class(&self, class: &str) -> bool {
+        self.classes.is_empty() || self.classes.iter().any(|allowed| allowed == class)
+    }
+}
+
+
+//Something the party can carry, like a potion or a key.
+#[derive(Clone)]
+pub struct Item {
+    pub name: String,
+    pub description: String,
+    pub icon: Option<(String, SpritePos)>, //sprite sheet name and sprite shown in menus
+    pub category: ItemCategory,
+    pub stack_limit: u32, //most of this item the party can carry
+    pub price: u32, //gold it costs in shops
+    pub effect: Option<ItemEffect>, //items without an effect can't be used
+    pub equipment: Option<Equipment>, //items without this can't be worn
+}
+
+impl Item {
+
+    pub fn is_usable(&self) -> bool {
+        self.effect.is_some() && self.category == ItemCategory::Consumable
+    }
+}
@@ -6,8 +6,46 @@ pub mod audio;
 mod sprite_sheet;
 pub use sprite_sheet::{SpriteSheet, SpritePos, SpriteAnimation};
 
+mod tilemap;
+pub use tilemap::{Tilemap, TileLayer, MapObject, areas_overlap};
+
+mod tiled_loader;
+
+mod entity_prefab;
+pub use entity_prefab::{EntityPrefab, PrefabComponent};
+
+mod expression;
+pub use expression::{Variable, Expression, ExpressionScope, FlagEffect};
+
+mod dialogue;
+pub use dialogue::{Dialogue, DialogueNode, DialogueChoice, Portrait};
+
+mod character;
+pub use character::{Stats, StatGrowth, StatModifiers, CharacterClass, Character};
+
+mod encounter;
+pub use encounter::{Encounter, EncounterEnemy};
+
+mod item;
+pub use item::{Item, ItemCategory, ItemEffect, Equipment};
+
+mod quest;
+pub use quest::{Quest, QuestStage, QuestObjective, QuestReward};
+
+mod shop;
+pub use shop::{Shop, ShopStock};
+
+mod event_script;
+pub use event_script::{EventScript, EventCommand, TriggerCondition, MapArrival};
+
+mod script;
+pub use script::{Script, sandboxed_engine};
+
 mod asset_database;
 pub use asset_database::{AssetDatabase, AssetContainer};
 
+mod campaign_metadata;
+pub use campaign_metadata::CampaignMetadata;
+
 mod campaign_loader;
-pub use campaign_loader::load_campaign_data;
+pub use campaign_loader::{load_campaign_data, load_all_campaign_metadata, resolve_relative_path};
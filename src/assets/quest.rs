@@ -0,0 +1,71 @@
+use super::FlagEffect;
+
+
+//Something the party has to do to finish a stage of a quest.
+#[derive(Clone, PartialEq, Debug)]
+pub enum QuestObjective {
+    TalkTo(String), //name of the entity on the map, or of the dialogue
+    Collect { item: String, count: u32 }, //the party has to be carrying that many at once
+    Defeat { character: String, count: u32 }, //name of the enemy character, only counts while the stage is active
+    Reach { region: String, map: Option<String> }, //named object on a map the party leader walks into
+}
+
+impl QuestObjective {
+
+    //how much progress finishes the objective
+    pub fn needed(&self) -> u32 {
+        match self {
+            QuestObjective::Collect { count, .. } | QuestObjective::Defeat { count, .. } => *count,
+            _ => 1,
+        }
+    }
+
+    //shown in the quest log
+    pub fn describe(&self) -> String {
+        match self {
+            QuestObjective::TalkTo(name) => format!("Talk to {}", name),
+            QuestObjective::Collect { item, .. } => format!("Collect {}", item),
+            QuestObjective::Defeat { character, .. } => format!("Defeat {}", character),
+            QuestObjective::Reach { region, .. } => format!("Reach {}", region),
+        }
+    }
+}
+
+
+//One part of a quest. Every objective has to be done before the quest moves on to the next stage.
+#[derive(Clone)]
+pub struct QuestStage {
+    pub description: String,
+    pub objectives: Vec<QuestObjective>,
+}
+
+
+//What the party gets for finishing a quest. Experience goes to every active party member.
+#[derive(Clone, Default)]
+pub struct QuestReward {
+    pub items: Vec<(String, u32)>,
+    pub experience: u32,
+    pub effects: Vec<FlagEffect>,
+}
+
+
+//A task the party can take on, made of stages done one after another. Started by event scripts.
+pub struct Quest {
+    pub title: String,
+    pub description: String,
+    pub stages: Vec<QuestStage>,
+    pub reward: QuestReward,
+}
+
+impl Quest {
+
+    //flag set once a quest has been started, so dialogue and triggers can ask about it
+    pub fn started_flag(quest_name: &str) -> String {
+        format!("quest started: {}", quest_name)
+    }
+
+    //flag set once a quest has been finished
+    pub fn done_flag(quest_name: &str) -> String {
+        format!("quest done: {}", quest_name)
+    }
+}
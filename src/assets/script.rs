@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use rhai::{Engine, AST};
+use rhai::module_resolvers::DummyModuleResolver;
+
+
+//limits that keep a broken or hostile script from hanging or eating up the game
+const MAX_OPERATIONS: u64 = 200_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 10_000;
+const MAX_COLLECTION_SIZE: usize = 1_000;
+
+
+//A campaign script written in Rhai. It is compiled when the campaign loads so mistakes show up
+//  right away, and keeps its file so errors while running can say where they happened.
+pub struct Script {
+    pub file: PathBuf,
+    pub ast: AST,
+}
+
+
+//engine that scripts are compiled and run with. Scripts can only use what the game registers
+//  on top of this, they can't load other files or modules and are stopped when they run too long.
+pub fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_module_resolver(DummyModuleResolver::new())
+          .set_max_operations(MAX_OPERATIONS)
+          .set_max_call_levels(MAX_CALL_LEVELS)
+          .set_max_string_size(MAX_STRING_SIZE)
+          .set_max_array_size(MAX_COLLECTION_SIZE)
+          .set_max_map_size(MAX_COLLECTION_SIZE)
+          .disable_symbol("eval");
+
+    engine.on_print(|text| info!("[Scripts] {}", text));
+    engine.on_debug(|text, source, position| {
+        info!("[Scripts] {} line {}: {}", source.unwrap_or("script"), position.line().unwrap_or(0), text)
+    });
+
+    engine
+}
@@ -0,0 +1,55 @@
+//An item a shop sells. Items with a limit sell out once that many have been bought.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShopStock {
+    pub item: String, //name of the item asset
+    pub limit: Option<u32>, //None when the shop never runs out
+}
+
+
+//A place the party can buy items from and sell their own items to. Prices are the item's price
+//  scaled by the shop's multipliers, so one shop can be cheaper or pay better than another.
+#[derive(Clone)]
+pub struct Shop {
+    pub title: String,
+    pub stock: Vec<ShopStock>,
+    pub buy_multiplier: f32, //what the party pays, as a multiple of the item's price
+    pub sell_multiplier: f32, //what the shop pays the party, as a multiple of the item's price
+}
+
+impl Shop {
+
+    //gold the party pays for one of an item worth the given price
+    pub fn buy_price(&self, price: u32) -> u32 {
+        scale_price(price, self.buy_multiplier)
+    }
+
+    //gold the party gets for one of an item worth the given price
+    pub fn sell_price(&self, price: u32) -> u32 {
+        scale_price(price, self.sell_multiplier)
+    }
+
+    pub fn stock_of(&self, item: &str) -> Option<&ShopStock> {
+        self.stock.iter().find(|stock| stock.item == item)
+    }
+}
+
+
+fn scale_price(price: u32, multiplier: f32) -> u32 {
+    (price as f32 * multiplier.max(0.0)).round().min(u32::MAX as f32) as u32
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_follow_the_multipliers() {
+        let shop = Shop { title: String::new(), stock: vec![], buy_multiplier: 1.5, sell_multiplier: 0.5 };
+
+        assert_eq!((shop.buy_price(10), shop.sell_price(10)), (15, 5));
+        assert_eq!(shop.sell_price(3), 2);
+        assert_eq!(shop.buy_price(0), 0);
+    }
+}
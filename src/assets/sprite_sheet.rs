@@ -14,7 +14,7 @@ pub type SpriteAnimation = Vec<SpritePos>;
 
 //An array of sprites packed into a single image, also called an Atlas.
 pub struct SpriteSheet {
-    //atlas: Image,
+    atlas: Image,
     pub batch: Batch,
     pub rows: u16,
     pub columns: u16,
@@ -32,7 +32,7 @@ impl SpriteSheet {
         };
 
         SpriteSheet {
-            //atlas: image.clone(),
+            atlas: image.clone(),
             batch: Batch::new(image),
             rows,
             columns,
@@ -74,6 +74,11 @@ impl SpriteSheet {
         }
     }
 
+    //full atlas image along with where a sprite is in it, used to show sprites in the UI
+    pub fn sprite_image(&self, row: u16, column: u16) -> (Image, Rectangle<u16>) {
+        (self.atlas.clone(), self.get_sprite(Point::new(0.0, 0.0), row, column).source)
+    }
+
     //add a sprite quad to the batch for later drawing
     pub fn add_to_batch(&mut self, position: Point, row: u16, column: u16) {
         self.batch.add( self.get_sprite( position, row, column ) );
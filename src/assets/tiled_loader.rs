@@ -0,0 +1,775 @@
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::ffi::OsStr;
+
+use coffee::graphics::{Gpu, Image, Rectangle};
+
+use config::Config;
+
+use xml::reader::{EventReader, XmlEvent};
+
+use serde_json::Value as JsonValue;
+
+use super::{AssetDatabase, AssetContainer, SpriteSheet, Tilemap, TileLayer, MapObject};
+use super::tilemap::EMPTY_TILE;
+use super::campaign_loader::resolve_relative_path;
+
+
+//Tiled stores flipping in the top bits of a tile's global id, we don't support flipping so they get masked off.
+const TILED_FLIP_FLAGS: u32 = 0xE000_0000;
+
+
+//tileset found in a Tiled file. Becomes a sprite sheet asset once the image is loaded.
+struct TiledTileset {
+    first_gid: u32,
+    image_path: PathBuf,
+    columns: u16,
+    rows: u16,
+}
+
+
+//everything we pull out of a Tiled file before it is turned into assets
+struct TiledMap {
+    tile_width: f32,
+    tile_height: f32,
+    tilesets: Vec<TiledTileset>,
+    layers: Vec<TileLayer>,
+    objects: Vec<MapObject>,
+    properties: HashMap<String, String>,
+}
+
+
+//load a map made with the Tiled editor. The config file gives the map a name and points at the
+//  .tmx or .json file. Every tileset in the map is loaded as a sprite sheet asset.
+pub fn load_tiled_map(config: &Config, config_path: &Path,
+                      gpu: &mut Gpu, asset_db: &mut AssetDatabase) -> bool {
+
+    //pull data we need and validate
+    let name = config.get_str("name");
+    let file = config.get_str("file");
+
+    if name.is_err() || file.is_err() {
+        let err_msg_head = format!("{} {} {}. {}",
+                               "[Asset Loading]",
+                               "Could not find required config value for tiled map type in config file",
+                               config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                               "Error follows: ");
+
+        if let Err(err) = name { warn!("{} {}", err_msg_head, err); }
+        if let Err(err) = file { warn!("{} {}", err_msg_head, err); }
+
+        return false //config missing required values
+    }
+
+    let map_path = match resolve_relative_path(config_path, &file.unwrap()) {
+        Some(map_path) => map_path,
+        None => {
+            warn!("{} {}",
+                  "[Asset Loading] Parent missing from config path when processing",
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+            );
+            return false;
+        },
+    };
+
+    //the format is decided by the file extension
+    let parsed_map = match map_path.extension().and_then(OsStr::to_str) {
+        Some("tmx") => parse_tmx(&map_path),
+        Some("json") => parse_tiled_json(&map_path),
+        _ => Err( "Tiled map file must have a .tmx or .json extension".to_string() ),
+    };
+
+    let tiled_map = match parsed_map {
+        Ok(tiled_map) => tiled_map,
+        Err(err) => {
+            warn!("[Asset Loading] Could not read Tiled map {} related to config file {}. Following error returned: {}",
+                  map_path.to_str().unwrap_or("<error could not convert map path to str>"),
+                  config_path.to_str().unwrap_or("<error could not convert config path to str>"),
+                  err,
+            );
+            return false;
+        }
+    };
+
+
+    //turn what we read into a tilemap asset
+    let mut tilemap = Tilemap::new(tiled_map.tile_width, tiled_map.tile_height);
+    tilemap.layers = tiled_map.layers;
+    tilemap.objects = tiled_map.objects;
+    tilemap.properties = tiled_map.properties;
+
+    for tileset in tiled_map.tilesets {
+        let sheet_name = match tileset.image_path.clone().into_os_string().into_string() {
+            Ok(sheet_name) => sheet_name,
+            Err(_) => {
+                warn!("[Asset Loading] Tileset image path is not UTF-8 compatible in Tiled map {}.",
+                      map_path.to_str().unwrap_or("<error could not convert map path to str>"));
+                continue;
+            }
+        };
+
+        //tile indices are stored as u16 so bigger ids can't be drawn
+        if tileset.first_gid > u32::from(u16::MAX) {
+            warn!("[Asset Loading] Tileset {} starts at tile id {} which is too large. Related to Tiled map {}.",
+                  sheet_name,
+                  tileset.first_gid,
+                  map_path.to_str().unwrap_or("<error could not convert map path to str>"));
+            continue;
+        }
+
+        //the same image may already be loaded as a sprite sheet by another map or config file
+        if let AssetContainer::DoesNotExist = asset_db.get_asset(&sheet_name) {
+            let image = match Image::load( tileset.image_path.clone() ).run(gpu) {
+                Ok(image) => image,
+                Err(err) => {
+                    warn!("[Asset Loading] Could not load tileset Image at {} related to Tiled map {}. Following error returned: {}",
+                          sheet_name,
+                          map_path.to_str().unwrap_or("<error could not convert map path to str>"),
+                          err,
+                    );
+                    continue;
+                }
+            };
+
+            asset_db.add_asset(sheet_name.clone(),
+                               AssetContainer::Spritesheet(SpriteSheet::new(image, tileset.rows, tileset.columns)));
+        }
+
+        tilemap.add_tileset(tileset.first_gid as u16, sheet_name);
+    }
+
+    asset_db.add_asset(name.unwrap(), AssetContainer::Tilemap(tilemap));
+    true
+}
+
+
+//converts a Tiled global tile id into the tile index used by our tilemaps
+fn gid_to_index(gid: u32) -> u16 {
+    let gid = gid & !TILED_FLIP_FLAGS;
+
+    if gid > u32::from(u16::MAX) {
+        warn!("[Asset Loading] Tiled tile id {} is too large and will be left empty.", gid);
+        return EMPTY_TILE;
+    }
+
+    gid as u16
+}
+
+
+//splits a flat list of tile ids into rows
+fn gids_to_rows(gids: Vec<u32>, width: usize) -> Vec<Vec<u16>> {
+    if width == 0 { return vec![]; }
+
+    gids.chunks(width)
+        .map(|row| row.iter().map(|gid| gid_to_index(*gid)).collect())
+        .collect()
+}
+
+
+//only plain tile ids are read, base64 layers would need decoding and decompressing first
+fn unsupported_encoding(layer_name: &str, encoding: &str) -> String {
+    format!("Layer {} uses '{}' encoding. Please save the map with CSV encoding", layer_name, encoding)
+}
+
+
+//rows and columns of a tileset image, preferring the column count Tiled gives us
+fn tileset_grid(columns: u32, tile_count: u32, image_height: u32, tile_height: u32) -> (u16, u16) {
+    let columns = columns.max(1);
+    let rows = if tile_count > 0 {
+        tile_count.div_ceil(columns)
+    } else {
+        image_height / tile_height.max(1)
+    };
+
+    (rows.max(1) as u16, columns as u16)
+}
+
+
+
+//---------------------------------------------------------------------------------------
+// TMX (xml) files
+//---------------------------------------------------------------------------------------
+
+
+//minimal xml tree, Tiled files are small enough to read into memory at once
+struct XmlElement {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|value| value.as_str())
+    }
+
+    fn attr_or<T: std::str::FromStr>(&self, name: &str, default: T) -> T {
+        self.attr(name).and_then(|value| value.parse().ok()).unwrap_or(default)
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+
+fn read_xml_file(path: &Path) -> Result<XmlElement, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    read_xml(BufReader::new(file))
+}
+
+
+fn read_xml<R: Read>(source: R) -> Result<XmlElement, String> {
+    let mut stack: Vec<XmlElement> = vec![];
+
+    for event in EventReader::new(source) {
+        match event.map_err(|err| err.to_string())? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                stack.push(XmlElement {
+                    name: name.local_name,
+                    attributes: attributes.into_iter()
+                                          .map(|attribute| (attribute.name.local_name, attribute.value))
+                                          .collect(),
+                    children: vec![],
+                    text: String::new(),
+                });
+            },
+            XmlEvent::EndElement { .. } => {
+                let element = stack.pop().ok_or("Unbalanced xml elements")?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element), //finished the root element
+                }
+            },
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    Err( "File ended before the root element was closed".to_string() )
+}
+
+
+fn tmx_properties(element: &XmlElement) -> HashMap<String, String> {
+    let mut properties = HashMap::new();
+
+    if let Some(property_list) = element.child("properties") {
+        for property in property_list.children.iter().filter(|child| child.name == "property") {
+            if let Some(name) = property.attr("name") {
+                //multi-line strings are stored as text instead of a value attribute
+                let value = property.attr("value").map(|value| value.to_string())
+                                    .unwrap_or_else(|| property.text.clone());
+                properties.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    properties
+}
+
+
+fn parse_tmx(path: &Path) -> Result<TiledMap, String> {
+    let root = read_xml_file(path)?;
+
+    if root.name != "map" {
+        return Err( format!("Expected a 'map' root element but found '{}'", root.name) );
+    }
+
+    if root.attr("orientation").unwrap_or("orthogonal") != "orthogonal" {
+        return Err( "Only orthogonal maps are supported".to_string() );
+    }
+
+    let mut tiled_map = TiledMap {
+        tile_width: root.attr_or("tilewidth", 0.0),
+        tile_height: root.attr_or("tileheight", 0.0),
+        tilesets: vec![],
+        layers: vec![],
+        objects: vec![],
+        properties: tmx_properties(&root),
+    };
+
+    for child in root.children.iter() {
+        if child.name == "tileset" {
+            tiled_map.tilesets.push( parse_tmx_tileset(path, child)? );
+        }
+    }
+
+    read_tmx_layers(&root, &mut tiled_map)?;
+
+    Ok(tiled_map)
+}
+
+
+//tilesets are either embedded in the map or stored in their own .tsx file
+fn parse_tmx_tileset(map_path: &Path, element: &XmlElement) -> Result<TiledTileset, String> {
+    let first_gid = element.attr_or("firstgid", 1);
+
+    match element.attr("source") {
+        Some(source) => {
+            let tileset_path = resolve_relative_path(map_path, source)
+                                   .ok_or("Could not resolve tileset path")?;
+
+            //json tilesets can be referenced from tmx maps as well
+            if tileset_path.extension().and_then(OsStr::to_str) == Some("json") {
+                let json = read_json_file(&tileset_path)?;
+                return parse_json_tileset_data(&tileset_path, &json, first_gid);
+            }
+
+            let tileset_element = read_xml_file(&tileset_path)?;
+            parse_tmx_tileset_data(&tileset_path, &tileset_element, first_gid)
+        },
+        None => parse_tmx_tileset_data(map_path, element, first_gid),
+    }
+}
+
+
+fn parse_tmx_tileset_data(tileset_path: &Path, element: &XmlElement, first_gid: u32)
+   -> Result<TiledTileset, String> {
+
+    let name = element.attr("name").unwrap_or("<unnamed>");
+
+    let image = element.child("image")
+                       .ok_or( format!("Tileset {} has no image. Image collection tilesets are not supported", name) )?;
+
+    let source = image.attr("source").ok_or( format!("Tileset {} image has no source", name) )?;
+
+    if element.attr_or("spacing", 0) != 0 || element.attr_or("margin", 0) != 0 {
+        warn!("[Asset Loading] Tileset {} uses spacing or margins which are not supported. Tiles may look wrong.", name);
+    }
+
+    let (rows, columns) = tileset_grid(element.attr_or("columns", 1),
+                                       element.attr_or("tilecount", 0),
+                                       image.attr_or("height", 0),
+                                       element.attr_or("tileheight", 1));
+
+    Ok(TiledTileset {
+        first_gid,
+        image_path: resolve_relative_path(tileset_path, source)
+                        .ok_or( format!("Could not resolve image path for tileset {}", name) )?,
+        columns,
+        rows,
+    })
+}
+
+
+//layers can be nested inside groups so this walks them recursively
+fn read_tmx_layers(parent: &XmlElement, tiled_map: &mut TiledMap) -> Result<(), String> {
+    for child in parent.children.iter() {
+        match child.name.as_str() {
+            "layer" => tiled_map.layers.push( parse_tmx_tile_layer(child)? ),
+            "objectgroup" => {
+                for object in child.children.iter().filter(|object| object.name == "object") {
+                    tiled_map.objects.push( parse_tmx_object(object) );
+                }
+            },
+            "group" => read_tmx_layers(child, tiled_map)?,
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+
+fn parse_tmx_tile_layer(element: &XmlElement) -> Result<TileLayer, String> {
+    let name = element.attr("name").unwrap_or("").to_string();
+    let width = element.attr_or("width", 0);
+
+    let data = element.child("data").ok_or( format!("Layer {} has no tile data", name) )?;
+
+    if data.child("chunk").is_some() {
+        return Err( format!("Layer {} is from an infinite map which is not supported", name) );
+    }
+
+    let gids: Vec<u32> = match data.attr("encoding") {
+        Some("csv") => data.text.split(',')
+                                .map(|gid| gid.trim())
+                                .filter(|gid| !gid.is_empty())
+                                .map(|gid| gid.parse::<u32>().map_err(|err| err.to_string()))
+                                .collect::<Result<Vec<u32>, String>>()?,
+
+        //no encoding means every tile is its own element
+        None => data.children.iter()
+                             .filter(|tile| tile.name == "tile")
+                             .map(|tile| tile.attr_or("gid", 0))
+                             .collect(),
+
+        Some(encoding) => return Err( unsupported_encoding(&name, encoding) ),
+    };
+
+    Ok(TileLayer {
+        tiles: gids_to_rows(gids, width),
+        properties: tmx_properties(element),
+    })
+}
+
+
+fn parse_tmx_object(element: &XmlElement) -> MapObject {
+    let mut area = Rectangle {
+        x: element.attr_or("x", 0.0),
+        y: element.attr_or("y", 0.0),
+        width: element.attr_or("width", 0.0),
+        height: element.attr_or("height", 0.0),
+    };
+
+    //polygons and polylines only give points relative to the object's position
+    if let Some(points) = element.child("polygon").or_else(|| element.child("polyline"))
+                                 .and_then(|shape| shape.attr("points")) {
+        let points: Vec<(f32, f32)> = points.split_whitespace()
+            .filter_map(|point| {
+                let mut xy = point.split(',').map(|value| value.parse::<f32>());
+                match (xy.next(), xy.next()) {
+                    (Some(Ok(x)), Some(Ok(y))) => Some((x, y)),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        area = bounding_box(area.x, area.y, &points);
+    }
+
+    //tile objects are positioned by their bottom left corner
+    if element.attr("gid").is_some() {
+        area.y -= area.height;
+    }
+
+    MapObject {
+        name: element.attr("name").unwrap_or("").to_string(),
+        //newer versions of Tiled call the type a class
+        kind: element.attr("type").or_else(|| element.attr("class")).unwrap_or("").to_string(),
+        area,
+        properties: tmx_properties(element),
+    }
+}
+
+
+fn bounding_box(origin_x: f32, origin_y: f32, points: &[(f32, f32)]) -> Rectangle<f32> {
+    if points.is_empty() {
+        return Rectangle { x: origin_x, y: origin_y, width: 0.0, height: 0.0 };
+    }
+
+    let min_x = points.iter().map(|point| point.0).fold(f32::MAX, f32::min);
+    let min_y = points.iter().map(|point| point.1).fold(f32::MAX, f32::min);
+    let max_x = points.iter().map(|point| point.0).fold(f32::MIN, f32::max);
+    let max_y = points.iter().map(|point| point.1).fold(f32::MIN, f32::max);
+
+    Rectangle { x: origin_x + min_x, y: origin_y + min_y, width: max_x - min_x, height: max_y - min_y }
+}
+
+
+
+//---------------------------------------------------------------------------------------
+// JSON files
+//---------------------------------------------------------------------------------------
+
+
+fn read_json_file(path: &Path) -> Result<JsonValue, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|err| err.to_string())
+}
+
+
+fn json_u32(value: &JsonValue, key: &str) -> u32 {
+    value[key].as_u64().unwrap_or(0) as u32
+}
+
+fn json_f32(value: &JsonValue, key: &str) -> f32 {
+    value[key].as_f64().unwrap_or(0.0) as f32
+}
+
+fn json_str<'a>(value: &'a JsonValue, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or("")
+}
+
+
+fn json_properties(value: &JsonValue) -> HashMap<String, String> {
+    let mut properties = HashMap::new();
+
+    if let Some(property_list) = value["properties"].as_array() {
+        for property in property_list {
+            let value = match &property["value"] {
+                JsonValue::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            properties.insert(json_str(property, "name").to_string(), value);
+        }
+    }
+
+    properties
+}
+
+
+fn parse_tiled_json(path: &Path) -> Result<TiledMap, String> {
+    let root = read_json_file(path)?;
+
+    if root["orientation"].as_str().unwrap_or("orthogonal") != "orthogonal" {
+        return Err( "Only orthogonal maps are supported".to_string() );
+    }
+
+    if root["infinite"].as_bool().unwrap_or(false) {
+        return Err( "Infinite maps are not supported".to_string() );
+    }
+
+    let mut tiled_map = TiledMap {
+        tile_width: json_f32(&root, "tilewidth"),
+        tile_height: json_f32(&root, "tileheight"),
+        tilesets: vec![],
+        layers: vec![],
+        objects: vec![],
+        properties: json_properties(&root),
+    };
+
+    for tileset in root["tilesets"].as_array().unwrap_or(&vec![]) {
+        let first_gid = json_u32(tileset, "firstgid");
+
+        let tiled_tileset = match tileset["source"].as_str() {
+            Some(source) => {
+                let tileset_path = resolve_relative_path(path, source)
+                                       .ok_or("Could not resolve tileset path")?;
+
+                if tileset_path.extension().and_then(OsStr::to_str) == Some("tsx") {
+                    parse_tmx_tileset_data(&tileset_path, &read_xml_file(&tileset_path)?, first_gid)?
+                } else {
+                    parse_json_tileset_data(&tileset_path, &read_json_file(&tileset_path)?, first_gid)?
+                }
+            },
+            None => parse_json_tileset_data(path, tileset, first_gid)?,
+        };
+
+        tiled_map.tilesets.push(tiled_tileset);
+    }
+
+    read_json_layers(&root, &mut tiled_map)?;
+
+    Ok(tiled_map)
+}
+
+
+fn parse_json_tileset_data(tileset_path: &Path, tileset: &JsonValue, first_gid: u32)
+   -> Result<TiledTileset, String> {
+
+    let name = json_str(tileset, "name");
+
+    let source = tileset["image"].as_str()
+                     .ok_or( format!("Tileset {} has no image. Image collection tilesets are not supported", name) )?;
+
+    if json_u32(tileset, "spacing") != 0 || json_u32(tileset, "margin") != 0 {
+        warn!("[Asset Loading] Tileset {} uses spacing or margins which are not supported. Tiles may look wrong.", name);
+    }
+
+    let (rows, columns) = tileset_grid(json_u32(tileset, "columns"),
+                                       json_u32(tileset, "tilecount"),
+                                       json_u32(tileset, "imageheight"),
+                                       json_u32(tileset, "tileheight"));
+
+    Ok(TiledTileset {
+        first_gid,
+        image_path: resolve_relative_path(tileset_path, source)
+                        .ok_or( format!("Could not resolve image path for tileset {}", name) )?,
+        columns,
+        rows,
+    })
+}
+
+
+//layers can be nested inside groups so this walks them recursively
+fn read_json_layers(parent: &JsonValue, tiled_map: &mut TiledMap) -> Result<(), String> {
+    for layer in parent["layers"].as_array().unwrap_or(&vec![]) {
+        match json_str(layer, "type") {
+            "tilelayer" => tiled_map.layers.push( parse_json_tile_layer(layer)? ),
+            "objectgroup" => {
+                for object in layer["objects"].as_array().unwrap_or(&vec![]) {
+                    tiled_map.objects.push( parse_json_object(object) );
+                }
+            },
+            "group" => read_json_layers(layer, tiled_map)?,
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+
+fn parse_json_tile_layer(layer: &JsonValue) -> Result<TileLayer, String> {
+    let name = json_str(layer, "name");
+
+    if layer.get("chunks").is_some() {
+        return Err( format!("Layer {} is from an infinite map which is not supported", name) );
+    }
+
+    //csv maps store the ids as an array, base64 ones as a string
+    let gids: Vec<u32> = match &layer["data"] {
+        JsonValue::Array(data) => data.iter()
+                                      .map(|gid| gid.as_u64().map(|gid| gid as u32)
+                                                    .ok_or( format!("Layer {} has a tile id that isn't a number", name) ))
+                                      .collect::<Result<Vec<u32>, String>>()?,
+
+        JsonValue::String(_) => return Err( unsupported_encoding(name, layer["encoding"].as_str().unwrap_or("base64")) ),
+
+        _ => return Err( format!("Layer {} has no tile data", name) ),
+    };
+
+    Ok(TileLayer {
+        tiles: gids_to_rows(gids, json_u32(layer, "width") as usize),
+        properties: json_properties(layer),
+    })
+}
+
+
+fn parse_json_object(object: &JsonValue) -> MapObject {
+    let mut area = Rectangle {
+        x: json_f32(object, "x"),
+        y: json_f32(object, "y"),
+        width: json_f32(object, "width"),
+        height: json_f32(object, "height"),
+    };
+
+    //polygons and polylines only give points relative to the object's position
+    if let Some(points) = object["polygon"].as_array().or_else(|| object["polyline"].as_array()) {
+        let points: Vec<(f32, f32)> = points.iter()
+                                            .map(|point| (json_f32(point, "x"), json_f32(point, "y")))
+                                            .collect();
+
+        area = bounding_box(area.x, area.y, &points);
+    }
+
+    //tile objects are positioned by their bottom left corner
+    if object.get("gid").is_some() {
+        area.y -= area.height;
+    }
+
+    //newer versions of Tiled call the type a class
+    let kind = match json_str(object, "type") {
+        "" => json_str(object, "class"),
+        kind => kind,
+    };
+
+    MapObject {
+        name: json_str(object, "name").to_string(),
+        kind: kind.to_string(),
+        area,
+        properties: json_properties(object),
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmx_layer(xml: &str) -> Result<TileLayer, String> {
+        parse_tmx_tile_layer( &read_xml(xml.as_bytes()).unwrap() )
+    }
+
+    #[test]
+    fn csv_layer_is_split_into_rows() {
+        let layer = tmx_layer(r#"<layer name="ground" width="3" height="2">
+                                   <data encoding="csv">
+                                     1,2,3,
+                                     0,5,6
+                                   </data>
+                                 </layer>"#).unwrap();
+
+        assert_eq!(layer.tiles, vec![vec![1, 2, 3], vec![0, 5, 6]]);
+    }
+
+    #[test]
+    fn xml_tiles_are_read_in_order() {
+        let layer = tmx_layer(r#"<layer name="ground" width="2" height="2">
+                                   <data>
+                                     <tile gid="4"/><tile/>
+                                     <tile gid="7"/><tile gid="1"/>
+                                   </data>
+                                 </layer>"#).unwrap();
+
+        assert_eq!(layer.tiles, vec![vec![4, 0], vec![7, 1]]);
+    }
+
+    #[test]
+    fn layer_properties_are_kept() {
+        let layer = tmx_layer(r#"<layer name="walls" width="1" height="1">
+                                   <properties><property name="solid" type="bool" value="true"/></properties>
+                                   <data encoding="csv">1</data>
+                                 </layer>"#).unwrap();
+
+        assert!(layer.is_solid());
+    }
+
+    #[test]
+    fn flip_flags_are_stripped() {
+        assert_eq!(gid_to_index(0x8000_0003), 3);
+        assert_eq!(gid_to_index(0x4000_0003), 3);
+        assert_eq!(gid_to_index(0x2000_0003), 3);
+        assert_eq!(gid_to_index(0xE000_0003), 3);
+
+        let layer = tmx_layer(r#"<layer name="ground" width="2" height="1">
+                                   <data encoding="csv">2147483650,1073741825</data>
+                                 </layer>"#).unwrap();
+        assert_eq!(layer.tiles, vec![vec![2, 1]]);
+    }
+
+    #[test]
+    fn tile_ids_too_large_are_left_empty() {
+        assert_eq!(gid_to_index(u32::from(u16::MAX) + 1), EMPTY_TILE);
+    }
+
+    #[test]
+    fn base64_layers_are_rejected_in_both_formats() {
+        let tmx_err = tmx_layer(r#"<layer name="ground" width="1" height="1">
+                                     <data encoding="base64">AQAAAA==</data>
+                                   </layer>"#).err().unwrap();
+
+        let json_layer: JsonValue = serde_json::from_str(
+            r#"{ "name": "ground", "type": "tilelayer", "width": 1, "height": 1,
+                 "encoding": "base64", "data": "AQAAAA==" }"#).unwrap();
+        let json_err = parse_json_tile_layer(&json_layer).err().unwrap();
+
+        assert_eq!(tmx_err, json_err);
+    }
+
+    #[test]
+    fn json_layer_is_split_into_rows() {
+        let json_layer: JsonValue = serde_json::from_str(
+            r#"{ "name": "ground", "type": "tilelayer", "width": 2, "height": 2, "data": [1, 2, 3221225475, 0] }"#
+        ).unwrap();
+
+        assert_eq!(parse_json_tile_layer(&json_layer).unwrap().tiles, vec![vec![1, 2], vec![3, 0]]);
+    }
+
+    #[test]
+    fn tileset_image_is_resolved_next_to_the_tileset() {
+        let element = read_xml(r#"<tileset name="town" tilewidth="16" tileheight="16" tilecount="12" columns="4">
+                                     <image source="../images/town.png" width="64" height="48"/>
+                                   </tileset>"#.as_bytes()).unwrap();
+
+        let tileset = parse_tmx_tileset_data(Path::new("campaign/maps/town.tsx"), &element, 5).unwrap();
+
+        assert_eq!(tileset.first_gid, 5);
+        assert_eq!(tileset.image_path, PathBuf::from("campaign/images/town.png"));
+        assert_eq!((tileset.rows, tileset.columns), (3, 4));
+    }
+
+    #[test]
+    fn tileset_without_image_is_rejected() {
+        let element = read_xml(r#"<tileset name="objects" tilecount="2" columns="0"></tileset>"#.as_bytes()).unwrap();
+
+        assert!(parse_tmx_tileset_data(Path::new("map.tmx"), &element, 1).is_err());
+    }
+
+    #[test]
+    fn tileset_grid_falls_back_to_image_height() {
+        assert_eq!(tileset_grid(4, 10, 0, 16), (3, 4));
+        assert_eq!(tileset_grid(4, 0, 64, 16), (4, 4));
+        assert_eq!(tileset_grid(0, 0, 0, 0), (1, 1));
+    }
+}
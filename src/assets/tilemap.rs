@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use coffee::graphics::{Point, Rectangle};
+
+use super::SpritePos;
+
+//index used in a layer to mark a spot that has no tile
+pub const EMPTY_TILE: u16 = 0;
+
+
+//A single grid of tile indices. Rows are stored top to bottom.
+pub struct TileLayer {
+    pub tiles: Vec<Vec<u16>>,
+    pub properties: HashMap<String, String>,
+}
+
+
+//A sprite sheet used as a tileset. Tile indices from first_index onward belong to this tileset.
+pub struct MapTileset {
+    pub first_index: u16,
+    pub sheet_name: String, //asset name of the sprite sheet
+}
+
+
+//Anything placed on the map that isn't a tile, like spawn points, triggers, or collision shapes.
+//  Shapes other than rectangles are stored as their bounding box.
+pub struct MapObject {
+    pub name: String,
+    pub kind: String,
+    pub area: Rectangle<f32>,
+    pub properties: HashMap<String, String>,
+}
+
+
+//A grid based map made up of several layers of tiles taken from one or more sprite sheets.
+pub struct Tilemap {
+    pub tilesets: Vec<MapTileset>,
+    pub tile_width: f32,
+    pub tile_height: f32,
+    pub layers: Vec<TileLayer>,
+    pub objects: Vec<MapObject>,
+    pub properties: HashMap<String, String>,
+}
+
+impl Tilemap {
+
+    pub fn new(tile_width: f32, tile_height: f32) -> Tilemap {
+        Tilemap {
+            tilesets: vec![],
+            tile_width,
+            tile_height,
+            layers: vec![],
+            objects: vec![],
+            properties: HashMap::new(),
+        }
+    }
+
+    pub fn add_tileset(&mut self, first_index: u16, sheet_name: String) {
+        self.tilesets.push( MapTileset { first_index, sheet_name } );
+        self.tilesets.sort_by_key(|tileset| tileset.first_index);
+    }
+
+    //finds the tileset a tile index belongs to along with the index inside that tileset (starting at 1)
+    pub fn tileset_for(&self, index: u16) -> Option<(&MapTileset, u16)> {
+        self.tilesets.iter()
+            .rev()
+            .find(|tileset| tileset.first_index <= index)
+            .map(|tileset| (tileset, index - tileset.first_index + 1))
+    }
+
+    pub fn add_layer(&mut self, layer: TileLayer) {
+        self.layers.push(layer);
+    }
+
+    //number of tile columns and rows. Layers can have different sizes so we take the largest.
+    pub fn size_in_tiles(&self) -> (usize, usize) {
+        let columns = self.layers.iter()
+                          .flat_map(|layer| layer.tiles.iter().map(|row| row.len()))
+                          .max().unwrap_or(0);
+        let rows = self.layers.iter().map(|layer| layer.tiles.len()).max().unwrap_or(0);
+
+        (columns, rows)
+    }
+
+    //size of the whole map in pixels
+    pub fn size_in_pixels(&self) -> (f32, f32) {
+        let (columns, rows) = self.size_in_tiles();
+        (columns as f32 * self.tile_width, rows as f32 * self.tile_height)
+    }
+
+    //name of the music asset played on the map, from its 'music' property
+    pub fn music(&self) -> Option<&String> {
+        self.properties.get("music")
+    }
+
+    //converts a tile index into a row and column on a tileset with the given number of columns.
+    //  Indices count left to right then top to bottom starting at 1, just like sprite positions.
+    pub fn index_to_sprite_pos(index: u16, tileset_columns: u16) -> SpritePos {
+        let zero_based = index - 1;
+        ( zero_based / tileset_columns + 1, zero_based % tileset_columns + 1 )
+    }
+
+    //every non-empty tile that overlaps the given area, from the bottom layer up.
+    // returns: index of the layer, map position of the tile and the tile index
+    pub fn visible_tiles(&self, area: &Rectangle<f32>) -> Vec<(usize, Point, u16)> {
+        let (first_column, first_row, last_column, last_row) = self.tile_range(area);
+
+        let mut tiles = vec![];
+
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            for row in first_row..last_row {
+                let tile_row = match layer.tiles.get(row) {
+                    Some(tile_row) => tile_row,
+                    None => break,
+                };
+
+                let visible_columns = tile_row.iter().enumerate()
+                                              .take(last_column)
+                                              .skip(first_column);
+
+                for (column, &index) in visible_columns {
+                    if index == EMPTY_TILE { continue; }
+
+                    tiles.push((
+                        layer_index,
+                        Point::new(column as f32 * self.tile_width, row as f32 * self.tile_height),
+                        index,
+                    ));
+                }
+            }
+        }
+
+        tiles
+    }
+
+
+    //range of tile columns and rows (end exclusive) that overlap an area, clamped to the map
+    fn tile_range(&self, area: &Rectangle<f32>) -> (usize, usize, usize, usize) {
+        let (columns, rows) = self.size_in_tiles();
+
+        let first_column = (area.x / self.tile_width).floor().max(0.0) as usize;
+        let first_row = (area.y / self.tile_height).floor().max(0.0) as usize;
+        let last_column = (((area.x + area.width) / self.tile_width).ceil().max(0.0) as usize).min(columns);
+        let last_row = (((area.y + area.height) / self.tile_height).ceil().max(0.0) as usize).min(rows);
+
+        (first_column, first_row, last_column, last_row)
+    }
+
+    //checks if an area runs into anything solid on the map. Solid things are tiles on layers with
+    //  the 'solid' property, objects of the 'collision' kind, and anything outside the map.
+    pub fn is_area_solid(&self, area: &Rectangle<f32>) -> bool {
+        let (map_width, map_height) = self.size_in_pixels();
+        if area.x < 0.0 || area.y < 0.0 
+        || area.x + area.width > map_width || area.y + area.height > map_height {
+            return true;
+        }
+
+        let (first_column, first_row, last_column, last_row) = self.tile_range(area);
+
+        for layer in self.layers.iter().filter(|layer| layer.is_solid()) {
+            for tile_row in layer.tiles.iter().take(last_row).skip(first_row) {
+                let solid_tile = tile_row.iter()
+                                         .take(last_column)
+                                         .skip(first_column)
+                                         .any(|&index| index != EMPTY_TILE);
+                if solid_tile { return true; }
+            }
+        }
+
+        self.objects.iter()
+            .filter(|object| object.kind == "collision")
+            .any(|object| areas_overlap(&object.area, area))
+    }
+}
+
+
+impl TileLayer {
+
+    //solid layers block movement wherever they have a tile
+    pub fn is_solid(&self) -> bool {
+        self.properties.get("solid").map(|value| value == "true").unwrap_or(false)
+    }
+}
+
+
+//true when two areas cover some of the same space. Touching edges don't count.
+pub fn areas_overlap(a: &Rectangle<f32>, b: &Rectangle<f32>) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width 
+    && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_index_resolves_to_the_right_tileset() {
+        let mut tilemap = Tilemap::new(16.0, 16.0);
+        tilemap.add_tileset(21, "objects".to_string());
+        tilemap.add_tileset(1, "ground".to_string());
+
+        let (tileset, local_index) = tilemap.tileset_for(1).unwrap();
+        assert_eq!((tileset.sheet_name.as_str(), local_index), ("ground", 1));
+
+        let (tileset, local_index) = tilemap.tileset_for(20).unwrap();
+        assert_eq!((tileset.sheet_name.as_str(), local_index), ("ground", 20));
+
+        let (tileset, local_index) = tilemap.tileset_for(23).unwrap();
+        assert_eq!((tileset.sheet_name.as_str(), local_index), ("objects", 3));
+
+        assert!(tilemap.tileset_for(EMPTY_TILE).is_none());
+    }
+
+    #[test]
+    fn index_to_sprite_pos_counts_from_one() {
+        assert_eq!(Tilemap::index_to_sprite_pos(1, 4), (1, 1));
+        assert_eq!(Tilemap::index_to_sprite_pos(4, 4), (1, 4));
+        assert_eq!(Tilemap::index_to_sprite_pos(5, 4), (2, 1));
+    }
+
+    #[test]
+    fn visible_tiles_keep_layer_order() {
+        let mut tilemap = Tilemap::new(16.0, 16.0);
+        tilemap.add_layer( TileLayer { tiles: vec![vec![1, 2]], properties: HashMap::new() } );
+        tilemap.add_layer( TileLayer { tiles: vec![vec![0, 3]], properties: HashMap::new() } );
+
+        let tiles: Vec<(usize, u16)> = tilemap.visible_tiles( &Rectangle { x: 0.0, y: 0.0, width: 32.0, height: 16.0 } )
+                                              .into_iter()
+                                              .map(|(layer, _, index)| (layer, index))
+                                              .collect();
+
+        assert_eq!(tiles, vec![(0, 1), (0, 2), (1, 3)]);
+    }
+}
@@ -0,0 +1,76 @@
+use specs::Entity;
+
+use crate::assets::{SpritePos, Stats, CharacterClass};
+
+
+//which team a combatant fights for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Side {
+    Party,
+    Enemies,
+}
+
+
+//Anyone fighting in a battle. Party members remember the entity they came from so the outcome
+//  can be written back to the world, enemies carry the rewards for beating them.
+pub struct Combatant {
+    pub name: String,
+    pub side: Side,
+    pub stats: Stats,
+    pub hp: u32,
+    pub mp: u32,
+    pub defending: bool, //takes half damage until their next turn
+
+    pub sprite: Option<(String, SpritePos)>, //sprite sheet name and sprite
+    pub entity: Option<Entity>,
+
+    //used to tell the party who levels up from a win
+    pub class: Option<CharacterClass>,
+    pub level: u32,
+    pub experience: u32,
+
+    pub experience_reward: u32,
+    pub loot: Vec<String>,
+}
+
+impl Combatant {
+
+    pub fn new(name: String, side: Side, stats: Stats) -> Combatant {
+        Combatant {
+            name,
+            side,
+            stats,
+            hp: stats.max_hp,
+            mp: stats.max_mp,
+            defending: false,
+
+            sprite: None,
+            entity: None,
+
+            class: None,
+            level: 1,
+            experience: 0,
+
+            experience_reward: 0,
+            loot: vec![],
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    //lowers hp and returns how much was actually lost
+    pub fn take_damage(&mut self, amount: u32) -> u32 {
+        let amount = if self.defending { (amount / 2).max(1) } else { amount };
+        let lost = amount.min(self.hp);
+        self.hp -= lost;
+        lost
+    }
+
+    //raises hp and mp up to their limits
+    pub fn restore(&mut self, hp: u32, mp: u32) {
+        self.hp = (self.hp + hp).min(self.stats.max_hp);
+        self.mp = (self.mp + mp).min(self.stats.max_mp);
+    }
+}
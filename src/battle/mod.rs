@@ -0,0 +1,7 @@
+//pull in the fighters
+mod combatant;
+pub use combatant::{Combatant, Side};
+
+//pull in turn handling and rules
+mod rules;
+pub use rules::{Battle, BattleCommand, BattleItem, BattleOutcome, BattleResult, SKILL_MP_COST};
@@ -0,0 +1,503 @@
+use specs::Entity;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use super::{Combatant, Side};
+
+
+//mp a skill costs and how much harder it hits than a normal attack
+pub const SKILL_MP_COST: u32 = 4;
+const SKILL_POWER: u32 = 3;
+
+//how many lines of the battle log are kept
+const LOG_LENGTH: usize = 6;
+
+
+//What the combatant whose turn it is does. Targets are indices into the battle's combatants.
+#[derive(Clone, Copy, Debug)]
+pub enum BattleCommand {
+    Attack(usize),
+    Skill(usize),
+    Item(usize, usize), //index of the item and the target
+    Defend,
+    Flee,
+}
+
+
+//Something the party carries into battle that can be used on a party member.
+pub struct BattleItem {
+    pub name: String,
+    pub quantity: u32,
+    pub restore_hp: u32,
+    pub restore_mp: u32,
+    pub revive: bool, //only works on knocked out party members
+}
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BattleOutcome {
+    Victory,
+    Defeat,
+    Fled,
+}
+
+
+//What happened in a battle, handed back to the map so it can be applied to the world.
+pub struct BattleResult {
+    pub outcome: BattleOutcome,
+    pub party: Vec<(Entity, u32, u32)>, //entity with the hp and mp it has left
+    pub experience: u32, //given to each party member still standing
+    pub loot: Vec<String>,
+    pub items_used: Vec<String>, //one entry for every item used up
+    pub defeated: Vec<String>, //names of the enemies knocked out, even when the party fled
+}
+
+
+//A fight between the party and a group of enemies. Every round everyone still standing acts
+//  once, fastest first.
+pub struct Battle {
+    pub combatants: Vec<Combatant>,
+    pub items: Vec<BattleItem>,
+    pub log: Vec<String>,
+    pub outcome: Option<BattleOutcome>,
+    pub can_flee: bool,
+
+    items_used: Vec<String>,
+    current: Option<usize>,
+    turn_order: Vec<usize>, //combatants still to act this round, next one last
+    rng: StdRng, //kept with the battle so a seeded one makes every roll repeatable
+}
+
+impl Battle {
+
+    pub fn new(combatants: Vec<Combatant>, items: Vec<BattleItem>, can_flee: bool) -> Battle {
+        Battle::with_rng(combatants, items, can_flee, StdRng::from_entropy())
+    }
+
+    fn with_rng(combatants: Vec<Combatant>, items: Vec<BattleItem>, can_flee: bool, rng: StdRng) -> Battle {
+        let mut battle = Battle {
+            combatants,
+            items,
+            log: vec![],
+            outcome: None,
+            can_flee,
+
+            items_used: vec![],
+            current: None,
+            turn_order: vec![],
+            rng,
+        };
+
+        let enemy_names: Vec<&str> = battle.combatants.iter()
+                                           .filter(|combatant| combatant.side == Side::Enemies)
+                                           .map(|combatant| combatant.name.as_str())
+                                           .collect();
+        let message = format!("{} appeared!", enemy_names.join(", "));
+        battle.add_log(message);
+
+        battle.check_outcome();
+        battle.next_turn();
+        battle
+    }
+
+
+    //combatant whose turn it is
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn is_party_turn(&self) -> bool {
+        self.current.map(|index| self.combatants[index].side == Side::Party).unwrap_or(false)
+    }
+
+    //indices of combatants on a side that are still standing
+    pub fn living(&self, side: Side) -> Vec<usize> {
+        self.combatants.iter()
+            .enumerate()
+            .filter(|(_, combatant)| combatant.side == side && combatant.is_alive())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+
+    //the current combatant acts and the turn passes to the next one
+    pub fn perform(&mut self, command: BattleCommand) {
+        let actor = match (self.current, self.outcome) {
+            (Some(actor), None) => actor,
+            _ => return,
+        };
+
+        match command {
+            BattleCommand::Attack(target) => {
+                if !self.is_valid_target(target) { return; }
+
+                let damage = damage(self.combatants[actor].stats.attack * 2, self.combatants[target].stats.defense, &mut self.rng);
+                let dealt = self.combatants[target].take_damage(damage);
+                let message = format!("{} attacks {} for {} damage.", 
+                                      self.combatants[actor].name, self.combatants[target].name, dealt);
+                self.add_log(message);
+                self.report_knock_out(target);
+            },
+
+            BattleCommand::Skill(target) => {
+                if !self.is_valid_target(target) || self.combatants[actor].mp < SKILL_MP_COST { return; }
+
+                self.combatants[actor].mp -= SKILL_MP_COST;
+                let power = self.combatants[actor].stats.magic * SKILL_POWER;
+                let damage = damage(power, self.combatants[target].stats.defense / 2, &mut self.rng);
+                let dealt = self.combatants[target].take_damage(damage);
+                let message = format!("{} casts a spell on {} for {} damage.", 
+                                      self.combatants[actor].name, self.combatants[target].name, dealt);
+                self.add_log(message);
+                self.report_knock_out(target);
+            },
+
+            BattleCommand::Item(item_index, target) => {
+                if !self.can_use_item(item_index, target) { return; }
+
+                let item = &mut self.items[item_index];
+                item.quantity -= 1;
+                let (item_name, restore_hp, restore_mp) = (item.name.clone(), item.restore_hp, item.restore_mp);
+                self.items_used.push(item_name.clone());
+
+                //reviving always leaves the target with at least 1 hp
+                let was_knocked_out = !self.combatants[target].is_alive();
+                let restore_hp = if was_knocked_out { restore_hp.max(1) } else { restore_hp };
+
+                self.combatants[target].restore(restore_hp, restore_mp);
+                let message = format!("{} uses {} on {}.", 
+                                      self.combatants[actor].name, item_name, self.combatants[target].name);
+                self.add_log(message);
+
+                if was_knocked_out {
+                    let message = format!("{} is back on their feet!", self.combatants[target].name);
+                    self.add_log(message);
+                }
+            },
+
+            BattleCommand::Defend => {
+                self.combatants[actor].defending = true;
+                let message = format!("{} defends.", self.combatants[actor].name);
+                self.add_log(message);
+            },
+
+            BattleCommand::Flee => {
+                if !self.can_flee { return; }
+
+                let flee_chance = self.flee_chance();
+                if self.rng.gen_bool(flee_chance) {
+                    self.add_log("The party got away!".to_string());
+                    self.outcome = Some(BattleOutcome::Fled);
+                    return;
+                }
+
+                self.add_log("Couldn't get away!".to_string());
+            },
+        }
+
+        self.check_outcome();
+        self.next_turn();
+    }
+
+
+    //picks what the current enemy does. Enemies attack a random party member and sometimes use
+    //  a skill when they have the mp for it.
+    pub fn choose_enemy_command(&mut self) -> BattleCommand {
+        let target = match self.living(Side::Party).choose(&mut self.rng) {
+            Some(target) => *target,
+            None => return BattleCommand::Defend,
+        };
+
+        let actor = &self.combatants[self.current.unwrap_or(0)];
+        if actor.stats.magic > 0 && actor.mp >= SKILL_MP_COST && self.rng.gen_bool(0.3) {
+            BattleCommand::Skill(target)
+        } else {
+            BattleCommand::Attack(target)
+        }
+    }
+
+
+    //messages for every party member still standing that a win would level up
+    pub fn level_ups(&self) -> Vec<String> {
+        let experience = self.result().experience;
+        if experience == 0 { return vec![]; }
+
+        self.living(Side::Party).iter()
+            .map(|index| &self.combatants[*index])
+            .filter_map(|member| {
+                let class = member.class.as_ref()?;
+                let new_level = class.level_for_experience(member.experience.saturating_add(experience));
+                if new_level > member.level {
+                    Some(format!("{} reached level {}!", member.name, new_level))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+
+    //what should be handed back to the map once the battle is over
+    pub fn result(&self) -> BattleResult {
+        let outcome = self.outcome.unwrap_or(BattleOutcome::Fled);
+
+        let (experience, loot) = match outcome {
+            BattleOutcome::Victory => {
+                let enemies = self.combatants.iter().filter(|combatant| combatant.side == Side::Enemies);
+                (
+                    enemies.clone().map(|enemy| enemy.experience_reward).sum(),
+                    enemies.flat_map(|enemy| enemy.loot.iter().cloned()).collect(),
+                )
+            },
+            _ => (0, vec![]),
+        };
+
+        BattleResult {
+            outcome,
+            party: self.combatants.iter()
+                       .filter_map(|combatant| combatant.entity.map(|entity| (entity, combatant.hp, combatant.mp)))
+                       .collect(),
+            experience,
+            loot,
+            items_used: self.items_used.clone(),
+            defeated: self.combatants.iter()
+                          .filter(|combatant| combatant.side == Side::Enemies && !combatant.is_alive())
+                          .map(|combatant| combatant.name.clone())
+                          .collect(),
+        }
+    }
+
+
+    //items go on party members. Reviving items only work on knocked out ones, the rest only on
+    //  those still standing.
+    pub fn can_use_item(&self, item: usize, target: usize) -> bool {
+        match (self.items.get(item), self.combatants.get(target)) {
+            (Some(item), Some(target)) => 
+                item.quantity > 0 && target.side == Side::Party && item.revive != target.is_alive(),
+            _ => false,
+        }
+    }
+
+
+    fn is_valid_target(&self, target: usize) -> bool {
+        self.combatants.get(target).map(|combatant| combatant.is_alive()).unwrap_or(false)
+    }
+
+    fn report_knock_out(&mut self, target: usize) {
+        if !self.combatants[target].is_alive() {
+            let message = format!("{} is knocked out!", self.combatants[target].name);
+            self.add_log(message);
+        }
+    }
+
+    //better odds the faster the party is compared to the enemies
+    fn flee_chance(&self) -> f64 {
+        let average_speed = |side: Side| {
+            let living = self.living(side);
+            let total: u32 = living.iter().map(|index| self.combatants[*index].stats.speed).sum();
+            f64::from(total) / living.len().max(1) as f64
+        };
+
+        (0.5 + (average_speed(Side::Party) - average_speed(Side::Enemies)) * 0.05).clamp(0.1, 0.95)
+    }
+
+    fn check_outcome(&mut self) {
+        if self.living(Side::Enemies).is_empty() {
+            self.add_log("Victory!".to_string());
+            self.outcome = Some(BattleOutcome::Victory);
+
+        } else if self.living(Side::Party).is_empty() {
+            self.add_log("The party was defeated...".to_string());
+            self.outcome = Some(BattleOutcome::Defeat);
+        }
+    }
+
+    //moves to the next combatant still standing, starting a new round when everyone has acted
+    fn next_turn(&mut self) {
+        if self.outcome.is_some() {
+            self.current = None;
+            return;
+        }
+
+        loop {
+            if self.turn_order.is_empty() {
+                self.start_round();
+            }
+
+            match self.turn_order.pop() {
+                Some(next) if self.combatants[next].is_alive() => {
+                    //defending only lasts until the defender acts again
+                    self.combatants[next].defending = false;
+                    self.current = Some(next);
+                    return;
+                },
+                Some(_) => continue,
+                None => {
+                    self.current = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    //fastest combatants act first, the party wins ties
+    fn start_round(&mut self) {
+        let mut order: Vec<usize> = (0..self.combatants.len())
+                                        .filter(|index| self.combatants[*index].is_alive())
+                                        .collect();
+
+        order.sort_by_key(|index| {
+            let combatant = &self.combatants[*index];
+            (combatant.stats.speed, combatant.side == Side::Party)
+        });
+
+        self.turn_order = order;
+    }
+
+    fn add_log(&mut self, message: String) {
+        self.log.push(message);
+        if self.log.len() > LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+}
+
+
+//damage before defending is taken into account, varies a bit so battles aren't predictable
+fn damage(power: u32, defense: u32, rng: &mut impl Rng) -> u32 {
+    let base = power.saturating_sub(defense).max(1) as f32;
+    (base * rng.gen_range(0.9, 1.1)).round().max(1.0) as u32
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::assets::Stats;
+
+    fn fighter(name: &str, side: Side, speed: u32) -> Combatant {
+        Combatant::new(name.to_string(), side, Stats { max_hp: 30, max_mp: 10, attack: 5, defense: 2, magic: 3, speed })
+    }
+
+    fn potion(quantity: u32, revive: bool) -> BattleItem {
+        BattleItem { name: "Potion".to_string(), quantity, restore_hp: 10, restore_mp: 0, revive }
+    }
+
+    fn battle(combatants: Vec<Combatant>, items: Vec<BattleItem>, seed: u64) -> Battle {
+        Battle::with_rng(combatants, items, true, StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn damage_stays_close_to_power_minus_defense() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let dealt = damage(30, 10, &mut rng);
+            assert!((18..=22).contains(&dealt), "{} is outside 20 +-10%", dealt);
+        }
+    }
+
+    #[test]
+    fn damage_is_at_least_one() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(damage(2, 50, &mut rng), 1);
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_battle() {
+        let play = |seed| {
+            let mut battle = battle(vec![fighter("Sara", Side::Party, 5), fighter("Slime", Side::Enemies, 3)], vec![], seed);
+            battle.perform(BattleCommand::Attack(1));
+            battle.combatants[1].hp
+        };
+
+        assert_eq!(play(3), play(3));
+    }
+
+    #[test]
+    fn defending_halves_damage() {
+        let mut target = fighter("Sara", Side::Party, 5);
+        target.defending = true;
+        assert_eq!(target.take_damage(10), 5);
+        assert_eq!(target.take_damage(1), 1);
+    }
+
+    #[test]
+    fn fastest_acts_first_and_party_wins_ties() {
+        let mut battle = battle(vec![fighter("Slime", Side::Enemies, 5),
+                                     fighter("Sara", Side::Party, 5),
+                                     fighter("Bat", Side::Enemies, 9),
+                                     fighter("Tom", Side::Party, 1)], vec![], 1);
+
+        let mut order = vec![];
+        for _ in 0..4 {
+            order.push(battle.current().unwrap());
+            battle.perform(BattleCommand::Defend);
+        }
+
+        assert_eq!(order, vec![2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn knocked_out_combatants_lose_their_turn() {
+        let mut slime = fighter("Slime", Side::Enemies, 5);
+        slime.hp = 0;
+        let battle = battle(vec![fighter("Sara", Side::Party, 1), slime, fighter("Bat", Side::Enemies, 3)], vec![], 1);
+
+        assert_eq!(battle.current(), Some(2));
+    }
+
+    #[test]
+    fn flee_chance_follows_speed_and_is_clamped() {
+        let even = battle(vec![fighter("Sara", Side::Party, 5), fighter("Slime", Side::Enemies, 5)], vec![], 1);
+        assert!((even.flee_chance() - 0.5).abs() < 1e-9);
+
+        let fast = battle(vec![fighter("Sara", Side::Party, 99), fighter("Slime", Side::Enemies, 1)], vec![], 1);
+        assert!((fast.flee_chance() - 0.95).abs() < 1e-9);
+
+        let slow = battle(vec![fighter("Sara", Side::Party, 1), fighter("Slime", Side::Enemies, 99)], vec![], 1);
+        assert!((slow.flee_chance() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fleeing_is_not_allowed_in_some_battles() {
+        let mut battle = battle(vec![fighter("Sara", Side::Party, 99), fighter("Slime", Side::Enemies, 1)], vec![], 1);
+        battle.can_flee = false;
+
+        battle.perform(BattleCommand::Flee);
+        assert_eq!(battle.outcome, None);
+        assert_eq!(battle.current(), Some(0));
+    }
+
+    #[test]
+    fn items_heal_and_are_used_up() {
+        let mut battle = battle(vec![fighter("Sara", Side::Party, 9), fighter("Slime", Side::Enemies, 1)],
+                                vec![potion(1, false)], 1);
+        battle.combatants[0].hp = 5;
+
+        battle.perform(BattleCommand::Item(0, 0));
+
+        assert_eq!(battle.combatants[0].hp, 15);
+        assert_eq!(battle.items[0].quantity, 0);
+        assert_eq!(battle.result().items_used, vec!["Potion".to_string()]);
+        assert!(!battle.can_use_item(0, 0));
+    }
+
+    #[test]
+    fn reviving_items_only_work_on_knocked_out_members() {
+        let mut battle = battle(vec![fighter("Sara", Side::Party, 9), fighter("Tom", Side::Party, 1),
+                                     fighter("Slime", Side::Enemies, 1)],
+                                vec![potion(1, false), BattleItem { restore_hp: 0, ..potion(1, true) }], 1);
+        battle.combatants[1].hp = 0;
+
+        assert!(!battle.can_use_item(0, 1));
+        assert!(!battle.can_use_item(1, 0));
+        assert!(!battle.can_use_item(0, 2)); //enemies can't be targeted
+
+        battle.perform(BattleCommand::Item(1, 1));
+        assert_eq!(battle.combatants[1].hp, 1);
+    }
+}
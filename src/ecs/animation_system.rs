@@ -1,7 +1,8 @@
-use specs::{WriteExpect, WriteStorage, System};
+use specs::{WriteExpect, ReadExpect, WriteStorage, System};
 
 use super::gfx_components::VisualComponent;
 use super::gfx_components::AnimationComponent;
+use super::resources::GameClock;
 
 use crate::assets::{AssetDatabase, AssetContainer};
 
@@ -9,23 +10,24 @@ use crate::assets::{AssetDatabase, AssetContainer};
 pub struct AnimationSystem;
 
 impl<'a> System<'a> for AnimationSystem {
-    type SystemData = ( WriteExpect<'a, AssetDatabase>,
+    type SystemData = ( ReadExpect<'a, GameClock>,
+                        WriteExpect<'a, AssetDatabase>,
                         WriteStorage<'a, VisualComponent>,
                         WriteStorage<'a, AnimationComponent>);
 
-    fn run(&mut self, (mut asset_database, mut visual, mut animation): Self::SystemData) {
+    fn run(&mut self, (clock, mut asset_database, mut visual, mut animation): Self::SystemData) {
         use specs::Join;
 
         for (visual, animation) in (&mut visual, &mut animation).join() {
 
             //first only advance animation if timer is finished
-            if animation.timer > 0 {
-                //TODO use an actual delta time for the count down
-                animation.timer -= 1;
+            animation.timer -= clock.delta_seconds;
+            if animation.timer > 0.0 {
                 continue;
 
             } else {
-                animation.timer = animation.speed;
+                //carry over the extra time so long frames don't make the animation drift
+                animation.timer = (animation.timer + animation.frame_duration).max(0.0);
             }
 
 
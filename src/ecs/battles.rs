@@ -0,0 +1,162 @@
+use specs::{World, WorldExt};
+
+use coffee::graphics::{Image, Rectangle};
+
+use super::{CharacterComponent, LevelComponent, BaseStatsComponent, VitalsComponent, 
+            VisualComponent, EquipmentComponent, Party};
+use super::equipment::{equipment_modifiers, stats_with_equipment};
+use super::leveling::{find_class, gain_experience};
+use super::inventory::{add_item, remove_item};
+use super::{Inventory, GameEvent, GameEvents};
+
+use crate::assets::{AssetDatabase, AssetContainer};
+use crate::battle::{Battle, BattleItem, BattleOutcome, BattleResult, Combatant, Side};
+
+
+//picture of each combatant in a battle, in the same order as the combatants
+pub type CombatantSprites = Vec<Option<(Image, Rectangle<u16>)>>;
+
+
+//sets up a battle between the active party members and an encounter's enemies.
+pub fn create_battle(world: &World, encounter_name: &str) -> Option<(Battle, CombatantSprites)> {
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+
+    let (enemies, can_flee) = match asset_database.get_asset(&encounter_name.to_string()) {
+        AssetContainer::Encounter(encounter) => (encounter.enemies.clone(), encounter.can_flee),
+        _ => {
+            warn!("[Battle] {} {} {}.",
+                  "Requested encounter",
+                  encounter_name,
+                  "does not exist",
+            );
+            return None;
+        }
+    };
+
+    let characters = world.read_storage::<CharacterComponent>();
+    let levels = world.read_storage::<LevelComponent>();
+    let base_stats = world.read_storage::<BaseStatsComponent>();
+    let vitals = world.read_storage::<VitalsComponent>();
+    let visuals = world.read_storage::<VisualComponent>();
+    let equipment = world.read_storage::<EquipmentComponent>();
+
+    let mut fighters = vec![];
+
+    let active_members = world.read_resource::<Party>().active.clone();
+    for entity in active_members {
+        let (character, level, base_stats, vitals) = 
+            match (characters.get(entity), levels.get(entity), base_stats.get(entity), vitals.get(entity)) {
+                (Some(character), Some(level), Some(base_stats), Some(vitals)) => (character, level, base_stats, vitals),
+                _ => continue,
+            };
+
+        //party members fight with their equipment on
+        let stats = stats_with_equipment(&mut asset_database, base_stats.stats, equipment.get(entity));
+
+        let mut member = Combatant::new(character.name.clone(), Side::Party, stats);
+        member.hp = vitals.hp.min(stats.max_hp);
+        member.mp = vitals.mp.min(stats.max_mp);
+        member.sprite = visuals.get(entity).map(|visual| (visual.sprite_sheet_name.clone(), visual.sprite_location));
+        member.entity = Some(entity);
+        member.class = find_class(&mut asset_database, &character.class);
+        member.level = level.level;
+        member.experience = level.experience;
+
+        fighters.push(member);
+    }
+
+    if fighters.is_empty() {
+        warn!("[Battle] Nobody in the party can fight so encounter {} was skipped.", encounter_name);
+        return None;
+    }
+
+    for enemy in enemies.iter() {
+        let character = match asset_database.get_asset(&enemy.character) {
+            AssetContainer::Character(character) => character.clone(),
+            _ => {
+                warn!("[Battle] Encounter {} uses character {} which does not exist.", encounter_name, enemy.character);
+                continue;
+            }
+        };
+
+        let class = match find_class(&mut asset_database, &character.class) {
+            Some(class) => class,
+            None => continue,
+        };
+
+        let level = enemy.level.unwrap_or(character.level).clamp(1, class.max_level);
+        let stats = equipment_modifiers(&mut asset_database, &character.equipment).apply_to(class.stats_at(level));
+        let mut fighter = Combatant::new(character.name.clone(), Side::Enemies, stats);
+        fighter.sprite = character.sprite.clone();
+        fighter.level = level;
+        fighter.experience_reward = character.experience_reward;
+        fighter.loot = character.loot.clone();
+
+        fighters.push(fighter);
+    }
+
+    let sprites = fighters.iter().map(|fighter| {
+        fighter.sprite.as_ref().and_then(|(sprite_sheet_name, (row, column))| {
+            match asset_database.get_asset(sprite_sheet_name) {
+                AssetContainer::Spritesheet(sprite_sheet) => Some(sprite_sheet.sprite_image(*row, *column)),
+                _ => None,
+            }
+        })
+    }).collect();
+
+    //only items that do something in a fight are brought along
+    let items = world.read_resource::<Inventory>().items.iter()
+        .filter_map(|(item_name, count)| match asset_database.get_asset(item_name) {
+            AssetContainer::Item(item) if item.is_usable() => item.effect.map(|effect| BattleItem {
+                name: item_name.clone(),
+                quantity: *count,
+                restore_hp: effect.restore_hp,
+                restore_mp: effect.restore_mp,
+                revive: effect.revive,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Some( (Battle::new(fighters, items, can_flee), sprites) )
+}
+
+
+//writes what happened in a battle back onto the party's entities
+pub fn apply_battle_result(world: &World, result: &BattleResult) {
+    {
+        let mut vitals = world.write_storage::<VitalsComponent>();
+
+        for (entity, hp, mp) in result.party.iter() {
+            if let Some(vitals) = vitals.get_mut(*entity) {
+                vitals.hp = *hp;
+                vitals.mp = *mp;
+            }
+        }
+    }
+
+    if result.outcome == BattleOutcome::Victory {
+        for (entity, hp, _) in result.party.iter() {
+            if *hp == 0 { continue; }
+
+            for message in gain_experience(world, *entity, result.experience) {
+                info!("[Battle] {}", message);
+            }
+        }
+    }
+
+    for item in result.items_used.iter() {
+        remove_item(world, item, 1);
+    }
+
+    for item in result.loot.iter() {
+        if add_item(world, item, 1) > 0 {
+            info!("[Battle] The party found {}.", item);
+        }
+    }
+
+    let mut game_events = world.write_resource::<GameEvents>();
+    for enemy in result.defeated.iter() {
+        game_events.announce( GameEvent::Defeated(enemy.clone()) );
+    }
+}
@@ -0,0 +1,49 @@
+use specs::{Entities, WriteExpect, ReadExpect, ReadStorage, System};
+
+use super::position_component::PositionComponent;
+use super::control_components::PlayerControlComponent;
+use super::resources::{Camera, CurrentMap, ViewArea, GameClock};
+
+use crate::assets::{AssetDatabase, AssetContainer};
+
+//the camera system moves the camera toward the entity it follows, keeps it on the map,
+//  and updates the visible area used by the other render systems
+pub struct CameraSystem;
+
+impl<'a> System<'a> for CameraSystem {
+    type SystemData = ( Entities<'a>,
+                        ReadExpect<'a, GameClock>,
+                        WriteExpect<'a, Camera>,
+                        WriteExpect<'a, ViewArea>,
+                        ReadExpect<'a, CurrentMap>,
+                        WriteExpect<'a, AssetDatabase>,
+                        ReadStorage<'a, PositionComponent>,
+                        ReadStorage<'a, PlayerControlComponent>);
+
+    fn run(&mut self, (entities, clock, mut camera, mut view_area, current_map, mut asset_database, 
+                       position, control): Self::SystemData) {
+        use specs::Join;
+
+        //follow the chosen entity, or the player controlled one if nothing was chosen
+        let target_position = match camera.target.and_then(|target| position.get(target)) {
+            Some(target_position) => Some(target_position.map_pos),
+            None => (&entities, &position, &control).join()
+                        .next()
+                        .map(|(_, position, _)| position.map_pos),
+        };
+
+        if let Some(target_position) = target_position {
+            camera.move_toward(target_position, clock.delta_seconds);
+        }
+
+        //keep the view on the map if there is one
+        if let Some(map_name) = &current_map.name {
+            if let AssetContainer::Tilemap(tilemap) = asset_database.get_asset(map_name) {
+                let (map_width, map_height) = tilemap.size_in_pixels();
+                camera.clamp_to(map_width, map_height);
+            }
+        }
+
+        view_area.area = camera.view_area();
+    }
+}
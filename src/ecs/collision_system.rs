@@ -0,0 +1,102 @@
+use specs::{Entities, Entity, WriteExpect, ReadExpect, ReadStorage, WriteStorage, System};
+
+use coffee::graphics::{Rectangle, Vector};
+
+use super::position_component::PositionComponent;
+use super::physics_components::{MovementComponent, ColliderComponent};
+use super::resources::CurrentMap;
+
+use crate::assets::{AssetDatabase, AssetContainer, Tilemap, areas_overlap};
+
+//how many times we halve a blocked movement looking for the furthest free spot
+const CONTACT_SEARCH_STEPS: u32 = 6;
+
+//the collision system applies each entity's movement while keeping colliders out of solid tiles
+//  and out of each other. Movement is resolved one axis at a time so entities slide along walls.
+pub struct CollisionSystem;
+
+impl<'a> System<'a> for CollisionSystem {
+    type SystemData = ( Entities<'a>,
+                        WriteExpect<'a, AssetDatabase>,
+                        ReadExpect<'a, CurrentMap>,
+                        WriteStorage<'a, PositionComponent>,
+                        WriteStorage<'a, MovementComponent>,
+                        ReadStorage<'a, ColliderComponent>);
+
+    fn run(&mut self, (entities, mut asset_database, current_map, 
+                       mut position, mut movement, collider): Self::SystemData) {
+        use specs::Join;
+
+        let tilemap = match &current_map.name {
+            Some(map_name) => match asset_database.get_asset(map_name) {
+                AssetContainer::Tilemap(tilemap) => Some(&*tilemap),
+                _ => None,
+            },
+            None => None,
+        };
+
+        //every collider's area before anything moves this update
+        let mut obstacles: Vec<(Entity, Rectangle<f32>)> = (&entities, &position, &collider).join()
+            .map(|(entity, position, collider)| (entity, collider.area_at(position.map_pos)))
+            .collect();
+
+        for (entity, position, movement) in (&entities, &mut position, &mut movement).join() {
+            let displacement = movement.displacement;
+            movement.displacement = Vector::new(0.0, 0.0);
+
+            if displacement.x == 0.0 && displacement.y == 0.0 { continue; }
+
+            //things without a collider go wherever they want
+            let collider = match collider.get(entity) {
+                Some(collider) => collider,
+                None => {
+                    position.map_pos += displacement;
+                    continue;
+                }
+            };
+
+            let blocked = |area: &Rectangle<f32>| -> bool {
+                tilemap.map(|tilemap: &Tilemap| tilemap.is_area_solid(area)).unwrap_or(false)
+                || obstacles.iter().any(|(other, other_area)| *other != entity && areas_overlap(other_area, area))
+            };
+
+            //if we are already stuck in something let the entity walk out of it
+            if blocked(&collider.area_at(position.map_pos)) {
+                position.map_pos += displacement;
+            } else {
+                let x_move = allowed_move(|amount| blocked(&collider.area_at(position.map_pos + Vector::new(amount, 0.0))),
+                                          displacement.x);
+                position.map_pos.x += x_move;
+
+                let y_move = allowed_move(|amount| blocked(&collider.area_at(position.map_pos + Vector::new(0.0, amount))),
+                                          displacement.y);
+                position.map_pos.y += y_move;
+            }
+
+            //later movers need to collide with where this entity ended up
+            if let Some(obstacle) = obstacles.iter_mut().find(|(other, _)| *other == entity) {
+                obstacle.1 = collider.area_at(position.map_pos);
+            }
+        }
+    }
+}
+
+
+//finds how much of a movement along one axis can happen before hitting something
+fn allowed_move<F: Fn(f32) -> bool>(is_blocked: F, amount: f32) -> f32 {
+    if amount == 0.0 || !is_blocked(amount) {
+        return amount;
+    }
+
+    //halve the distance until we find the furthest free spot
+    let mut free = 0.0;
+    let mut step = amount / 2.0;
+    for _ in 0..CONTACT_SEARCH_STEPS {
+        if !is_blocked(free + step) {
+            free += step;
+        }
+        step /= 2.0;
+    }
+
+    free
+}
@@ -4,5 +4,13 @@ use specs::{Component, VecStorage};
 #[derive(Component)]
 #[storage(VecStorage)]
 pub struct PlayerControlComponent {
-    pub speed: f32,
+    pub speed: f32, //pixels per second
+}
+
+
+//For entities the player can talk to by walking up to them and pressing the interact key
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct InteractionComponent {
+    pub dialogue: String, //name of the dialogue asset to start
 }
@@ -0,0 +1,262 @@
+use specs::{World, WorldExt};
+
+use coffee::graphics::{Image, Rectangle};
+
+use super::{ActiveDialogue, GameFlags, Inventory};
+
+use crate::assets::{AssetDatabase, AssetContainer, Dialogue};
+
+
+//guards against nodes whose 'otherwise' links loop back on each other
+const MAX_NODE_JUMPS: usize = 32;
+
+
+//What the dialogue box needs to show for the current node.
+pub struct DialogueView {
+    pub speaker: String,
+    pub text: String,
+    pub portrait: Option<(Image, Rectangle<u16>)>,
+    pub choices: Vec<(usize, String)>, //index in the node's choices and the choice text
+}
+
+
+//moves the conversation to a node. Nodes whose conditions aren't met pass on to their
+//  'otherwise' node. Effects of the node that ends up shown are applied.
+//  returns: false when the conversation ended instead
+fn enter_node(active: &mut ActiveDialogue, dialogue: &Dialogue, node_id: Option<String>, flags: &mut GameFlags,
+              inventory: &Inventory) -> bool {
+    let mut node_id = node_id;
+
+    for _ in 0..MAX_NODE_JUMPS {
+        let id = match node_id {
+            Some(id) => id,
+            None => break,
+        };
+
+        let node = match dialogue.node(&id) {
+            Some(node) => node,
+            None => {
+                warn!("[Dialogue] Node {} does not exist, ending the conversation.", id);
+                break;
+            }
+        };
+
+        if flags.meets(&node.conditions, inventory) {
+            flags.apply(&node.effects, inventory);
+            active.node_id = id;
+            return true;
+        }
+
+        node_id = node.otherwise.clone();
+    }
+
+    active.end();
+    false
+}
+
+
+//starts a conversation from its first node
+pub fn start_dialogue(active: &mut ActiveDialogue, asset_database: &mut AssetDatabase, 
+                      flags: &mut GameFlags, inventory: &Inventory, dialogue_name: &str) {
+
+    match asset_database.get_asset(&dialogue_name.to_string()) {
+        AssetContainer::Dialogue(dialogue) => {
+            active.dialogue_name = Some(dialogue_name.to_string());
+            enter_node(active, dialogue, Some(dialogue.start.clone()), flags, inventory);
+        },
+        _ => warn!("[Dialogue] {} {} {}.",
+                   "Requested dialogue",
+                   dialogue_name,
+                   "does not exist",
+        ),
+    }
+}
+
+
+//moves past the current node. Nodes with choices need one picked, anything else just continues.
+pub fn advance_dialogue(world: &World, choice: Option<usize>) {
+    let mut active = world.write_resource::<ActiveDialogue>();
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let mut flags = world.write_resource::<GameFlags>();
+    let inventory = world.read_resource::<Inventory>();
+
+    let dialogue = match &active.dialogue_name {
+        Some(dialogue_name) => match asset_database.get_asset(dialogue_name) {
+            AssetContainer::Dialogue(dialogue) => dialogue,
+            _ => { active.end(); return; }
+        },
+        None => return,
+    };
+
+    let node = match dialogue.node(&active.node_id) {
+        Some(node) => node,
+        None => { active.end(); return; }
+    };
+
+    //only choices that are shown can be picked. When every choice is hidden the box shows a
+    //  continue button instead so the node carries on like one without choices.
+    let any_visible = node.choices.iter().any(|choice| flags.meets(&choice.conditions, &inventory));
+
+    let next = if !any_visible {
+        node.next.clone()
+    } else {
+        match choice.and_then(|index| node.choices.get(index)) {
+            Some(picked) if flags.meets(&picked.conditions, &inventory) => {
+                flags.apply(&picked.effects, &inventory);
+                picked.next.clone()
+            },
+            _ => return,
+        }
+    };
+
+    enter_node(&mut active, dialogue, next, &mut flags, &inventory);
+}
+
+
+//gathers what should be shown for the current node, None when not in a conversation
+pub fn dialogue_view(world: &World) -> Option<DialogueView> {
+    let active = world.read_resource::<ActiveDialogue>();
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let flags = world.read_resource::<GameFlags>();
+    let inventory = world.read_resource::<Inventory>();
+
+    let dialogue_name = active.dialogue_name.as_ref()?;
+
+    let (speaker, text, portrait, choices) = match asset_database.get_asset(dialogue_name) {
+        AssetContainer::Dialogue(dialogue) => {
+            let node = dialogue.node(&active.node_id)?;
+
+            let choices = node.choices.iter()
+                              .enumerate()
+                              .filter(|(_, choice)| flags.meets(&choice.conditions, &inventory))
+                              .map(|(index, choice)| (index, choice.text.clone()))
+                              .collect();
+
+            let portrait = node.portrait.as_ref()
+                               .map(|portrait| (portrait.sprite_sheet_name.clone(), portrait.sprite_location));
+
+            (node.speaker.clone(), node.text.clone(), portrait, choices)
+        },
+        _ => return None,
+    };
+
+    let portrait = portrait.and_then(|(sprite_sheet_name, (row, column))| {
+        match asset_database.get_asset(&sprite_sheet_name) {
+            AssetContainer::Spritesheet(sprite_sheet) => Some(sprite_sheet.sprite_image(row, column)),
+            _ => None,
+        }
+    });
+
+    Some( DialogueView { speaker, text, portrait, choices } )
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::assets::{DialogueNode, DialogueChoice, Expression, FlagEffect};
+
+    fn node(text: &str, next: Option<&str>, choices: Vec<DialogueChoice>) -> DialogueNode {
+        DialogueNode {
+            speaker: "Sara".to_string(),
+            text: text.to_string(),
+            portrait: None,
+            choices,
+            next: next.map(|next| next.to_string()),
+            conditions: vec![],
+            otherwise: None,
+            effects: vec![],
+        }
+    }
+
+    fn choice(next: &str, condition: &str) -> DialogueChoice {
+        DialogueChoice {
+            text: next.to_string(),
+            next: Some(next.to_string()),
+            conditions: vec![Expression::parse(condition).unwrap()],
+            effects: vec![],
+        }
+    }
+
+    fn world_with(dialogue: Dialogue) -> World {
+        let mut asset_database = AssetDatabase::new();
+        asset_database.add_asset("talk".to_string(), AssetContainer::Dialogue(dialogue));
+
+        let mut world = World::new();
+        world.insert(asset_database);
+        world.insert(GameFlags::new());
+        world.insert(ActiveDialogue::new());
+        let mut inventory = Inventory::new();
+        inventory.add("potion", 2, 99);
+        world.insert(inventory);
+
+        {
+            let mut active = world.write_resource::<ActiveDialogue>();
+            let mut asset_database = world.write_resource::<AssetDatabase>();
+            let mut flags = world.write_resource::<GameFlags>();
+            let inventory = world.read_resource::<Inventory>();
+            start_dialogue(&mut active, &mut asset_database, &mut flags, &inventory, "talk");
+        }
+
+        world
+    }
+
+    #[test]
+    fn hidden_choices_fall_back_to_next() {
+        let mut dialogue = Dialogue::new("ask".to_string());
+        dialogue.add_node("ask".to_string(), node("Well?", Some("after"), vec![choice("secret", "knows secret")]));
+        dialogue.add_node("after".to_string(), node("Never mind.", None, vec![]));
+        dialogue.add_node("secret".to_string(), node("You know!", None, vec![]));
+
+        let world = world_with(dialogue);
+        advance_dialogue(&world, None);
+
+        assert_eq!(world.read_resource::<ActiveDialogue>().node_id, "after");
+    }
+
+    #[test]
+    fn hidden_choices_without_next_end_the_conversation() {
+        let mut dialogue = Dialogue::new("ask".to_string());
+        dialogue.add_node("ask".to_string(), node("Well?", None, vec![choice("secret", "knows secret")]));
+        dialogue.add_node("secret".to_string(), node("You know!", None, vec![]));
+
+        let world = world_with(dialogue);
+        advance_dialogue(&world, None);
+
+        assert!(!world.read_resource::<ActiveDialogue>().is_active());
+    }
+
+    #[test]
+    fn visible_choices_need_one_picked() {
+        let mut dialogue = Dialogue::new("ask".to_string());
+        dialogue.add_node("ask".to_string(), node("Well?", Some("after"), vec![choice("secret", "!knows secret")]));
+        dialogue.add_node("after".to_string(), node("Never mind.", None, vec![]));
+        dialogue.add_node("secret".to_string(), node("Tell me!", None, vec![]));
+
+        let world = world_with(dialogue);
+        advance_dialogue(&world, None);
+        assert_eq!(world.read_resource::<ActiveDialogue>().node_id, "ask");
+
+        advance_dialogue(&world, Some(0));
+        assert_eq!(world.read_resource::<ActiveDialogue>().node_id, "secret");
+    }
+
+    #[test]
+    fn choices_can_check_items_and_change_values() {
+        let mut buy = choice("thanks", "has_item(potion) && item_count(potion) >= 2");
+        buy.effects = vec![FlagEffect::parse("potions sold = potions sold + 2").unwrap()];
+
+        let mut dialogue = Dialogue::new("ask".to_string());
+        dialogue.add_node("ask".to_string(), node("Sell me potions?", None, vec![buy, choice("never", "has_item(ether)")]));
+        dialogue.add_node("thanks".to_string(), node("Thanks!", None, vec![]));
+
+        let world = world_with(dialogue);
+        assert_eq!(dialogue_view(&world).unwrap().choices.len(), 1);
+
+        advance_dialogue(&world, Some(0));
+        assert_eq!(world.read_resource::<ActiveDialogue>().node_id, "thanks");
+        assert_eq!(world.read_resource::<GameFlags>().get("potions sold"), Some(&crate::assets::Variable::Int(2)));
+    }
+}
@@ -0,0 +1,97 @@
+use specs::{ReadExpect, WriteExpect, ReadStorage, System};
+
+use coffee::graphics::{Point, Rectangle};
+
+use rand::Rng;
+
+use super::position_component::PositionComponent;
+use super::physics_components::ColliderComponent;
+use super::control_components::PlayerControlComponent;
+use super::resources::{CurrentMap, PendingBattle, EventQueue, SceneManager};
+
+use crate::assets::{AssetDatabase, AssetContainer, areas_overlap};
+
+
+//average distance in pixels walked inside an encounter area between battles
+const DEFAULT_ENCOUNTER_DISTANCE: f32 = 600.0;
+
+
+//starts random battles while the player walks through 'encounter' objects on the map. The
+//  object's 'encounter' property names the encounter asset and 'distance' optionally sets how
+//  far the player walks on average between battles.
+pub struct EncounterSystem {
+    last_position: Option<Point>,
+}
+
+impl EncounterSystem {
+
+    pub fn new() -> EncounterSystem {
+        EncounterSystem {
+            last_position: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for EncounterSystem {
+    type SystemData = ( WriteExpect<'a, AssetDatabase>,
+                        ReadExpect<'a, CurrentMap>,
+                        WriteExpect<'a, PendingBattle>,
+                        ReadExpect<'a, EventQueue>,
+                        ReadExpect<'a, SceneManager>,
+                        ReadStorage<'a, PositionComponent>,
+                        ReadStorage<'a, ColliderComponent>,
+                        ReadStorage<'a, PlayerControlComponent>);
+
+    fn run(&mut self, (mut asset_database, current_map, mut pending_battle, event_queue, scene,
+                       position, collider, player_control): Self::SystemData) {
+        use specs::Join;
+
+        let player = (&position, collider.maybe(), &player_control).join().next();
+        let (player_position, player_collider) = match player {
+            Some((position, collider, _)) => (position.map_pos, collider),
+            None => return,
+        };
+
+        let walked = match self.last_position.replace(player_position) {
+            Some(last) => ((player_position.x - last.x).powi(2) + (player_position.y - last.y).powi(2)).sqrt(),
+            None => 0.0,
+        };
+
+        //walking done by an event script or arriving on a new map doesn't count
+        if walked == 0.0 || pending_battle.encounter.is_some() || event_queue.is_running() || scene.is_changing() { return; }
+
+        let tilemap = match &current_map.name {
+            Some(map_name) => match asset_database.get_asset(map_name) {
+                AssetContainer::Tilemap(tilemap) => tilemap,
+                _ => return,
+            },
+            None => return,
+        };
+
+        let player_area = match player_collider {
+            Some(collider) => collider.area_at(player_position),
+            None => Rectangle { x: player_position.x, y: player_position.y, width: 1.0, height: 1.0 },
+        };
+
+        let mut rng = rand::thread_rng();
+
+        for object in tilemap.objects.iter().filter(|object| object.kind == "encounter") {
+            if !areas_overlap(&object.area, &player_area) { continue; }
+
+            let encounter_name = match object.properties.get("encounter") {
+                Some(encounter_name) => encounter_name,
+                None => continue,
+            };
+
+            let distance = object.properties.get("distance")
+                                 .and_then(|distance| distance.parse::<f32>().ok())
+                                 .unwrap_or(DEFAULT_ENCOUNTER_DISTANCE)
+                                 .max(1.0);
+
+            if rng.gen_bool( f64::from((walked / distance).min(1.0)) ) {
+                pending_battle.encounter = Some(encounter_name.clone());
+                return;
+            }
+        }
+    }
+}
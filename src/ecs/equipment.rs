@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use specs::{World, WorldExt, Entity};
+
+use super::{CharacterComponent, BaseStatsComponent, VitalsComponent, EquipmentComponent, EquipmentSlots, Inventory};
+use super::inventory::{find_item, add_item, item_targets};
+
+use crate::assets::{AssetDatabase, AssetContainer, Stats, StatModifiers};
+
+
+//What the equip menu shows for one party member.
+pub struct EquipView {
+    pub entity: Entity,
+    pub name: String,
+    pub base_stats: Stats,
+    pub stats: Stats, //with equipment
+    pub slots: Vec<(String, Option<String>)>, //each slot and the item worn in it
+    pub options: Vec<(String, String)>, //items in the inventory this member can wear and what they do
+}
+
+
+//combined stat changes of everything worn. Items that aren't equipment are ignored.
+pub(super) fn equipment_modifiers(asset_database: &mut AssetDatabase, equipped: &HashMap<String, String>) -> StatModifiers {
+    equipped.values().fold(StatModifiers::default(), |total, item_name| {
+        match asset_database.get_asset(item_name) {
+            AssetContainer::Item(item) => match &item.equipment {
+                Some(equipment) => total.add(&equipment.modifiers),
+                None => total,
+            },
+            _ => total,
+        }
+    })
+}
+
+
+//base stats with equipment applied
+pub(super) fn stats_with_equipment(asset_database: &mut AssetDatabase, base_stats: Stats,
+                                   equipment: Option<&EquipmentComponent>) -> Stats {
+    match equipment {
+        Some(equipment) => equipment_modifiers(asset_database, &equipment.equipped).apply_to(base_stats),
+        None => base_stats,
+    }
+}
+
+
+//stats a character actually fights with
+pub fn derived_stats(world: &World, entity: Entity) -> Option<Stats> {
+    let base_stats = world.read_storage::<BaseStatsComponent>().get(entity)?.stats;
+    let equipment = world.read_storage::<EquipmentComponent>();
+
+    Some( stats_with_equipment(&mut world.write_resource::<AssetDatabase>(), base_stats, equipment.get(entity)) )
+}
+
+
+//takes an item out of the inventory and puts it on a character. Whatever was in that slot
+//  goes back into the inventory.
+// returns: what happened or why the item couldn't be worn
+pub fn equip_item(world: &World, entity: Entity, item_name: &str) -> Result<String, String> {
+    if world.read_resource::<Inventory>().count(item_name) == 0 {
+        return Err( format!("The party has no {}.", item_name) );
+    }
+
+    let item = find_item(&mut world.write_resource::<AssetDatabase>(), item_name)
+        .ok_or_else(|| format!("{} can't be worn.", item_name))?;
+    let equipment = item.equipment.ok_or_else(|| format!("{} can't be worn.", item_name))?;
+
+    if !world.read_resource::<EquipmentSlots>().slots.contains(&equipment.slot) {
+        return Err( format!("{} goes in the {} slot which nobody has.", item_name, equipment.slot) );
+    }
+
+    let (character_name, class) = match world.read_storage::<CharacterComponent>().get(entity) {
+        Some(character) => (character.name.clone(), character.class.clone()),
+        None => return Err( format!("{} can't be worn by that.", item_name) ),
+    };
+
+    if !equipment.allows_class(&class) {
+        return Err( format!("{} can't wear {}.", character_name, item_name) );
+    }
+
+    let previous = world.read_storage::<EquipmentComponent>().get(entity)
+                        .and_then(|worn| worn.equipped.get(&equipment.slot).cloned());
+
+    //swapping for the same item would change nothing
+    if previous.as_deref() == Some(item_name) {
+        return Err( format!("{} is already wearing {}.", character_name, item_name) );
+    }
+
+    world.write_resource::<Inventory>().remove(item_name, 1);
+
+    if let Some(previous) = &previous {
+        if add_item(world, previous, 1) == 0 {
+            //put things back the way they were
+            add_item(world, item_name, 1);
+            return Err( format!("The party can't carry another {}.", previous) );
+        }
+    }
+
+    {
+        let mut equipment_storage = world.write_storage::<EquipmentComponent>();
+        if equipment_storage.get(entity).is_none() {
+            let _ = equipment_storage.insert(entity, EquipmentComponent { equipped: HashMap::new() });
+        }
+
+        if let Some(worn) = equipment_storage.get_mut(entity) {
+            worn.equipped.insert(equipment.slot.clone(), item_name.to_string());
+        }
+    }
+
+    clamp_vitals(world, entity);
+
+    Ok( match previous {
+        Some(previous) => format!("{} swapped {} for {}.", character_name, previous, item_name),
+        None => format!("{} is now wearing {}.", character_name, item_name),
+    })
+}
+
+
+//takes whatever a character wears in a slot off and puts it back in the inventory
+// returns: what happened or why it couldn't be taken off
+pub fn unequip_slot(world: &World, entity: Entity, slot: &str) -> Result<String, String> {
+    let character_name = world.read_storage::<CharacterComponent>().get(entity)
+                              .map(|character| character.name.clone())
+                              .unwrap_or_default();
+
+    let item_name = world.read_storage::<EquipmentComponent>().get(entity)
+                         .and_then(|worn| worn.equipped.get(slot).cloned())
+                         .ok_or_else(|| format!("{} isn't wearing anything there.", character_name))?;
+
+    if add_item(world, &item_name, 1) == 0 {
+        return Err( format!("The party can't carry another {}.", item_name) );
+    }
+
+    if let Some(worn) = world.write_storage::<EquipmentComponent>().get_mut(entity) {
+        worn.equipped.remove(slot);
+    }
+
+    clamp_vitals(world, entity);
+
+    Ok( format!("{} took off {}.", character_name, item_name) )
+}
+
+
+//keeps hp and mp under maximums that may have dropped after changing equipment
+fn clamp_vitals(world: &World, entity: Entity) {
+    if let Some(stats) = derived_stats(world, entity) {
+        if let Some(vitals) = world.write_storage::<VitalsComponent>().get_mut(entity) {
+            vitals.hp = vitals.hp.min(stats.max_hp);
+            vitals.mp = vitals.mp.min(stats.max_mp);
+        }
+    }
+}
+
+
+//what the equip menu shows for a party member, by their place in the party
+pub fn equip_view(world: &World, member: usize) -> Option<EquipView> {
+    let target = item_targets(world).into_iter().nth(member)?;
+
+    let base_stats = world.read_storage::<BaseStatsComponent>().get(target.entity)?.stats;
+    let stats = derived_stats(world, target.entity)?;
+
+    let class = world.read_storage::<CharacterComponent>().get(target.entity)
+                     .map(|character| character.class.clone())
+                     .unwrap_or_default();
+
+    let equipped = world.read_storage::<EquipmentComponent>().get(target.entity)
+                        .map(|worn| worn.equipped.clone())
+                        .unwrap_or_default();
+
+    let slots = world.read_resource::<EquipmentSlots>().slots.iter()
+                     .map(|slot| (slot.clone(), equipped.get(slot).cloned()))
+                     .collect();
+
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let options = world.read_resource::<Inventory>().items.iter()
+        .filter_map(|(item_name, _)| match asset_database.get_asset(item_name) {
+            AssetContainer::Item(item) => match &item.equipment {
+                Some(equipment) if equipment.allows_class(&class) =>
+                    Some( (item_name.clone(), format!("{}: {}", equipment.slot, equipment.modifiers.describe())) ),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    Some( EquipView {
+        entity: target.entity,
+        name: target.name,
+        base_stats,
+        stats,
+        slots,
+        options,
+    })
+}
@@ -0,0 +1,48 @@
+use specs::{Component, VecStorage};
+
+use coffee::graphics::{Point, Rectangle};
+
+use crate::assets::TriggerCondition;
+
+
+//For entities event scripts need to find, like someone a script walks around. Comes from the
+//  name of the map object that spawned the entity.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct NameComponent {
+    pub name: String,
+}
+
+
+//For entities that start an event script when something happens. Area based triggers cover
+//  'size' pixels from the entity's position.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct TriggerComponent {
+    pub condition: TriggerCondition,
+    pub event: String, //name of the event script to run
+    pub size: (f32, f32),
+    pub last_state: Option<bool>, //whether the condition held last update, None before the first check
+}
+
+impl TriggerComponent {
+
+    pub fn new(condition: TriggerCondition, event: String, size: (f32, f32)) -> TriggerComponent {
+        TriggerComponent {
+            condition,
+            event,
+            size,
+            last_state: None,
+        }
+    }
+
+    //area covered when the entity is at the given position
+    pub fn area_at(&self, position: Point) -> Rectangle<f32> {
+        Rectangle {
+            x: position.x,
+            y: position.y,
+            width: self.size.0,
+            height: self.size.1,
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use specs::{Entities, Read, ReadExpect, WriteExpect, ReadStorage, WriteStorage, System, LazyUpdate, WorldExt};
+
+use coffee::graphics::Vector;
+
+use super::position_component::PositionComponent;
+use super::gfx_components::AnimationComponent;
+use super::event_components::NameComponent;
+use super::scene_components::MapComponent;
+use super::resources::{EventQueue, RunningEvent, GameClock, GameFlags, ActiveDialogue, PendingBattle,
+                       SceneManager, MapChange, CurrentMap, Inventory, Party, QuestLog, PendingShop, ViewArea};
+use super::dialogue::start_dialogue;
+use super::inventory::give_item;
+use super::quests::start_quest;
+use super::scripting::{ScriptRunner, ScriptInput, ScriptOutput};
+use super::spawning::spawn_prefab;
+
+use crate::assets::{AssetDatabase, AssetContainer, EventScript, EventCommand};
+use crate::assets::audio::SoundEffects;
+
+
+//runs the event scripts queued by triggers. Commands that finish right away are all run in the
+//  same update, the script only pauses on commands that take time like dialogue, waiting, walking
+//  somewhere, or a battle. A trigger can also name a script asset, which runs like an event
+//  script with a single 'run script' command.
+pub struct EventSystem {
+    scripts: ScriptRunner,
+}
+
+impl EventSystem {
+
+    pub fn new() -> EventSystem {
+        EventSystem {
+            scripts: ScriptRunner::new(),
+        }
+    }
+}
+
+impl<'a> System<'a> for EventSystem {
+    type SystemData = ( WriteExpect<'a, EventQueue>,
+                        WriteExpect<'a, AssetDatabase>,
+                        WriteExpect<'a, ActiveDialogue>,
+                        WriteExpect<'a, GameFlags>,
+                        WriteExpect<'a, Inventory>,
+                        WriteExpect<'a, PendingBattle>,
+                        WriteExpect<'a, SceneManager>,
+                        WriteExpect<'a, SoundEffects>,
+                        WriteExpect<'a, QuestLog>,
+                        WriteExpect<'a, PendingShop>,
+                        ReadExpect<'a, Party>,
+                        ReadExpect<'a, GameClock>,
+                        ReadExpect<'a, ViewArea>,
+                        Read<'a, LazyUpdate>,
+                        Entities<'a>,
+                        ReadStorage<'a, NameComponent>,
+                        WriteStorage<'a, PositionComponent>,
+                        WriteStorage<'a, AnimationComponent>);
+
+    fn run(&mut self, (mut event_queue, mut asset_database, mut active_dialogue, mut flags, mut inventory,
+                       mut pending_battle, mut scene, mut sound_effects, mut quest_log, mut pending_shop,
+                       party, clock, view_area, lazy_update, entities, names, mut position, mut animation): Self::SystemData) {
+        use specs::Join;
+
+        let find_entity = |entity_name: &str| if entity_name == "leader" {
+            party.leader()
+        } else {
+            (&entities, &names).join().find(|(_, name)| name.name == entity_name).map(|(entity, _)| entity)
+        };
+
+        loop {
+            //start the next script once the last one is done
+            if event_queue.running.is_none() {
+                let event_name = match event_queue.waiting.pop_front() {
+                    Some(event_name) => event_name,
+                    None => return,
+                };
+
+                match asset_database.get_asset(&event_name) {
+                    AssetContainer::EventScript(script) => {
+                        if script.once && flags.is_set(&EventScript::done_flag(&event_name)) { continue; }
+                    },
+                    AssetContainer::Script(_) => {},
+                    _ => {
+                        warn!("[Events] {} {} {}.",
+                              "Requested event script",
+                              event_name,
+                              "does not exist",
+                        );
+                        continue;
+                    }
+                }
+
+                event_queue.running = Some( RunningEvent::new(event_name, 0) );
+            }
+
+            let running = match event_queue.running.as_mut() {
+                Some(running) => running,
+                None => return,
+            };
+
+            //commands a script asked for come before the event's own
+            let from_script = !running.from_script.is_empty();
+
+            let (command, once) = match asset_database.get_asset(&running.name) {
+                _ if from_script => (running.from_script.front().cloned(), false),
+                AssetContainer::EventScript(script) => (script.commands.get(running.step).cloned(), script.once),
+                AssetContainer::Script(_) if running.step == 0 => (Some( EventCommand::RunScript(running.name.clone()) ), false),
+                _ => (None, false),
+            };
+
+            let command = match command {
+                Some(command) => command,
+                None => {
+                    if once { flags.set(&EventScript::done_flag(&running.name), true); }
+                    event_queue.running = None;
+                    continue;
+                }
+            };
+
+            let finished = match command {
+                EventCommand::ShowDialogue(dialogue_name) => {
+                    //a conversation that is already going finishes first
+                    if !running.started && !active_dialogue.is_active() {
+                        start_dialogue(&mut active_dialogue, &mut asset_database, &mut flags, &inventory, &dialogue_name);
+                        running.started = true;
+                    }
+                    running.started && !active_dialogue.is_active()
+                },
+
+                EventCommand::MoveEntity { entity: entity_name, to, speed } => {
+                    match find_entity(&entity_name).and_then(|entity| position.get_mut(entity).map(|position| (entity, position))) {
+                        Some((entity, position)) => {
+                            let remaining = to - position.map_pos;
+                            let distance = remaining.norm();
+                            let reach = speed * clock.delta_seconds;
+
+                            let step = if distance <= reach { remaining } else { remaining * (reach / distance) };
+                            position.map_pos += step;
+
+                            let arrived = distance <= reach;
+                            if let Some(animation) = animation.get_mut(entity) {
+                                animation.set( if arrived { "idle" } else { walk_animation(step) } );
+                            }
+                            arrived
+                        },
+                        None => {
+                            warn!("[Events] Event script {} moves {} but nothing on the map has that name.",
+                                  running.name, entity_name);
+                            true
+                        }
+                    }
+                },
+
+                EventCommand::SetFlag(effect) => {
+                    flags.apply(&[effect], &inventory);
+                    true
+                },
+
+                EventCommand::GiveItem { item, count } => {
+                    let added = give_item(&mut asset_database, &mut inventory, &item, count);
+                    if added > 0 {
+                        info!("[Events] The party received {} {}.", added, item);
+                    }
+                    true
+                },
+
+                //the script carries on once the playing state is back from the battle
+                EventCommand::StartBattle(encounter) => {
+                    if !running.started {
+                        pending_battle.encounter = Some(encounter);
+                        running.started = true;
+                        false
+                    } else {
+                        true
+                    }
+                },
+
+                //waits for a change a warp started to finish first, then for its own
+                EventCommand::ChangeMap { map, arrival } => {
+                    if !running.started {
+                        running.started = scene.request( MapChange { map, arrival } );
+                        false
+                    } else {
+                        !scene.is_changing()
+                    }
+                },
+
+                //a sound from an entity that isn't on the map is still played, just not from anywhere
+                EventCommand::PlaySound { clip, at } => {
+                    let from = at.and_then(|entity_name| {
+                        let from = find_entity(&entity_name).and_then(|entity| position.get(entity)).map(|position| position.map_pos);
+                        if from.is_none() {
+                            warn!("[Events] Event script {} plays a sound at {} but nothing on the map has that name.",
+                                  running.name, entity_name);
+                        }
+                        from
+                    });
+
+                    match from {
+                        Some(from) => sound_effects.play_at(&mut asset_database, &clip, from, &view_area.area),
+                        None => sound_effects.play(&mut asset_database, &clip),
+                    }
+                    true
+                },
+
+                EventCommand::Wait(seconds) => {
+                    if running.started { running.timer += clock.delta_seconds; }
+                    running.started = true;
+                    running.timer >= seconds
+                },
+
+                EventCommand::StartQuest(quest_name) => {
+                    start_quest(&mut asset_database, &mut quest_log, &mut flags, &quest_name);
+                    true
+                },
+
+                //the world is paused while the shop is open, so the script carries on once it is closed
+                EventCommand::OpenShop(shop) => {
+                    if !running.started {
+                        pending_shop.shop = Some(shop);
+                        running.started = true;
+                        false
+                    } else {
+                        true
+                    }
+                },
+
+                EventCommand::RunScript(script_name) => {
+                    let input = ScriptInput {
+                        flags: flags.flags.clone(),
+                        entities: party.leader()
+                                       .and_then(|leader| position.get(leader))
+                                       .map(|leader_position| ("leader".to_string(), leader_position.map_pos))
+                                       .into_iter()
+                                       .chain( (&names, &position).join()
+                                                   .map(|(name, position)| (name.name.clone(), position.map_pos)) )
+                                       .collect(),
+                        items: inventory.items.clone(),
+                    };
+
+                    let result = match asset_database.get_asset(&script_name) {
+                        AssetContainer::Script(script) => self.scripts.run(script, input),
+                        _ => Err( format!("Requested script {} does not exist", script_name) ),
+                    };
+
+                    match result {
+                        Ok(output) => {
+                            let ScriptOutput { flag_changes, commands, spawns, despawns } = output;
+                            for (flag, value) in flag_changes {
+                                flags.set(&flag, value);
+                            }
+                            running.from_script.extend(commands);
+
+                            for name in despawns {
+                                let found = (&entities, &names).join()
+                                                               .find(|(entity, entity_name)| entity_name.name == name && !party.is_member(*entity))
+                                                               .map(|(entity, _)| entity);
+                                match found {
+                                    Some(entity) => { let _ = entities.delete(entity); },
+                                    None => warn!("[Events] Script {} removes {} but nothing on the map has that name.",
+                                                  script_name, name),
+                                }
+                            }
+
+                            //spawning needs the whole world so it waits for the end of the update
+                            for spawn in spawns {
+                                lazy_update.exec_mut(move |world| {
+                                    if let Some(entity) = spawn_prefab(world, &spawn.prefab, Some(spawn.position)) {
+                                        if let Some(map) = world.read_resource::<CurrentMap>().name.clone() {
+                                            let _ = world.write_storage::<MapComponent>().insert(entity, MapComponent { map });
+                                        }
+                                        if let Some(name) = spawn.name {
+                                            let _ = world.write_storage::<NameComponent>().insert(entity, NameComponent { name });
+                                        }
+                                    }
+                                });
+                            }
+                        },
+                        Err(err) => warn!("[Events] Script failed and was skipped. {}", err),
+                    }
+                    true
+                },
+            };
+
+            if !finished { return; }
+
+            if from_script {
+                running.from_script.pop_front();
+                running.started = false;
+                running.timer = 0.0;
+                continue;
+            }
+
+            running.step += 1;
+            running.started = false;
+            running.timer = 0.0;
+        }
+    }
+}
+
+
+//faces the way something is walking, the bigger direction wins
+fn walk_animation(step: Vector) -> &'static str {
+         if step.x == 0.0 && step.y == 0.0 { "idle" }
+    else if step.x.abs() >= step.y.abs() { if step.x > 0.0 { "walk right" } else { "walk left" } }
+    else if step.y > 0.0 { "walk down" } else { "walk up" }
+}
@@ -0,0 +1,51 @@
+use specs::{WriteExpect, WriteStorage, System};
+
+use super::position_component::PositionComponent;
+use super::gfx_components::AnimationComponent;
+use super::resources::Party;
+
+//how many trail points each follower stays behind the one in front of them
+const FOLLOW_GAP: usize = 8;
+
+//the follow system remembers the path the party leader walks and moves the other active
+//  members along it so they trail behind in a line
+pub struct FollowSystem;
+
+impl<'a> System<'a> for FollowSystem {
+    type SystemData = ( WriteExpect<'a, Party>,
+                        WriteStorage<'a, PositionComponent>,
+                        WriteStorage<'a, AnimationComponent>);
+
+    fn run(&mut self, (mut party, mut position, mut animation): Self::SystemData) {
+
+        let leader_position = match party.leader().and_then(|leader| position.get(leader)) {
+            Some(leader_position) => leader_position.map_pos,
+            None => return,
+        };
+
+        let followers: Vec<_> = party.active.iter().skip(1).copied().collect();
+        party.record_trail(leader_position, followers.len() * FOLLOW_GAP + 1);
+
+        for (place, follower) in followers.into_iter().enumerate() {
+            let target = match party.trail_point((place + 1) * FOLLOW_GAP) {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let position = match position.get_mut(follower) {
+                Some(position) => position,
+                None => continue,
+            };
+
+            let step = target - position.map_pos;
+            position.map_pos = target;
+
+            //face the way they are walking, the bigger direction wins
+            if let Some(animation) = animation.get_mut(follower) {
+                     if step.x == 0.0 && step.y == 0.0 { animation.set("idle"); }
+                else if step.x.abs() >= step.y.abs() { animation.set( if step.x > 0.0 { "walk right" } else { "walk left" } ); }
+                else { animation.set( if step.y > 0.0 { "walk down" } else { "walk up" } ); }
+            }
+        }
+    }
+}
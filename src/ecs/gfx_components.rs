@@ -17,17 +17,17 @@ pub struct VisualComponent {
 pub struct AnimationComponent {
     pub name: String,
     pub index: usize,
-    pub speed: u16,
-    pub timer: u16,
+    pub frame_duration: f32, //seconds each sprite is shown for
+    pub timer: f32, //seconds left before moving to the next sprite
 }
 
 impl AnimationComponent {
-  pub fn new(speed: u16) -> AnimationComponent {
+  pub fn new(frame_duration: f32) -> AnimationComponent {
       AnimationComponent{
           name: "idle".to_string(),
           index: 0,
-          speed,
-          timer: speed,
+          frame_duration,
+          timer: frame_duration,
       }
   }
 
@@ -35,7 +35,7 @@ impl AnimationComponent {
       if self.name != new_name {
           self.name = new_name.to_string();
           self.index = 0;
-          self.timer = self.speed;
+          self.timer = self.frame_duration;
       }
   }
 }
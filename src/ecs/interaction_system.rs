@@ -0,0 +1,89 @@
+use specs::{ReadExpect, ReadStorage, WriteExpect, System};
+
+use coffee::graphics::Point;
+
+use super::position_component::PositionComponent;
+use super::control_components::{PlayerControlComponent, InteractionComponent};
+use super::event_components::{NameComponent, TriggerComponent};
+use super::resources::{ActiveDialogue, GameFlags, Inventory, EventQueue, SceneManager, GameEvent, GameEvents};
+use super::dialogue::start_dialogue;
+
+use crate::assets::{AssetDatabase, TriggerCondition};
+use crate::game_state::playing_state::ControlData;
+use crate::input::InputAction;
+
+
+//how close, in pixels, the player has to be to something to interact with it
+const INTERACTION_RANGE: f32 = 48.0;
+
+
+//what happens when the player interacts with something
+enum Reaction<'a> {
+    Dialogue(&'a str, Option<&'a NameComponent>),
+    Event(&'a str),
+}
+
+
+//starts the dialogue or interact trigger of the closest interactable entity when the player
+//  presses confirm
+pub struct InteractionSystem;
+
+impl<'a> System<'a> for InteractionSystem {
+    type SystemData = ( WriteExpect<'a, ControlData>,
+                        WriteExpect<'a, ActiveDialogue>,
+                        WriteExpect<'a, AssetDatabase>,
+                        WriteExpect<'a, GameFlags>,
+                        WriteExpect<'a, EventQueue>,
+                        WriteExpect<'a, GameEvents>,
+                        ReadExpect<'a, SceneManager>,
+                        ReadExpect<'a, Inventory>,
+                        ReadStorage<'a, PositionComponent>,
+                        ReadStorage<'a, PlayerControlComponent>,
+                        ReadStorage<'a, InteractionComponent>,
+                        ReadStorage<'a, NameComponent>,
+                        ReadStorage<'a, TriggerComponent>);
+
+    fn run(&mut self, (mut control_data, mut active_dialogue, mut asset_database, mut flags, mut event_queue, mut game_events,
+                       scene, inventory, position, player_control, interaction, name, trigger): Self::SystemData) {
+        use specs::Join;
+
+        //the key press is used up either way so it doesn't linger until the next update
+        if !control_data.take_pressed(InputAction::Confirm) { return; }
+
+        if active_dialogue.is_active() || event_queue.is_running() || scene.is_changing() { return; }
+
+        for (player_position, _) in (&position, &player_control).join() {
+            let dialogues = (&position, &interaction, name.maybe()).join()
+                .map(|(position, interaction, name)| (position, Reaction::Dialogue(&interaction.dialogue, name)));
+            let events = (&position, &trigger).join()
+                .filter(|(_, trigger)| matches!(trigger.condition, TriggerCondition::Interact))
+                .map(|(position, trigger)| (position, Reaction::Event(&trigger.event)));
+
+            let closest = dialogues.chain(events)
+                .map(|(position, reaction)| (distance(player_position.map_pos, position.map_pos), reaction))
+                .filter(|(distance, _)| *distance <= INTERACTION_RANGE)
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+            match closest {
+                Some((_, Reaction::Dialogue(dialogue, name))) => {
+                    start_dialogue(&mut active_dialogue, &mut asset_database, &mut flags, &inventory, dialogue);
+                    game_events.announce( GameEvent::Talked {
+                        entity: name.map(|name| name.name.clone()),
+                        dialogue: dialogue.to_string(),
+                    });
+                    return;
+                },
+                Some((_, Reaction::Event(event))) => {
+                    event_queue.push(event);
+                    return;
+                },
+                None => {},
+            }
+        }
+    }
+}
+
+
+fn distance(a: Point, b: Point) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
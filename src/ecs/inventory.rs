@@ -0,0 +1,253 @@
+use specs::{World, WorldExt, Entity};
+
+use coffee::graphics::{Image, Rectangle};
+
+use super::{Inventory, Party, CharacterComponent, BaseStatsComponent, VitalsComponent, EquipmentComponent};
+use super::equipment::{derived_stats, stats_with_equipment};
+
+use crate::assets::{AssetDatabase, AssetContainer, Item, ItemEffect, Stats};
+
+
+//An item the party carries as shown in the inventory screen.
+pub struct InventoryEntry {
+    pub name: String,
+    pub description: String,
+    pub category: &'static str,
+    pub count: u32,
+    pub price: u32,
+    pub icon: Option<(Image, Rectangle<u16>)>,
+    pub usable: bool,
+}
+
+
+//A party member items can be used on.
+pub struct ItemTarget {
+    pub entity: Entity,
+    pub name: String,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub mp: u32,
+    pub max_mp: u32,
+}
+
+
+//looks up an item asset, logging when it is missing
+pub fn find_item(asset_database: &mut AssetDatabase, item_name: &str) -> Option<Item> {
+    match asset_database.get_asset(&item_name.to_string()) {
+        AssetContainer::Item(item) => Some(item.clone()),
+        _ => {
+            warn!("[Inventory] {} {} {}.",
+                  "Requested item",
+                  item_name,
+                  "does not exist",
+            );
+            None
+        }
+    }
+}
+
+
+//puts items in the party's inventory. Anything over the item's stack limit is left behind.
+// returns: how many were added
+pub fn add_item(world: &World, item_name: &str, count: u32) -> u32 {
+    give_item(&mut world.write_resource::<AssetDatabase>(), &mut world.write_resource::<Inventory>(), item_name, count)
+}
+
+
+//add_item for systems that already hold the resources
+pub(super) fn give_item(asset_database: &mut AssetDatabase, inventory: &mut Inventory, item_name: &str, count: u32) -> u32 {
+    let item = match find_item(asset_database, item_name) {
+        Some(item) => item,
+        None => return 0,
+    };
+
+    let added = inventory.add(item_name, count, item.stack_limit);
+    if added < count {
+        info!("[Inventory] The party can't carry more than {} {} so {} were left behind.",
+              item.stack_limit, item_name, count - added);
+    }
+
+    added
+}
+
+
+//takes items out of the party's inventory
+// returns: false when the party doesn't have that many
+pub fn remove_item(world: &World, item_name: &str, count: u32) -> bool {
+    world.write_resource::<Inventory>().remove(item_name, count)
+}
+
+
+//uses one of an item on a party member, applying its effect and taking it from the inventory
+// returns: what happened or why the item couldn't be used
+pub fn use_item(world: &World, item_name: &str, target: Entity) -> Result<String, String> {
+    if world.read_resource::<Inventory>().count(item_name) == 0 {
+        return Err( format!("The party has no {}.", item_name) );
+    }
+
+    let item = find_item(&mut world.write_resource::<AssetDatabase>(), item_name)
+        .ok_or_else(|| format!("{} can't be used.", item_name))?;
+
+    let effect = match item.effect {
+        Some(effect) if item.is_usable() => effect,
+        _ => return Err( format!("{} can't be used.", item_name) ),
+    };
+
+    let message = {
+        let stats = derived_stats(world, target);
+        let characters = world.read_storage::<CharacterComponent>();
+        let mut vitals = world.write_storage::<VitalsComponent>();
+
+        match (characters.get(target), stats, vitals.get_mut(target)) {
+            (Some(character), Some(stats), Some(vitals)) =>
+                apply_item_effect(&effect, &character.name, &stats, vitals)?,
+            _ => return Err( format!("{} can't be used on that.", item_name) ),
+        }
+    };
+
+    world.write_resource::<Inventory>().remove(item_name, 1);
+    Ok(message)
+}
+
+
+//changes a character's hp and mp by an item's effect
+fn apply_item_effect(effect: &ItemEffect, name: &str, stats: &Stats, vitals: &mut VitalsComponent)
+   -> Result<String, String> {
+
+    let knocked_out = vitals.hp == 0;
+    if effect.revive && !knocked_out {
+        return Err( format!("{} doesn't need reviving.", name) );
+    }
+    if !effect.revive && knocked_out {
+        return Err( format!("{} is knocked out.", name) );
+    }
+
+    //hp and mp can be above the maximums after taking equipment off, items never lower them
+    let hp = vitals.hp.saturating_add(effect.restore_hp).max(1).min(stats.max_hp).max(vitals.hp);
+    let mp = vitals.mp.saturating_add(effect.restore_mp).min(stats.max_mp).max(vitals.mp);
+    let (hp_gained, mp_gained) = (hp.saturating_sub(vitals.hp), mp.saturating_sub(vitals.mp));
+
+    if hp_gained == 0 && mp_gained == 0 {
+        return Err( format!("It would have no effect on {}.", name) );
+    }
+
+    vitals.hp = hp;
+    vitals.mp = mp;
+
+    Ok( match (effect.revive, hp_gained, mp_gained) {
+        (true, _, _) => format!("{} was revived with {} HP.", name, hp),
+        (false, _, 0) => format!("{} recovered {} HP.", name, hp_gained),
+        (false, 0, _) => format!("{} recovered {} MP.", name, mp_gained),
+        (false, _, _) => format!("{} recovered {} HP and {} MP.", name, hp_gained, mp_gained),
+    })
+}
+
+
+//everything the party carries along with what the inventory screen shows for it
+pub fn inventory_view(world: &World) -> Vec<InventoryEntry> {
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let inventory = world.read_resource::<Inventory>();
+
+    inventory.items.iter().filter_map(|(item_name, count)| {
+        let item = match asset_database.get_asset(item_name) {
+            AssetContainer::Item(item) => item.clone(),
+            _ => return None,
+        };
+
+        let icon = item_icon(&mut asset_database, &item);
+
+        Some( InventoryEntry {
+            usable: item.is_usable(),
+            name: item.name,
+            description: item.description,
+            category: item.category.name(),
+            count: *count,
+            price: item.price,
+            icon,
+        })
+    }).collect()
+}
+
+
+//picture of an item shown in menus
+pub(super) fn item_icon(asset_database: &mut AssetDatabase, item: &Item) -> Option<(Image, Rectangle<u16>)> {
+    item.icon.as_ref().and_then(|(sprite_sheet_name, (row, column))| {
+        match asset_database.get_asset(sprite_sheet_name) {
+            AssetContainer::Spritesheet(sprite_sheet) => Some(sprite_sheet.sprite_image(*row, *column)),
+            _ => None,
+        }
+    })
+}
+
+
+//party members items can be used on, reserve members included
+pub fn item_targets(world: &World) -> Vec<ItemTarget> {
+    let members = world.read_resource::<Party>().members();
+    let characters = world.read_storage::<CharacterComponent>();
+    let base_stats = world.read_storage::<BaseStatsComponent>();
+    let vitals = world.read_storage::<VitalsComponent>();
+    let equipment = world.read_storage::<EquipmentComponent>();
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+
+    members.into_iter()
+        .filter_map(|entity| {
+            let (character, base_stats, vitals) = (characters.get(entity)?, base_stats.get(entity)?, vitals.get(entity)?);
+            let stats = stats_with_equipment(&mut asset_database, base_stats.stats, equipment.get(entity));
+
+            Some( ItemTarget {
+                entity,
+                name: character.name.clone(),
+                hp: vitals.hp,
+                max_hp: stats.max_hp,
+                mp: vitals.mp,
+                max_mp: stats.max_mp,
+            })
+        })
+        .collect()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> Stats {
+        Stats { max_hp: 30, max_mp: 10, ..Stats::default() }
+    }
+
+    fn potion(restore_hp: u32, restore_mp: u32, revive: bool) -> ItemEffect {
+        ItemEffect { restore_hp, restore_mp, revive }
+    }
+
+    #[test]
+    fn healing_stops_at_max_hp() {
+        let mut vitals = VitalsComponent { hp: 25, mp: 10 };
+
+        assert_eq!(apply_item_effect(&potion(20, 0, false), "Sara", &stats(), &mut vitals).unwrap(),
+                   "Sara recovered 5 HP.");
+        assert_eq!(vitals.hp, 30);
+    }
+
+    #[test]
+    fn hp_above_max_is_not_lowered() {
+        //max hp went down after taking off equipment
+        let mut vitals = VitalsComponent { hp: 40, mp: 2 };
+
+        assert!(apply_item_effect(&potion(20, 0, false), "Sara", &stats(), &mut vitals).is_err());
+        assert_eq!(apply_item_effect(&potion(20, 5, false), "Sara", &stats(), &mut vitals).unwrap(),
+                   "Sara recovered 5 MP.");
+        assert_eq!((vitals.hp, vitals.mp), (40, 7));
+    }
+
+    #[test]
+    fn reviving_needs_a_knocked_out_target() {
+        let mut vitals = VitalsComponent { hp: 5, mp: 0 };
+        assert!(apply_item_effect(&potion(0, 0, true), "Sara", &stats(), &mut vitals).is_err());
+
+        let mut vitals = VitalsComponent { hp: 0, mp: 0 };
+        assert!(apply_item_effect(&potion(10, 0, false), "Sara", &stats(), &mut vitals).is_err());
+        assert_eq!(apply_item_effect(&potion(0, 0, true), "Sara", &stats(), &mut vitals).unwrap(),
+                   "Sara was revived with 1 HP.");
+    }
+}
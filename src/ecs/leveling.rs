@@ -0,0 +1,200 @@
+use specs::{World, WorldExt, Entity};
+
+use super::{CharacterComponent, LevelComponent, BaseStatsComponent, VitalsComponent, EquipmentComponent};
+use super::equipment::{equipment_modifiers, stats_with_equipment};
+
+use crate::assets::{AssetDatabase, AssetContainer, CharacterClass};
+
+
+//Components that give an entity the stats of a character asset.
+pub struct CharacterComponents {
+    pub character: CharacterComponent,
+    pub level: LevelComponent,
+    pub base_stats: BaseStatsComponent,
+    pub vitals: VitalsComponent,
+    pub equipment: EquipmentComponent,
+}
+
+
+//looks up a class asset, logging when it is missing
+pub fn find_class(asset_database: &mut AssetDatabase, class_name: &str) -> Option<CharacterClass> {
+    match asset_database.get_asset(&class_name.to_string()) {
+        AssetContainer::CharacterClass(class) => Some(class.clone()),
+        _ => {
+            warn!("[Leveling] {} {} {}.",
+                  "Requested class",
+                  class_name,
+                  "does not exist",
+            );
+            None
+        }
+    }
+}
+
+
+//builds the components for a character at full health. The level falls back to the character's own.
+pub fn character_components(asset_database: &mut AssetDatabase, character_name: &str, level: Option<u32>) 
+   -> Option<CharacterComponents> {
+
+    let character = match asset_database.get_asset(&character_name.to_string()) {
+        AssetContainer::Character(character) => character.clone(),
+        _ => {
+            warn!("[Leveling] {} {} {}.",
+                  "Requested character",
+                  character_name,
+                  "does not exist",
+            );
+            return None;
+        }
+    };
+
+    let class = find_class(asset_database, &character.class)?;
+    let level = level.unwrap_or(character.level).clamp(1, class.max_level);
+    let base_stats = class.stats_at(level);
+
+    //starting equipment has to be something this character can wear
+    let mut equipped = character.equipment.clone();
+    equipped.retain(|slot, item_name| {
+        let wearable = match asset_database.get_asset(item_name) {
+            AssetContainer::Item(item) => item.equipment.as_ref()
+                .map(|equipment| &equipment.slot == slot && equipment.allows_class(&character.class))
+                .unwrap_or(false),
+            _ => false,
+        };
+
+        if !wearable {
+            warn!("[Leveling] Character {} can't wear {} in their {} slot so it was left off.",
+                  character.name, item_name, slot);
+        }
+        wearable
+    });
+
+    let stats = equipment_modifiers(asset_database, &equipped).apply_to(base_stats);
+
+    Some( CharacterComponents {
+        character: CharacterComponent { name: character.name, class: character.class },
+        level: LevelComponent { level, experience: class.experience_for_level(level).unwrap_or(0) },
+        base_stats: BaseStatsComponent { stats: base_stats },
+        vitals: VitalsComponent { hp: stats.max_hp, mp: stats.max_mp },
+        equipment: EquipmentComponent { equipped },
+    })
+}
+
+
+//adds experience to a character, raising their level and stats when they pass a level.
+//  HP and MP go up by as much as their maximums did.
+// returns: a message for each level gained
+pub fn gain_experience(world: &World, entity: Entity, experience: u32) -> Vec<String> {
+    let characters = world.read_storage::<CharacterComponent>();
+    let mut levels = world.write_storage::<LevelComponent>();
+    let mut base_stats = world.write_storage::<BaseStatsComponent>();
+    let mut vitals = world.write_storage::<VitalsComponent>();
+
+    let (character, level) = match (characters.get(entity), levels.get_mut(entity)) {
+        (Some(character), Some(level)) => (character, level),
+        _ => return vec![],
+    };
+
+    let class = match find_class(&mut world.write_resource::<AssetDatabase>(), &character.class) {
+        Some(class) => class,
+        None => return vec![],
+    };
+
+    level.experience = level.experience.saturating_add(experience);
+    let new_level = class.level_for_experience(level.experience);
+    if new_level <= level.level { return vec![]; }
+
+    let messages = (level.level + 1..=new_level)
+                       .map(|reached| format!("{} reached level {}!", character.name, reached))
+                       .collect();
+
+    let old_stats = class.stats_at(level.level);
+    let new_stats = class.stats_at(new_level);
+    level.level = new_level;
+
+    if let Some(base_stats) = base_stats.get_mut(entity) {
+        base_stats.stats = new_stats;
+    }
+
+    //equipment still counts towards the maximums
+    let equipment = world.read_storage::<EquipmentComponent>();
+    let max_stats = stats_with_equipment(&mut world.write_resource::<AssetDatabase>(), new_stats, equipment.get(entity));
+
+    if let Some(vitals) = vitals.get_mut(entity) {
+        vitals.hp = (vitals.hp + new_stats.max_hp.saturating_sub(old_stats.max_hp)).min(max_stats.max_hp);
+        vitals.mp = (vitals.mp + new_stats.max_mp.saturating_sub(old_stats.max_mp)).min(max_stats.max_mp);
+    }
+
+    messages
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use specs::Builder;
+
+    use crate::assets::{Stats, StatGrowth};
+
+    fn world_with_character(level: u32, hp: u32) -> (World, Entity) {
+        let class = CharacterClass {
+            level_one: Stats { max_hp: 20, max_mp: 4, attack: 4, defense: 3, magic: 2, speed: 5 },
+            growth: StatGrowth { max_hp: 5.0, max_mp: 2.0, ..StatGrowth::default() },
+            growth_curve: 1.0,
+            experience_table: vec![10, 30, 60, 100],
+            max_level: 5,
+        };
+
+        let mut asset_database = AssetDatabase::new();
+        asset_database.add_asset("fighter".to_string(), AssetContainer::CharacterClass(class.clone()));
+
+        let mut world = World::new();
+        super::super::register_components(&mut world);
+        world.insert(asset_database);
+
+        let entity = world.create_entity()
+            .with(CharacterComponent { name: "Sara".to_string(), class: "fighter".to_string() })
+            .with(LevelComponent { level, experience: class.experience_for_level(level).unwrap() })
+            .with(BaseStatsComponent { stats: class.stats_at(level) })
+            .with(VitalsComponent { hp, mp: 0 })
+            .build();
+
+        (world, entity)
+    }
+
+    #[test]
+    fn gaining_several_levels_at_once() {
+        let (world, entity) = world_with_character(1, 20);
+
+        let messages = gain_experience(&world, entity, 65);
+
+        assert_eq!(messages, vec!["Sara reached level 2!", "Sara reached level 3!", "Sara reached level 4!"]);
+        assert_eq!(world.read_storage::<LevelComponent>().get(entity).unwrap().level, 4);
+        assert_eq!(world.read_storage::<BaseStatsComponent>().get(entity).unwrap().stats.max_hp, 35);
+
+        //hp and mp go up by as much as the maximums did
+        let vitals = world.read_storage::<VitalsComponent>();
+        assert_eq!((vitals.get(entity).unwrap().hp, vitals.get(entity).unwrap().mp), (35, 6));
+    }
+
+    #[test]
+    fn experience_short_of_a_level_is_kept() {
+        let (world, entity) = world_with_character(2, 10);
+
+        assert!(gain_experience(&world, entity, 5).is_empty());
+
+        let levels = world.read_storage::<LevelComponent>();
+        assert_eq!((levels.get(entity).unwrap().level, levels.get(entity).unwrap().experience), (2, 15));
+    }
+
+    #[test]
+    fn levels_stop_at_max_level() {
+        let (world, entity) = world_with_character(4, 35);
+
+        assert_eq!(gain_experience(&world, entity, 10_000), vec!["Sara reached level 5!"]);
+        assert!(gain_experience(&world, entity, 10_000).is_empty());
+        assert_eq!(world.read_storage::<LevelComponent>().get(entity).unwrap().level, 5);
+    }
+}
@@ -1,18 +1,47 @@
-use specs::{World, WorldExt, Builder, Dispatcher, DispatcherBuilder};
-
-use coffee::graphics::Point;
-use std::path::PathBuf;
+use specs::{World, WorldExt, Dispatcher, DispatcherBuilder};
 
 //pull in systems
 mod render_system;
 pub use render_system::RenderSystem;
 
+mod camera_system;
+pub use camera_system::CameraSystem;
+
+mod tilemap_render_system;
+pub use tilemap_render_system::TilemapRenderSystem;
+
 mod animation_system;
 pub use animation_system::AnimationSystem;
 
 mod player_control_system;
 pub use player_control_system::PlayerControlSystem;
 
+mod collision_system;
+pub use collision_system::CollisionSystem;
+
+mod interaction_system;
+pub use interaction_system::InteractionSystem;
+
+mod encounter_system;
+pub use encounter_system::EncounterSystem;
+
+mod follow_system;
+pub use follow_system::FollowSystem;
+
+mod trigger_system;
+pub use trigger_system::TriggerSystem;
+
+mod event_system;
+pub use event_system::EventSystem;
+
+mod warp_system;
+pub use warp_system::WarpSystem;
+
+mod quest_system;
+pub use quest_system::QuestSystem;
+
+mod scripting;
+
 //pull in components
 mod position_component;
 pub use position_component::PositionComponent;
@@ -21,7 +50,70 @@ mod gfx_components;
 pub use gfx_components::{VisualComponent, AnimationComponent};
 
 mod control_components;
-pub use control_components::PlayerControlComponent;
+pub use control_components::{PlayerControlComponent, InteractionComponent};
+
+mod physics_components;
+pub use physics_components::{MovementComponent, ColliderComponent};
+
+mod event_components;
+pub use event_components::{NameComponent, TriggerComponent};
+
+mod scene_components;
+pub use scene_components::{MapComponent, WarpComponent};
+
+mod stats_components;
+pub use stats_components::{CharacterComponent, LevelComponent, BaseStatsComponent, VitalsComponent, EquipmentComponent};
+
+//pull in entity creation
+mod spawning;
+pub use spawning::{spawn_prefab, spawn_map_entities};
+
+//pull in moving between maps
+mod scene;
+pub use scene::update_scene;
+
+//pull in saving and restoring the world
+mod saving;
+pub use saving::{save_world, restore_world};
+
+//pull in conversation handling
+mod dialogue;
+pub use dialogue::{DialogueView, advance_dialogue, dialogue_view};
+
+//pull in character stats and leveling
+mod leveling;
+pub use leveling::character_components;
+
+//pull in the party's items
+mod inventory;
+pub use inventory::{InventoryEntry, ItemTarget, add_item, use_item, inventory_view, item_targets};
+
+//pull in party membership
+mod party;
+pub use party::{PartyMemberView, join_party, swap_party_members, move_party_member, party_view,
+                gather_party};
+
+//pull in wearing equipment
+mod equipment;
+pub use equipment::{EquipView, equip_item, unequip_slot, equip_view};
+
+//pull in quests
+mod quests;
+pub use quests::{QuestEntry, quest_log_view};
+
+//pull in buying and selling
+mod shops;
+pub use shops::{ShopEntry, ShopView, buy_item, sell_item, shop_view};
+
+//pull in moving between the map and battles
+mod battles;
+pub use battles::{CombatantSprites, create_battle, apply_battle_result};
+
+//pull in resources
+mod resources;
+pub use resources::{CurrentMap, ViewArea, TileBatches, Camera, GameClock, GameFlags, ActiveDialogue, PendingBattle, Inventory,
+                    EquipmentSlots, Party, MAX_ACTIVE_MEMBERS, EventQueue, SceneManager, MapChange,
+                    GameEvent, GameEvents, QuestLog, PendingShop, ShopSales};
 
 
 pub fn register_components(world: &mut World) {
@@ -29,55 +121,45 @@ pub fn register_components(world: &mut World) {
     world.register::<VisualComponent>();
     world.register::<AnimationComponent>();
     world.register::<PlayerControlComponent>();
+    world.register::<MovementComponent>();
+    world.register::<ColliderComponent>();
+    world.register::<InteractionComponent>();
+    world.register::<CharacterComponent>();
+    world.register::<LevelComponent>();
+    world.register::<BaseStatsComponent>();
+    world.register::<VitalsComponent>();
+    world.register::<EquipmentComponent>();
+    world.register::<NameComponent>();
+    world.register::<TriggerComponent>();
+    world.register::<MapComponent>();
+    world.register::<WarpComponent>();
 }
 
 
 pub fn build_data_dispatcher() -> Dispatcher<'static, 'static> {
     DispatcherBuilder::new()
-    //.with(HelloWorld, "hello_world", &[])
+    .with(AnimationSystem, "AnimationSystem", &[])
+    .with(CameraSystem, "CameraSystem", &[])
+    .with(EncounterSystem::new(), "EncounterSystem", &[])
+    .with(TriggerSystem::new(), "TriggerSystem", &[])
+    .with(EventSystem::new(), "EventSystem", &["TriggerSystem"])
+    .with(WarpSystem, "WarpSystem", &["EventSystem"])
+    .with(QuestSystem::new(), "QuestSystem", &["EventSystem"])
     .build()
 }
 
 pub fn build_input_handling_dispatcher() -> Dispatcher<'static, 'static> {
     DispatcherBuilder::new()
     .with(PlayerControlSystem, "PlayerControlSystem", &[])
+    .with(CollisionSystem, "CollisionSystem", &["PlayerControlSystem"])
+    .with(InteractionSystem, "InteractionSystem", &["CollisionSystem"])
+    .with(FollowSystem, "FollowSystem", &["CollisionSystem"])
     .build()
 }
 
 pub fn build_render_dispatcher() -> Dispatcher<'static, 'static> {
     DispatcherBuilder::new()
-    .with(AnimationSystem, "AnimationSystem", &[])
-    .with(RenderSystem, "RenderSystem", &[])
+    .with(TilemapRenderSystem, "TilemapRenderSystem", &[])
+    .with(RenderSystem, "RenderSystem", &["TilemapRenderSystem"])
     .build()
 }
-
-pub fn create_test_entities(world: &mut World) {
-    //TODO temp until we get better entity spawning functionality.
-    let path: PathBuf =  ["campaigns", "TestGame", "sprite_sheets", "sara-atlas.png"].iter().collect();
-    let sprite_sheet_name = path.to_str().unwrap().to_string();
-
-    //println!("This is the name: {}", sprite_sheet_name);
-    //panic!("kill program so we can see above print value.");
-
-    //player
-    world
-    .create_entity()
-    .with(PlayerControlComponent { speed: 5.0 })
-    .with(PositionComponent { map_pos: Point::new(100.0, 100.0) })
-    .with(VisualComponent { 
-               sprite_sheet_name: sprite_sheet_name.clone(), 
-               sprite_location: (1,1) 
-    })
-    .with(AnimationComponent::new(5))
-    .build();
-
-    //another character
-    world
-    .create_entity()
-    .with(PositionComponent { map_pos: Point::new(500.0, 500.0) })
-    .with(VisualComponent { 
-               sprite_sheet_name: sprite_sheet_name.clone(), 
-               sprite_location: (5,1) 
-    })
-    .build();
-}
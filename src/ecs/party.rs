@@ -0,0 +1,209 @@
+use specs::{World, WorldExt, Entity, Join};
+
+use super::{Party, Camera, MAX_ACTIVE_MEMBERS, PositionComponent, PlayerControlComponent, MovementComponent,
+            ColliderComponent, CharacterComponent, LevelComponent, BaseStatsComponent, VitalsComponent,
+            EquipmentComponent, MapComponent};
+use super::equipment::stats_with_equipment;
+
+use crate::assets::AssetDatabase;
+
+
+//What the party menu shows for one member.
+pub struct PartyMemberView {
+    pub name: String,
+    pub level: u32,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub mp: u32,
+    pub max_mp: u32,
+    pub active: bool,
+    pub leader: bool,
+}
+
+
+//adds a character to the party. They join the active members when there is room and wait in
+//  reserve otherwise. Anyone joining after the leader starts out next to them. Members no longer
+//  belong to the map they were found on so they go along when the party leaves it.
+pub fn join_party(world: &World, entity: Entity) {
+    {
+        let mut party = world.write_resource::<Party>();
+        if party.is_member(entity) { return; }
+
+        world.write_storage::<MapComponent>().remove(entity);
+
+        if party.active.is_empty() {
+            party.active.push(entity);
+        } else {
+            world.write_storage::<PositionComponent>().remove(entity);
+
+            if party.active.len() < MAX_ACTIVE_MEMBERS {
+                party.active.push(entity);
+            } else {
+                party.reserve.push(entity);
+            }
+        }
+    }
+
+    refresh_party(world);
+}
+
+
+//swaps two members by their place in the party menu. This also swaps between the active
+//  members and the reserve.
+pub fn swap_party_members(world: &World, first: usize, second: usize) {
+    {
+        let mut party = world.write_resource::<Party>();
+        let active_count = party.active.len();
+        let mut members = party.members();
+
+        if first >= members.len() || second >= members.len() || first == second { return; }
+        members.swap(first, second);
+
+        party.reserve = members.split_off(active_count);
+        party.active = members;
+    }
+
+    refresh_party(world);
+}
+
+
+//moves a member between the active members and the reserve
+// returns: why the member couldn't be moved
+pub fn move_party_member(world: &World, member: usize) -> Result<(), String> {
+    {
+        let mut party = world.write_resource::<Party>();
+        let active_count = party.active.len();
+
+        if member < active_count {
+            if active_count == 1 {
+                return Err( "Someone has to lead the party.".to_string() );
+            }
+            let entity = party.active.remove(member);
+            party.reserve.insert(0, entity);
+
+        } else if member < active_count + party.reserve.len() {
+            if active_count >= MAX_ACTIVE_MEMBERS {
+                return Err( format!("Only {} can travel together.", MAX_ACTIVE_MEMBERS) );
+            }
+            let entity = party.reserve.remove(member - active_count);
+            party.active.push(entity);
+
+        } else {
+            return Ok(());
+        }
+    }
+
+    refresh_party(world);
+    Ok(())
+}
+
+
+//puts the party's components where they belong after the members change. The leader gets the
+//  player's controls and collider and takes the party's place on the map, followers get a
+//  position near the leader, and the reserve is taken off the map.
+pub fn refresh_party(world: &World) {
+    let party = world.read_resource::<Party>();
+    let mut positions = world.write_storage::<PositionComponent>();
+    let mut controls = world.write_storage::<PlayerControlComponent>();
+    let mut movements = world.write_storage::<MovementComponent>();
+    let mut colliders = world.write_storage::<ColliderComponent>();
+
+    let leader = match party.leader() {
+        Some(leader) => leader,
+        None => return,
+    };
+
+    //whoever the player controlled so far decides where the party is
+    let controlled = party.members().into_iter().find(|member| controls.get(*member).is_some());
+    let party_position = controlled.and_then(|member| positions.get(member))
+                                   .or_else(|| party.active.iter().find_map(|member| positions.get(*member)))
+                                   .map(|position| position.map_pos);
+
+    if let Some(controlled) = controlled.filter(|controlled| *controlled != leader) {
+        if let Some(control) = controls.remove(controlled) {
+            let _ = controls.insert(leader, control);
+        }
+        if let Some(movement) = movements.remove(controlled) {
+            let _ = movements.insert(leader, movement);
+        }
+        if let Some(collider) = colliders.remove(controlled) {
+            let _ = colliders.insert(leader, collider);
+        }
+    }
+
+    //followers walk through whatever the leader does so they can't block anything
+    for follower in party.members().into_iter().filter(|member| *member != leader) {
+        controls.remove(follower);
+        movements.remove(follower);
+        colliders.remove(follower);
+    }
+
+    if let Some(party_position) = party_position {
+        let _ = positions.insert(leader, PositionComponent { map_pos: party_position });
+
+        for follower in party.active.iter().skip(1) {
+            if positions.get(*follower).is_none() {
+                let _ = positions.insert(*follower, PositionComponent { map_pos: party_position });
+            }
+        }
+    }
+
+    for waiting in party.reserve.iter() {
+        positions.remove(*waiting);
+    }
+
+    //a camera that followed a party member follows the leader instead
+    let mut camera = world.write_resource::<Camera>();
+    if camera.target.map(|target| party.is_member(target)).unwrap_or(false) {
+        camera.target = Some(leader);
+    }
+}
+
+
+//what the party menu shows for every member
+pub fn party_view(world: &World) -> Vec<PartyMemberView> {
+    let party = world.read_resource::<Party>();
+    let characters = world.read_storage::<CharacterComponent>();
+    let levels = world.read_storage::<LevelComponent>();
+    let base_stats = world.read_storage::<BaseStatsComponent>();
+    let vitals = world.read_storage::<VitalsComponent>();
+    let equipment = world.read_storage::<EquipmentComponent>();
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+
+    party.members().into_iter().enumerate().filter_map(|(index, entity)| {
+        let character = characters.get(entity)?;
+        let stats = stats_with_equipment(&mut asset_database, base_stats.get(entity)?.stats, equipment.get(entity));
+        let vitals = vitals.get(entity)?;
+
+        Some( PartyMemberView {
+            name: character.name.clone(),
+            level: levels.get(entity).map(|level| level.level).unwrap_or(1),
+            hp: vitals.hp,
+            max_hp: stats.max_hp,
+            mp: vitals.mp,
+            max_mp: stats.max_mp,
+            active: index < party.active.len(),
+            leader: index == 0,
+        })
+    }).collect()
+}
+
+
+//characters the player controls that aren't in the party yet, like ones placed on the starting map
+pub fn gather_party(world: &World) {
+    let new_members: Vec<Entity> = {
+        let entities = world.entities();
+        let party = world.read_resource::<Party>();
+        let characters = world.read_storage::<CharacterComponent>();
+        let controls = world.read_storage::<PlayerControlComponent>();
+
+        (&entities, &characters, &controls).join()
+            .map(|(entity, _, _)| entity)
+            .filter(|entity| !party.is_member(*entity))
+            .collect()
+    };
+
+    for entity in new_members {
+        join_party(world, entity);
+    }
+}
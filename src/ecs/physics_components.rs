@@ -0,0 +1,44 @@
+use specs::{Component, VecStorage};
+
+use coffee::graphics::{Point, Rectangle, Vector};
+
+
+//For entities that move around. Holds how far the entity wants to move this update,
+//  the collision system decides how much of that movement is actually allowed.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct MovementComponent {
+    pub displacement: Vector,
+}
+
+impl MovementComponent {
+  pub fn new() -> MovementComponent {
+      MovementComponent {
+          displacement: Vector::new(0.0, 0.0),
+      }
+  }
+}
+
+
+//For entities that can't overlap solid tiles or other colliders. The box is placed relative
+//  to the entity's position, usually around the feet of a character.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct ColliderComponent {
+    pub offset: Vector,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ColliderComponent {
+
+  //area the collider covers when the entity is at the given position
+  pub fn area_at(&self, position: Point) -> Rectangle<f32> {
+      Rectangle {
+          x: position.x + self.offset.x,
+          y: position.y + self.offset.y,
+          width: self.width,
+          height: self.height,
+      }
+  }
+}
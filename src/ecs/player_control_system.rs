@@ -1,31 +1,44 @@
 use specs::{ReadExpect, ReadStorage, WriteStorage, System};
 
-use super::position_component::PositionComponent;
+use super::physics_components::MovementComponent;
 use super::control_components::PlayerControlComponent;
 use super::gfx_components::AnimationComponent;
 
+use super::resources::{GameClock, EventQueue, SceneManager};
+
 use crate::game_state::playing_state::ControlData;
+use crate::input::InputAction;
+
+
+//how many times faster the party leader moves while the run action is held
+const RUN_SPEED_MULTIPLIER: f32 = 1.75;
 
 //the animation system advances the sprite selection on spritesheets to make an animation
 pub struct PlayerControlSystem;
 
 impl<'a> System<'a> for PlayerControlSystem {
     type SystemData = ( ReadExpect<'a, ControlData>,
-                        WriteStorage<'a, PositionComponent>,
+                        ReadExpect<'a, GameClock>,
+                        ReadExpect<'a, EventQueue>,
+                        ReadExpect<'a, SceneManager>,
+                        WriteStorage<'a, MovementComponent>,
                         ReadStorage<'a, PlayerControlComponent>,
                         WriteStorage<'a, AnimationComponent>);
 
-    fn run(&mut self, (control_data, mut position, control, mut animation): Self::SystemData) {
+    fn run(&mut self, (control_data, clock, event_queue, scene, mut movement, control, mut animation): Self::SystemData) {
         use specs::Join;
 
-        //there should only be one really, but maybe multiple will be useful at some point
-        for (position, control, animation) in (&mut position, &control, &mut animation).join() {
+        //event scripts are in charge of the party while they run, and nobody moves between maps
+        if event_queue.is_running() || scene.is_changing() { return; }
+
+        //only the party leader is given player control, the rest of the party follows them
+        for (movement, control, animation) in (&mut movement, &control, &mut animation).join() {
 
             let mut displacement = (0.0 as f32, 0.0 as f32);
-            if control_data.move_left  { displacement.0 -= 1.0; }
-            if control_data.move_right { displacement.0 += 1.0; }
-            if control_data.move_up    { displacement.1 -= 1.0; }
-            if control_data.move_down  { displacement.1 += 1.0; }
+            if control_data.is_held(InputAction::MoveLeft)  { displacement.0 -= 1.0; }
+            if control_data.is_held(InputAction::MoveRight) { displacement.0 += 1.0; }
+            if control_data.is_held(InputAction::MoveUp)    { displacement.1 -= 1.0; }
+            if control_data.is_held(InputAction::MoveDown)  { displacement.1 += 1.0; }
 
 
             //set animation
@@ -36,21 +49,25 @@ impl<'a> System<'a> for PlayerControlSystem {
             else { animation.set("idle") }
 
 
+            //speed is per second so only move the part that fits in this update
+            let speed = if control_data.is_held(InputAction::Run) { control.speed * RUN_SPEED_MULTIPLIER } else { control.speed };
+            let distance = speed * clock.delta_seconds;
+
             //if we move at an angle then speed would be evenly split between the two directions
             if displacement.0.abs() == 1.0 && displacement.1.abs() == 1.0 {
-                displacement.0 *= control.speed / 2.0;
-                displacement.1 *= control.speed / 2.0;
+                displacement.0 *= distance / 2.0;
+                displacement.1 *= distance / 2.0;
 
             //otherwise whichever direction has a non-zero value will get the full speed
             } else {
-                displacement.0 *= control.speed;
-                displacement.1 *= control.speed;
+                displacement.0 *= distance;
+                displacement.1 *= distance;
             }
 
             
-            //update position
-            position.map_pos.x += displacement.0;
-            position.map_pos.y += displacement.1;
+            //request the move, the collision system decides how far we actually get
+            movement.displacement.x += displacement.0;
+            movement.displacement.y += displacement.1;
 
         }
     }
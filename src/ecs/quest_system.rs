@@ -0,0 +1,262 @@
+use specs::{Read, ReadExpect, WriteExpect, ReadStorage, System, World, ReaderId, LazyUpdate};
+
+use super::position_component::PositionComponent;
+use super::physics_components::ColliderComponent;
+use super::trigger_system::leader_area;
+use super::resources::{QuestLog, GameEvent, GameEvents, Inventory, Party, CurrentMap};
+use super::quests::reward_quest;
+
+use crate::assets::{AssetDatabase, AssetContainer, QuestObjective, areas_overlap};
+
+
+//keeps track of the objectives of every quest the party is working on. Conversations and
+//  defeated enemies are counted as they are announced, carried items and where the party leader
+//  stands are checked every update. Once every objective of a stage is done the quest moves on,
+//  and after the last stage the party gets the quest's reward.
+pub struct QuestSystem {
+    events: Option<ReaderId<GameEvent>>, //None until set up
+}
+
+impl QuestSystem {
+
+    pub fn new() -> QuestSystem {
+        QuestSystem {
+            events: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for QuestSystem {
+    type SystemData = ( WriteExpect<'a, QuestLog>,
+                        WriteExpect<'a, AssetDatabase>,
+                        ReadExpect<'a, GameEvents>,
+                        ReadExpect<'a, Inventory>,
+                        ReadExpect<'a, Party>,
+                        ReadExpect<'a, CurrentMap>,
+                        Read<'a, LazyUpdate>,
+                        ReadStorage<'a, PositionComponent>,
+                        ReadStorage<'a, ColliderComponent>);
+
+    fn setup(&mut self, world: &mut World) {
+        use specs::SystemData;
+        Self::SystemData::setup(world);
+
+        self.events = Some( world.fetch_mut::<GameEvents>().track() );
+    }
+
+    fn run(&mut self, (mut quest_log, mut asset_database, game_events, inventory, party, current_map,
+                       lazy_update, position, collider): Self::SystemData) {
+
+        //events are read every update so old ones don't count towards a quest started later
+        let events: Vec<GameEvent> = match &mut self.events {
+            Some(reader) => game_events.read(reader).cloned().collect(),
+            None => vec![],
+        };
+
+        //named objects on the current map the party leader is standing in
+        let regions: Vec<String> = match (&current_map.name, leader_area(&party, &position, &collider)) {
+            (Some(map_name), Some(leader_area)) => match asset_database.get_asset(map_name) {
+                AssetContainer::Tilemap(tilemap) => tilemap.objects.iter()
+                    .filter(|object| !object.name.is_empty() && areas_overlap(&object.area, &leader_area))
+                    .map(|object| object.name.clone())
+                    .collect(),
+                _ => vec![],
+            },
+            _ => vec![],
+        };
+
+        for progress in quest_log.quests.iter_mut().filter(|progress| !progress.completed) {
+            let quest = match asset_database.get_asset(&progress.name) {
+                AssetContainer::Quest(quest) => quest,
+                _ => continue,
+            };
+
+            let stage = match quest.stages.get(progress.stage) {
+                Some(stage) => stage,
+                None => continue,
+            };
+            progress.progress.resize(stage.objectives.len(), 0);
+
+            for (objective, done) in stage.objectives.iter().zip(progress.progress.iter_mut()) {
+                let now = match objective {
+                    QuestObjective::TalkTo(name) => {
+                        let talked = events.iter().any(|event| match event {
+                            GameEvent::Talked { entity, dialogue } => entity.as_ref() == Some(name) || dialogue == name,
+                            _ => false,
+                        });
+                        if talked { 1 } else { *done }
+                    },
+                    QuestObjective::Collect { item, .. } => inventory.count(item),
+                    QuestObjective::Defeat { character, .. } => {
+                        let defeated = events.iter().filter(|event| **event == GameEvent::Defeated(character.clone())).count();
+                        done.saturating_add(defeated as u32)
+                    },
+                    QuestObjective::Reach { region, map } => {
+                        let on_map = map.is_none() || *map == current_map.name;
+                        if on_map && regions.contains(region) { 1 } else { *done }
+                    },
+                };
+                *done = now.min(objective.needed());
+            }
+
+            let stage_done = stage.objectives.iter().zip(progress.progress.iter())
+                                  .all(|(objective, done)| *done >= objective.needed());
+            if !stage_done { continue; }
+
+            progress.stage += 1;
+            match quest.stages.get(progress.stage) {
+                Some(next_stage) => {
+                    progress.progress = vec![0; next_stage.objectives.len()];
+                    info!("[Quests] {} moved on to stage {}.", quest.title, progress.stage + 1);
+                },
+                None => {
+                    progress.progress.clear();
+                    progress.completed = true;
+                    info!("[Quests] {} is complete.", quest.title);
+
+                    //rewards can level up the party which needs the whole world
+                    let (quest_name, reward) = (progress.name.clone(), quest.reward.clone());
+                    lazy_update.exec_mut(move |world| reward_quest(world, &quest_name, &reward));
+                },
+            }
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specs::{WorldExt, Builder, RunNow};
+    use coffee::graphics::{Point, Rectangle};
+    use std::collections::HashMap;
+    use super::super::{GameFlags, register_components};
+    use super::super::quests::start_quest;
+    use crate::assets::{Quest, QuestStage, QuestReward, FlagEffect, Tilemap, MapObject};
+
+    fn stage(objectives: Vec<QuestObjective>) -> QuestStage {
+        QuestStage { description: String::new(), objectives }
+    }
+
+    fn world_with(quest: Quest) -> (World, QuestSystem) {
+        let mut town = Tilemap::new(32.0, 32.0);
+        town.objects.push( MapObject {
+            name: "old well".to_string(),
+            kind: "marker".to_string(),
+            area: Rectangle { x: 100.0, y: 100.0, width: 32.0, height: 32.0 },
+            properties: HashMap::new(),
+        });
+
+        let mut asset_database = AssetDatabase::new();
+        asset_database.add_asset("lost ring".to_string(), AssetContainer::Quest(quest));
+        asset_database.add_asset("town".to_string(), AssetContainer::Tilemap(town));
+
+        let mut world = World::new();
+        register_components(&mut world);
+        world.insert(asset_database);
+        world.insert(QuestLog::new());
+        world.insert(GameEvents::new());
+        world.insert(GameFlags::new());
+        world.insert(Inventory::new());
+        world.insert(Party::new());
+        world.insert(CurrentMap { name: Some("town".to_string()) });
+
+        let mut system = QuestSystem::new();
+        System::setup(&mut system, &mut world);
+
+        {
+            let mut asset_database = world.write_resource::<AssetDatabase>();
+            let mut quest_log = world.write_resource::<QuestLog>();
+            let mut flags = world.write_resource::<GameFlags>();
+            assert!(start_quest(&mut asset_database, &mut quest_log, &mut flags, "lost ring"));
+        }
+
+        (world, system)
+    }
+
+    fn update(world: &mut World, system: &mut QuestSystem) {
+        system.run_now(world);
+        world.maintain();
+    }
+
+    fn progress(world: &World) -> (usize, Vec<u32>, bool) {
+        let quest_log = world.read_resource::<QuestLog>();
+        let quest = &quest_log.quests[0];
+        (quest.stage, quest.progress.clone(), quest.completed)
+    }
+
+    #[test]
+    fn quests_move_through_their_stages_and_reward_the_party() {
+        let (mut world, mut system) = world_with( Quest {
+            title: "The Lost Ring".to_string(),
+            description: String::new(),
+            stages: vec![
+                stage(vec![ QuestObjective::TalkTo("villager".to_string()) ]),
+                stage(vec![ QuestObjective::Defeat { character: "Slime".to_string(), count: 2 },
+                            QuestObjective::Collect { item: "Potion".to_string(), count: 2 } ]),
+            ],
+            reward: QuestReward { effects: vec![FlagEffect::parse("gold = gold + 50").unwrap()], ..QuestReward::default() },
+        });
+        assert!(world.read_resource::<GameFlags>().is_set("quest started: lost ring"));
+
+        world.write_resource::<GameEvents>().announce( GameEvent::Talked { entity: Some("villager".to_string()),
+                                                                           dialogue: "greeting".to_string() } );
+        update(&mut world, &mut system);
+        assert_eq!(progress(&world), (1, vec![0, 0], false));
+
+        world.write_resource::<GameEvents>().announce( GameEvent::Defeated("Slime".to_string()) );
+        world.write_resource::<GameEvents>().announce( GameEvent::Defeated("Bat".to_string()) );
+        world.write_resource::<Inventory>().add("Potion", 2, 99);
+        update(&mut world, &mut system);
+        assert_eq!(progress(&world), (1, vec![1, 2], false));
+
+        world.write_resource::<GameEvents>().announce( GameEvent::Defeated("Slime".to_string()) );
+        update(&mut world, &mut system);
+        assert_eq!(progress(&world), (2, vec![], true));
+
+        let flags = world.read_resource::<GameFlags>();
+        assert!(flags.is_set("quest done: lost ring"));
+        assert_eq!(flags.get("gold"), Some(&50.into()));
+    }
+
+    #[test]
+    fn reaching_a_region_needs_the_leader_inside_it() {
+        let (mut world, mut system) = world_with( Quest {
+            title: "The Lost Ring".to_string(),
+            description: String::new(),
+            stages: vec![ stage(vec![ QuestObjective::Reach { region: "old well".to_string(), map: Some("town".to_string()) } ]) ],
+            reward: QuestReward::default(),
+        });
+
+        let leader = world.create_entity().with(PositionComponent { map_pos: Point::new(0.0, 0.0) }).build();
+        world.write_resource::<Party>().active.push(leader);
+        update(&mut world, &mut system);
+        assert_eq!(progress(&world), (0, vec![0], false));
+
+        world.write_storage::<PositionComponent>().get_mut(leader).unwrap().map_pos = Point::new(110.0, 110.0);
+        update(&mut world, &mut system);
+        assert!(progress(&world).2);
+    }
+
+    #[test]
+    fn only_events_after_the_stage_started_count() {
+        let (mut world, mut system) = world_with( Quest {
+            title: "The Lost Ring".to_string(),
+            description: String::new(),
+            stages: vec![
+                stage(vec![ QuestObjective::Collect { item: "Potion".to_string(), count: 1 } ]),
+                stage(vec![ QuestObjective::Defeat { character: "Slime".to_string(), count: 1 } ]),
+            ],
+            reward: QuestReward::default(),
+        });
+
+        world.write_resource::<Inventory>().add("Potion", 1, 99);
+        world.write_resource::<GameEvents>().announce( GameEvent::Defeated("Slime".to_string()) );
+        update(&mut world, &mut system);
+        assert_eq!(progress(&world), (1, vec![0], false));
+
+        update(&mut world, &mut system);
+        assert_eq!(progress(&world), (1, vec![0], false));
+    }
+}
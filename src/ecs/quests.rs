@@ -0,0 +1,99 @@
+use specs::{World, WorldExt};
+
+use super::{QuestLog, GameFlags, Inventory, Party};
+use super::inventory::add_item;
+use super::leveling::gain_experience;
+
+use crate::assets::{AssetDatabase, AssetContainer, Quest, QuestReward};
+
+
+//A quest as shown in the quest log.
+pub struct QuestEntry {
+    pub title: String,
+    pub description: String,
+    pub stage: String, //description of the stage being worked on
+    pub objectives: Vec<(String, u32, u32)>, //what to do, how far along it is, and how much is needed
+    pub completed: bool,
+}
+
+
+//takes on a quest at its first stage. Quests can only be taken on once.
+// returns: whether the quest was started
+pub(super) fn start_quest(asset_database: &mut AssetDatabase, quest_log: &mut QuestLog, flags: &mut GameFlags,
+                          quest_name: &str) -> bool {
+    let objectives = match asset_database.get_asset(&quest_name.to_string()) {
+        AssetContainer::Quest(quest) => quest.stages.first().map(|stage| stage.objectives.len()).unwrap_or(0),
+        _ => {
+            warn!("[Quests] {} {} {}.",
+                  "Requested quest",
+                  quest_name,
+                  "does not exist",
+            );
+            return false;
+        }
+    };
+
+    if !quest_log.start(quest_name, objectives) { return false; }
+
+    info!("[Quests] The party took on {}.", quest_name);
+    flags.set(&Quest::started_flag(quest_name), true);
+    true
+}
+
+
+//gives the party what they earned for finishing a quest
+pub fn reward_quest(world: &World, quest_name: &str, reward: &QuestReward) {
+    for (item_name, count) in reward.items.iter() {
+        let added = add_item(world, item_name, *count);
+        if added > 0 {
+            info!("[Quests] The party received {} {}.", added, item_name);
+        }
+    }
+
+    if reward.experience > 0 {
+        let active_members = world.read_resource::<Party>().active.clone();
+        for member in active_members {
+            for message in gain_experience(world, member, reward.experience) {
+                info!("[Quests] {}", message);
+            }
+        }
+    }
+
+    let inventory = world.read_resource::<Inventory>();
+    let mut flags = world.write_resource::<GameFlags>();
+    flags.apply(&reward.effects, &inventory);
+    flags.set(&Quest::done_flag(quest_name), true);
+}
+
+
+//quests for the quest log, ones still being worked on first
+pub fn quest_log_view(world: &World) -> Vec<QuestEntry> {
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let quest_log = world.read_resource::<QuestLog>();
+
+    let mut entries: Vec<QuestEntry> = quest_log.quests.iter().filter_map(|progress| {
+        let quest = match asset_database.get_asset(&progress.name) {
+            AssetContainer::Quest(quest) => quest,
+            _ => return None,
+        };
+
+        let stage = quest.stages.get(progress.stage);
+
+        Some( QuestEntry {
+            title: quest.title.clone(),
+            description: quest.description.clone(),
+            stage: stage.map(|stage| stage.description.clone()).unwrap_or_default(),
+            objectives: stage.map(|stage| {
+                stage.objectives.iter().enumerate()
+                     .map(|(index, objective)| (objective.describe(),
+                                                progress.progress.get(index).copied().unwrap_or(0),
+                                                objective.needed()))
+                     .collect()
+            }).unwrap_or_default(),
+            completed: progress.completed,
+        })
+    }).collect();
+
+    entries.sort_by_key(|entry| entry.completed);
+    entries
+}
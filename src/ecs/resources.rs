@@ -0,0 +1,716 @@
+use std::collections::{HashMap, VecDeque};
+
+use specs::Entity;
+use specs::shrev::{EventChannel, ReaderId};
+
+use coffee::graphics::{Point, Rectangle, Transformation, Vector};
+
+use crate::assets::{SpritePos, EventCommand, MapArrival, Variable, Expression, ExpressionScope, FlagEffect};
+
+
+//name of the tilemap asset the world is currently taking place on
+pub struct CurrentMap {
+    pub name: Option<String>,
+}
+
+
+//part of the map that is visible on the screen, in map coordinates.
+//  Used to skip drawing things that can't be seen.
+pub struct ViewArea {
+    pub area: Rectangle<f32>,
+}
+
+
+//tiles of the current map that are on screen, grouped so each group uses a single tileset.
+//  Groups are ordered from the bottom layer up and have to be drawn in that order, drawing
+//  a whole tileset at once would put its tiles above tiles on higher layers from other tilesets.
+pub struct TileBatches {
+    pub batches: Vec<TileBatch>,
+}
+
+pub struct TileBatch {
+    pub layer: usize,
+    pub sheet_name: String, //asset name of the tileset's sprite sheet
+    pub sprites: Vec<(Point, SpritePos)>,
+}
+
+
+//Time that passed in the game world. Updated once per game update so systems can express
+//  speeds and durations in seconds instead of frames. The step is fixed on purpose: coffee's
+//  timer only lets an update run once a full 1/TICKS_PER_SECOND has built up and carries any
+//  time left over to the next frame, so every update stands for exactly that long. A fixed
+//  step also keeps movement and collisions the same on every machine.
+pub struct GameClock {
+    pub delta_seconds: f32, //time since the last update
+    pub elapsed_seconds: f64, //time since the world was created
+}
+
+impl GameClock {
+
+    pub fn new() -> GameClock {
+        GameClock {
+            delta_seconds: 0.0,
+            elapsed_seconds: 0.0,
+        }
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        self.delta_seconds = seconds;
+        self.elapsed_seconds += f64::from(seconds);
+    }
+}
+
+
+//name of the flag holding the party's gold, so conditions and effects can use it like any other
+pub const GOLD_FLAG: &str = "gold";
+
+//Named values that remember what the player has done, shared by dialogue, events, and anything
+//  else that needs to know. Flags hold true or false, a whole number, or text, and flags that
+//  were never set read as false. Every change is announced on a channel systems can follow.
+pub struct GameFlags {
+    pub flags: HashMap<String, Variable>,
+    changes: EventChannel<String>, //names of flags whose value changed
+}
+
+impl GameFlags {
+
+    pub fn new() -> GameFlags {
+        GameFlags {
+            flags: HashMap::new(),
+            changes: EventChannel::new(),
+        }
+    }
+
+    pub fn get(&self, flag: &str) -> Option<&Variable> {
+        self.flags.get(flag)
+    }
+
+    pub fn is_set(&self, flag: &str) -> bool {
+        self.flags.get(flag).map(Variable::is_true).unwrap_or(false)
+    }
+
+    //gold the party is carrying, a flag that isn't a positive number counts as none
+    pub fn gold(&self) -> u32 {
+        match self.flags.get(GOLD_FLAG) {
+            Some(Variable::Int(gold)) => (*gold).clamp(0, i64::from(u32::MAX)) as u32,
+            _ => 0,
+        }
+    }
+
+    pub fn set_gold(&mut self, gold: u32) {
+        self.set(GOLD_FLAG, i64::from(gold));
+    }
+
+    //changes a flag, announcing it when the value is different
+    pub fn set(&mut self, flag: &str, value: impl Into<Variable>) {
+        let value = value.into();
+        if self.flags.get(flag) == Some(&value) { return; }
+
+        self.flags.insert(flag.to_string(), value);
+        self.changes.single_write(flag.to_string());
+    }
+
+    //starts following flag changes, only changes made after this are seen
+    pub fn track_changes(&mut self) -> ReaderId<String> {
+        self.changes.register_reader()
+    }
+
+    //names of the flags changed since the reader last looked
+    pub fn changes<'a>(&'a self, reader: &'a mut ReaderId<String>) -> impl Iterator<Item = &'a String> {
+        self.changes.read(reader)
+    }
+
+    //true when every condition holds. Conditions can also ask about the party's items.
+    pub fn meets(&self, conditions: &[Expression], inventory: &Inventory) -> bool {
+        let scope = FlagScope { flags: self, inventory };
+        conditions.iter().all(|condition| condition.holds(&scope))
+    }
+
+    //makes each change in turn, so later ones see the values earlier ones gave
+    pub fn apply(&mut self, effects: &[FlagEffect], inventory: &Inventory) {
+        for effect in effects {
+            match effect.value.evaluate(&FlagScope { flags: self, inventory }) {
+                Ok(value) => self.set(&effect.flag, value),
+                Err(err) => warn!("[Flags] Could not change {}, \"{}\" could not be worked out. {}.",
+                                  effect.flag, effect.value.source(), err),
+            }
+        }
+    }
+}
+
+//lets expressions look up flags and the party's items
+struct FlagScope<'a> {
+    flags: &'a GameFlags,
+    inventory: &'a Inventory,
+}
+
+impl ExpressionScope for FlagScope<'_> {
+    fn variable(&self, name: &str) -> Option<Variable> {
+        self.flags.get(name).cloned()
+    }
+
+    fn item_count(&self, item: &str) -> u32 {
+        self.inventory.count(item)
+    }
+}
+
+
+//Things that happened in the world that other parts of the game follow, like quests counting
+//  the enemies the party beat. Anything can announce one, followers only see what came after
+//  they started following.
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    Talked { entity: Option<String>, dialogue: String }, //the player started a conversation with someone
+    Defeated(String), //name of an enemy character the party beat
+}
+
+pub struct GameEvents {
+    channel: EventChannel<GameEvent>,
+}
+
+impl GameEvents {
+
+    pub fn new() -> GameEvents {
+        GameEvents {
+            channel: EventChannel::new(),
+        }
+    }
+
+    pub fn announce(&mut self, event: GameEvent) {
+        self.channel.single_write(event);
+    }
+
+    //starts following events, only ones announced after this are seen
+    pub fn track(&mut self) -> ReaderId<GameEvent> {
+        self.channel.register_reader()
+    }
+
+    //events announced since the reader last looked
+    pub fn read<'a>(&'a self, reader: &'a mut ReaderId<GameEvent>) -> impl Iterator<Item = &'a GameEvent> {
+        self.channel.read(reader)
+    }
+}
+
+
+//How far the party has got with a quest.
+#[derive(Clone, PartialEq, Debug)]
+pub struct QuestProgress {
+    pub name: String, //name of the quest asset
+    pub stage: usize, //index of the stage being worked on, the number of stages once completed
+    pub progress: Vec<u32>, //progress on each objective of the current stage
+    pub completed: bool,
+}
+
+//Quests the party has taken on, in the order they were started.
+pub struct QuestLog {
+    pub quests: Vec<QuestProgress>,
+}
+
+impl QuestLog {
+
+    pub fn new() -> QuestLog {
+        QuestLog {
+            quests: vec![],
+        }
+    }
+
+    pub fn has(&self, quest_name: &str) -> bool {
+        self.quests.iter().any(|quest| quest.name == quest_name)
+    }
+
+    //adds a quest at its first stage, quests can only be taken on once
+    // returns: whether the quest was added
+    pub fn start(&mut self, quest_name: &str, objectives: usize) -> bool {
+        if self.has(quest_name) { return false; }
+
+        self.quests.push( QuestProgress {
+            name: quest_name.to_string(),
+            stage: 0,
+            progress: vec![0; objectives],
+            completed: false,
+        });
+        true
+    }
+}
+
+
+//The conversation the player is currently in, if any. Movement stops while talking.
+pub struct ActiveDialogue {
+    pub dialogue_name: Option<String>,
+    pub node_id: String,
+}
+
+impl ActiveDialogue {
+
+    pub fn new() -> ActiveDialogue {
+        ActiveDialogue {
+            dialogue_name: None,
+            node_id: String::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.dialogue_name.is_some()
+    }
+
+    pub fn end(&mut self) {
+        self.dialogue_name = None;
+        self.node_id.clear();
+    }
+}
+
+
+//Set when something on the map starts a battle. The playing state hands it over to a battle.
+pub struct PendingBattle {
+    pub encounter: Option<String>, //name of the encounter asset to fight
+}
+
+
+//Set when an event script opens a shop. The playing state shows it over the map.
+pub struct PendingShop {
+    pub shop: Option<String>, //name of the shop asset to open
+}
+
+
+//How many of each limited item every shop has sold so far, by shop and then item name. Kept
+//  so shops stay sold out.
+pub struct ShopSales {
+    pub sold: HashMap<String, HashMap<String, u32>>,
+}
+
+impl ShopSales {
+
+    pub fn new() -> ShopSales {
+        ShopSales {
+            sold: HashMap::new(),
+        }
+    }
+
+    pub fn sold(&self, shop: &str, item: &str) -> u32 {
+        self.sold.get(shop).and_then(|items| items.get(item)).copied().unwrap_or(0)
+    }
+
+    pub fn record(&mut self, shop: &str, item: &str, count: u32) {
+        let sold = self.sold.entry(shop.to_string()).or_default().entry(item.to_string()).or_insert(0);
+        *sold = sold.saturating_add(count);
+    }
+}
+
+
+//seconds the screen takes to fade out before a map change, and again to fade back in after
+pub const FADE_SECONDS: f32 = 0.35;
+
+//A move to another map asked for by a warp or an event script.
+#[derive(Clone)]
+pub struct MapChange {
+    pub map: String, //name of the tilemap asset to go to
+    pub arrival: MapArrival,
+}
+
+//Moves the party between maps. A change fades the screen out, the playing state swaps the maps
+//  over once it is black, then it fades back in. The player can't move until it is done.
+pub struct SceneManager {
+    transition: Option<SceneTransition>,
+}
+
+struct SceneTransition {
+    change: MapChange,
+    timer: f32,
+    swapped: bool, //the new map is loaded and the screen is fading back in
+}
+
+impl SceneManager {
+
+    pub fn new() -> SceneManager {
+        SceneManager {
+            transition: None,
+        }
+    }
+
+    //starts moving to another map, unless a change is already under way
+    // returns: whether the change was started
+    pub fn request(&mut self, change: MapChange) -> bool {
+        if self.transition.is_some() { return false; }
+
+        self.transition = Some( SceneTransition { change, timer: 0.0, swapped: false } );
+        true
+    }
+
+    pub fn is_changing(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    //how dark the screen is, from 0 when the map can be seen to 1 when it is black
+    pub fn fade(&self) -> f32 {
+        match &self.transition {
+            Some(transition) => {
+                let progress = (transition.timer / FADE_SECONDS).min(1.0);
+                if transition.swapped { 1.0 - progress } else { progress }
+            },
+            None => 0.0,
+        }
+    }
+
+    //moves the transition along by the time that passed
+    // returns: the change to make now, once the screen has gone black
+    pub fn advance(&mut self, seconds: f32) -> Option<MapChange> {
+        let transition = self.transition.as_mut()?;
+        transition.timer += seconds;
+
+        if transition.timer < FADE_SECONDS { return None; }
+
+        if transition.swapped {
+            self.transition = None;
+            None
+        } else {
+            transition.swapped = true;
+            transition.timer = 0.0;
+            Some(transition.change.clone())
+        }
+    }
+}
+
+
+//An event script part way through running.
+pub struct RunningEvent {
+    pub name: String,
+    pub step: usize, //index of the command being run
+    pub started: bool, //the current command has been started and is being waited on
+    pub timer: f32, //seconds spent on the current command
+    pub from_script: VecDeque<EventCommand>, //asked for by a script, run before the next step
+}
+
+impl RunningEvent {
+
+    pub fn new(name: String, step: usize) -> RunningEvent {
+        RunningEvent {
+            name,
+            step,
+            started: false,
+            timer: 0.0,
+            from_script: VecDeque::new(),
+        }
+    }
+}
+
+
+//Event scripts that were triggered. Only one runs at a time, the rest wait their turn in order.
+//  The player can't move while a script runs.
+pub struct EventQueue {
+    pub waiting: VecDeque<String>,
+    pub running: Option<RunningEvent>,
+}
+
+impl EventQueue {
+
+    pub fn new() -> EventQueue {
+        EventQueue {
+            waiting: VecDeque::new(),
+            running: None,
+        }
+    }
+
+    pub fn push(&mut self, event: &str) {
+        self.waiting.push_back(event.to_string());
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some() || !self.waiting.is_empty()
+    }
+}
+
+
+//most members that can be in the active party at once, everyone else waits in reserve
+pub const MAX_ACTIVE_MEMBERS: usize = 4;
+
+//how far apart in pixels the points along the leader's path are kept
+const TRAIL_STEP: f32 = 4.0;
+
+
+//Characters travelling together. The first active member is the leader who is controlled on the
+//  map, the other active members follow along behind and everyone active fights in battles.
+//  Reserve members are off the map until they are swapped in.
+pub struct Party {
+    pub active: Vec<Entity>,
+    pub reserve: Vec<Entity>,
+    trail: VecDeque<Point>, //where the leader has walked, newest first
+}
+
+impl Party {
+
+    pub fn new() -> Party {
+        Party {
+            active: vec![],
+            reserve: vec![],
+            trail: VecDeque::new(),
+        }
+    }
+
+    pub fn leader(&self) -> Option<Entity> {
+        self.active.first().copied()
+    }
+
+    //active members followed by the reserve, the order menus show them in
+    pub fn members(&self) -> Vec<Entity> {
+        self.active.iter().chain(self.reserve.iter()).copied().collect()
+    }
+
+    pub fn is_member(&self, entity: Entity) -> bool {
+        self.active.contains(&entity) || self.reserve.contains(&entity)
+    }
+
+    //remembers where the leader is. Points are only added once the leader has moved far enough
+    //  so followers keep their distance when the leader stops.
+    pub fn record_trail(&mut self, leader_position: Point, length: usize) {
+        let moved_enough = match self.trail.front() {
+            Some(last) => (leader_position - *last).norm() >= TRAIL_STEP,
+            None => true,
+        };
+
+        if moved_enough {
+            self.trail.push_front(leader_position);
+        }
+        self.trail.truncate(length.max(1));
+    }
+
+    //forgets the leader's path, used when the party is placed somewhere new
+    pub fn clear_trail(&mut self) {
+        self.trail.clear();
+    }
+
+    //a point the given number of steps back along the leader's path, or the oldest one known
+    pub fn trail_point(&self, steps_back: usize) -> Option<Point> {
+        self.trail.get(steps_back).or_else(|| self.trail.back()).copied()
+    }
+}
+
+
+//Places a character can wear equipment in this campaign, in the order menus show them.
+pub struct EquipmentSlots {
+    pub slots: Vec<String>,
+}
+
+
+//Items the party is carrying and how many of each, in the order they were first picked up.
+pub struct Inventory {
+    pub items: Vec<(String, u32)>,
+}
+
+impl Inventory {
+
+    pub fn new() -> Inventory {
+        Inventory {
+            items: vec![],
+        }
+    }
+
+    pub fn count(&self, item: &str) -> u32 {
+        self.items.iter().find(|(name, _)| name == item).map(|(_, count)| *count).unwrap_or(0)
+    }
+
+    //adds as many as fit under the stack limit
+    // returns: how many were added
+    pub fn add(&mut self, item: &str, count: u32, stack_limit: u32) -> u32 {
+        let added = count.min( stack_limit.saturating_sub(self.count(item)) );
+        if added == 0 { return 0; }
+
+        match self.items.iter_mut().find(|(name, _)| name == item) {
+            Some((_, carried)) => *carried += added,
+            None => self.items.push( (item.to_string(), added) ),
+        }
+
+        added
+    }
+
+    //takes items away, nothing is removed unless there are enough
+    // returns: false when the party doesn't have that many
+    pub fn remove(&mut self, item: &str, count: u32) -> bool {
+        let index = match self.items.iter().position(|(name, _)| name == item) {
+            Some(index) => index,
+            None => return count == 0,
+        };
+
+        if self.items[index].1 < count { return false; }
+
+        self.items[index].1 -= count;
+        if self.items[index].1 == 0 {
+            self.items.remove(index);
+        }
+
+        true
+    }
+}
+
+
+//limits on how far the camera can zoom in or out
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+
+//Decides which part of the map is shown on screen. Follows an entity around and is kept inside the map.
+pub struct Camera {
+    pub position: Point, //center of the view in map coordinates
+    pub zoom: f32,
+    pub follow_speed: f32, //how quickly the camera catches up to its target, higher is faster
+    pub target: Option<Entity>, //entity to follow, when None the player controlled entity is followed
+    pub screen_size: (f32, f32),
+    snap_next: bool,
+}
+
+impl Camera {
+
+    pub fn new() -> Camera {
+        Camera {
+            position: Point::new(0.0, 0.0),
+            zoom: 1.0,
+            follow_speed: 6.0,
+            target: None,
+            screen_size: (0.0, 0.0),
+            snap_next: true, //don't slide over from the map origin the first time
+        }
+    }
+
+    //jumps straight to the target next update instead of sliding over
+    pub fn snap(&mut self) {
+        self.snap_next = true;
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    //moves the camera toward a point. The fraction of the distance covered depends on how much
+    //  time passed so the camera moves the same no matter the frame rate.
+    pub fn move_toward(&mut self, point: Point, delta_seconds: f32) {
+        let amount = if self.snap_next { 
+            1.0 
+        } else { 
+            1.0 - (-self.follow_speed.max(0.0) * delta_seconds).exp()
+        };
+        self.snap_next = false;
+
+        self.position.x += (point.x - self.position.x) * amount;
+        self.position.y += (point.y - self.position.y) * amount;
+    }
+
+    //size of the view in map coordinates
+    pub fn view_size(&self) -> (f32, f32) {
+        (self.screen_size.0 / self.zoom, self.screen_size.1 / self.zoom)
+    }
+
+    //keeps the view inside a map of the given size. Maps smaller than the view are centered.
+    pub fn clamp_to(&mut self, map_width: f32, map_height: f32) {
+        let (view_width, view_height) = self.view_size();
+
+        self.position.x = clamp_axis(self.position.x, view_width, map_width);
+        self.position.y = clamp_axis(self.position.y, view_height, map_height);
+    }
+
+    pub fn view_area(&self) -> Rectangle<f32> {
+        let (view_width, view_height) = self.view_size();
+
+        Rectangle {
+            x: self.position.x - view_width / 2.0,
+            y: self.position.y - view_height / 2.0,
+            width: view_width,
+            height: view_height,
+        }
+    }
+
+    //transformation from map coordinates to screen coordinates
+    pub fn transformation(&self) -> Transformation {
+        let view_area = self.view_area();
+
+        Transformation::scale(self.zoom)
+            * Transformation::translate( Vector::new(-view_area.x.round(), -view_area.y.round()) )
+    }
+}
+
+fn clamp_axis(center: f32, view_size: f32, map_size: f32) -> f32 {
+    if map_size <= view_size {
+        map_size / 2.0
+    } else {
+        center.clamp(view_size / 2.0, map_size - view_size / 2.0)
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inventory_add_stops_at_the_stack_limit() {
+        let mut inventory = Inventory::new();
+
+        assert_eq!(inventory.add("Potion", 3, 5), 3);
+        assert_eq!(inventory.add("Potion", 3, 5), 2);
+        assert_eq!(inventory.add("Potion", 1, 5), 0);
+        assert_eq!(inventory.count("Potion"), 5);
+        assert_eq!(inventory.items.len(), 1);
+    }
+
+    #[test]
+    fn inventory_add_nothing_leaves_no_entry() {
+        let mut inventory = Inventory::new();
+
+        assert_eq!(inventory.add("Potion", 0, 5), 0);
+        assert!(inventory.items.is_empty());
+    }
+
+    #[test]
+    fn inventory_remove_takes_part_of_a_stack() {
+        let mut inventory = Inventory::new();
+        inventory.add("Potion", 5, 99);
+
+        assert!(inventory.remove("Potion", 2));
+        assert_eq!(inventory.count("Potion"), 3);
+    }
+
+    #[test]
+    fn inventory_remove_needs_enough_items() {
+        let mut inventory = Inventory::new();
+        inventory.add("Potion", 2, 99);
+
+        assert!(!inventory.remove("Potion", 3));
+        assert_eq!(inventory.count("Potion"), 2);
+        assert!(!inventory.remove("Ether", 1));
+    }
+
+    #[test]
+    fn inventory_remove_last_item_drops_the_entry() {
+        let mut inventory = Inventory::new();
+        inventory.add("Potion", 2, 99);
+        inventory.add("Ether", 1, 99);
+
+        assert!(inventory.remove("Potion", 2));
+        assert_eq!(inventory.items, vec![("Ether".to_string(), 1)]);
+    }
+
+    fn change_to(map: &str) -> MapChange {
+        MapChange { map: map.to_string(), arrival: MapArrival::MapSpawn }
+    }
+
+    #[test]
+    fn scene_changes_once_the_screen_is_black() {
+        let mut scene = SceneManager::new();
+        assert!(scene.request(change_to("house")));
+        assert!(!scene.request(change_to("cave")));
+
+        assert!(scene.advance(FADE_SECONDS * 0.5).is_none());
+        assert!((scene.fade() - 0.5).abs() < 0.001);
+
+        let change = scene.advance(FADE_SECONDS * 0.5).expect("the map should change once faded out");
+        assert_eq!(change.map, "house");
+        assert_eq!(scene.fade(), 1.0);
+        assert!(scene.is_changing());
+
+        assert!(scene.advance(FADE_SECONDS * 0.5).is_none());
+        assert!((scene.fade() - 0.5).abs() < 0.001);
+
+        assert!(scene.advance(FADE_SECONDS * 0.5).is_none());
+        assert!(!scene.is_changing());
+        assert_eq!(scene.fade(), 0.0);
+        assert!(scene.request(change_to("cave")));
+    }
+}
@@ -0,0 +1,357 @@
+use specs::{World, WorldExt, Builder, Join};
+
+use coffee::graphics::{Point, Vector};
+
+use super::{PositionComponent, VisualComponent, AnimationComponent, PlayerControlComponent,
+            MovementComponent, ColliderComponent, InteractionComponent, CharacterComponent, LevelComponent, 
+            BaseStatsComponent, VitalsComponent, EquipmentComponent, NameComponent, TriggerComponent,
+            MapComponent, WarpComponent, CurrentMap, Camera, GameFlags, ActiveDialogue, Inventory, Party, EventQueue,
+            QuestLog, ShopSales};
+use super::resources::{RunningEvent, QuestProgress};
+use super::party::gather_party;
+
+use crate::assets::{AssetDatabase, AssetContainer, Stats, TriggerCondition};
+use crate::save_game::{SavedWorld, SavedEntity, SavedVisual, SavedAnimation, SavedCollider, SavedCharacter, 
+                       SavedStats, SavedTrigger, SavedWarp, SavedQuest, SaveError};
+
+
+//copies the state of every entity and the world resources that matter into save data
+pub fn save_world(world: &World) -> SavedWorld {
+    let entities = world.entities();
+    let positions = world.read_storage::<PositionComponent>();
+    let visuals = world.read_storage::<VisualComponent>();
+    let animations = world.read_storage::<AnimationComponent>();
+    let player_controls = world.read_storage::<PlayerControlComponent>();
+    let movements = world.read_storage::<MovementComponent>();
+    let colliders = world.read_storage::<ColliderComponent>();
+    let interactions = world.read_storage::<InteractionComponent>();
+    let characters = world.read_storage::<CharacterComponent>();
+    let levels = world.read_storage::<LevelComponent>();
+    let base_stats = world.read_storage::<BaseStatsComponent>();
+    let vitals = world.read_storage::<VitalsComponent>();
+    let equipment = world.read_storage::<EquipmentComponent>();
+    let names = world.read_storage::<NameComponent>();
+    let triggers = world.read_storage::<TriggerComponent>();
+    let warps = world.read_storage::<WarpComponent>();
+
+    let camera = world.read_resource::<Camera>();
+
+    //party members are saved by their place in the entity list
+    let entity_order: Vec<_> = entities.join().collect();
+    let party = world.read_resource::<Party>();
+    let places = |members: &Vec<_>| -> Vec<usize> {
+        members.iter()
+               .filter_map(|member| entity_order.iter().position(|entity| entity == member))
+               .collect()
+    };
+
+    let saved_entities = entity_order.iter().map(|&entity| {
+        SavedEntity {
+            position: positions.get(entity).map(|position| (position.map_pos.x, position.map_pos.y)),
+            visual: visuals.get(entity).map(|visual| SavedVisual {
+                sprite_sheet: visual.sprite_sheet_name.clone(),
+                sprite: visual.sprite_location,
+            }),
+            animation: animations.get(entity).map(|animation| SavedAnimation {
+                name: animation.name.clone(),
+                index: animation.index,
+                frame_duration: animation.frame_duration,
+                timer: animation.timer,
+            }),
+            player_control_speed: player_controls.get(entity).map(|control| control.speed),
+            movement: movements.get(entity).is_some(),
+            collider: colliders.get(entity).map(|collider| SavedCollider {
+                offset: (collider.offset.x, collider.offset.y),
+                size: (collider.width, collider.height),
+            }),
+            interaction_dialogue: interactions.get(entity).map(|interaction| interaction.dialogue.clone()),
+            //the character components are only useful together
+            character: match (characters.get(entity), levels.get(entity), base_stats.get(entity), vitals.get(entity)) {
+                (Some(character), Some(level), Some(base_stats), Some(vitals)) => Some( SavedCharacter {
+                    name: character.name.clone(),
+                    class: character.class.clone(),
+                    level: level.level,
+                    experience: level.experience,
+                    stats: SavedStats {
+                        max_hp: base_stats.stats.max_hp,
+                        max_mp: base_stats.stats.max_mp,
+                        attack: base_stats.stats.attack,
+                        defense: base_stats.stats.defense,
+                        magic: base_stats.stats.magic,
+                        speed: base_stats.stats.speed,
+                    },
+                    hp: vitals.hp,
+                    mp: vitals.mp,
+                    equipment: equipment.get(entity).map(|worn| worn.equipped.clone()).unwrap_or_default(),
+                }),
+                _ => None,
+            },
+            name: names.get(entity).map(|name| name.name.clone()),
+            trigger: triggers.get(entity).map(|trigger| SavedTrigger {
+                on: trigger.condition.name().to_string(),
+                condition: match &trigger.condition {
+                    TriggerCondition::FlagChange(condition) => Some( condition.source().to_string() ),
+                    _ => None,
+                },
+                event: trigger.event.clone(),
+                size: trigger.size,
+                last_state: trigger.last_state,
+            }),
+            warp: warps.get(entity).map(|warp| SavedWarp {
+                map: warp.map.clone(),
+                arrive_at: warp.arrive_at.clone(),
+                size: warp.size,
+                last_state: warp.last_state,
+            }),
+            camera_target: camera.target == Some(entity),
+        }
+    }).collect();
+
+    let active_dialogue = world.read_resource::<ActiveDialogue>();
+    let event_queue = world.read_resource::<EventQueue>();
+
+    SavedWorld {
+        current_map: world.read_resource::<CurrentMap>().name.clone(),
+        camera_zoom: camera.zoom,
+        entities: saved_entities,
+        flags: world.read_resource::<GameFlags>().flags.clone(),
+        dialogue: active_dialogue.dialogue_name.clone()
+                                 .map(|dialogue_name| (dialogue_name, active_dialogue.node_id.clone())),
+        inventory: world.read_resource::<Inventory>().items.clone(),
+        party: places(&party.active),
+        reserve: places(&party.reserve),
+        //scripts pick up again at the start of the command they were on
+        events: event_queue.running.iter().map(|running| (running.name.clone(), running.step))
+                           .chain(event_queue.waiting.iter().map(|event_name| (event_name.clone(), 0)))
+                           .collect(),
+        quests: world.read_resource::<QuestLog>().quests.iter().map(|quest| SavedQuest {
+            name: quest.name.clone(),
+            stage: quest.stage,
+            progress: quest.progress.clone(),
+            completed: quest.completed,
+        }).collect(),
+        shop_sales: world.read_resource::<ShopSales>().sold.clone(),
+    }
+}
+
+
+//recreates saved entities in a freshly set up world. Everything the save refers to is checked
+//  against the loaded assets first so a broken save doesn't leave a half built world.
+pub fn restore_world(world: &mut World, saved: &SavedWorld) -> Result<(), SaveError> {
+    {
+        let mut asset_database = world.write_resource::<AssetDatabase>();
+
+        let map_names = saved.current_map.iter()
+                             .chain(saved.entities.iter().filter_map(|entity| entity.warp.as_ref().map(|warp| &warp.map)));
+
+        for map_name in map_names {
+            match asset_database.get_asset(map_name) {
+                AssetContainer::Tilemap(_) => {},
+                _ => return Err(SaveError::MissingAsset(map_name.clone())),
+            }
+        }
+
+        for visual in saved.entities.iter().filter_map(|entity| entity.visual.as_ref()) {
+            match asset_database.get_asset(&visual.sprite_sheet) {
+                AssetContainer::Spritesheet(_) => {},
+                _ => return Err(SaveError::MissingAsset(visual.sprite_sheet.clone())),
+            }
+        }
+
+        let dialogue_names = saved.entities.iter()
+                                  .filter_map(|entity| entity.interaction_dialogue.as_ref())
+                                  .chain(saved.dialogue.iter().map(|(dialogue_name, _)| dialogue_name));
+
+        for class_name in saved.entities.iter().filter_map(|entity| entity.character.as_ref().map(|character| &character.class)) {
+            match asset_database.get_asset(class_name) {
+                AssetContainer::CharacterClass(_) => {},
+                _ => return Err(SaveError::MissingAsset(class_name.clone())),
+            }
+        }
+
+        for dialogue_name in dialogue_names {
+            match asset_database.get_asset(dialogue_name) {
+                AssetContainer::Dialogue(_) => {},
+                _ => return Err(SaveError::MissingAsset(dialogue_name.clone())),
+            }
+        }
+
+        let worn_items = saved.entities.iter()
+                              .filter_map(|entity| entity.character.as_ref())
+                              .flat_map(|character| character.equipment.values());
+
+        let event_names = saved.entities.iter()
+                               .filter_map(|entity| entity.trigger.as_ref().map(|trigger| &trigger.event))
+                               .chain(saved.events.iter().map(|(event_name, _)| event_name));
+
+        for event_name in event_names {
+            match asset_database.get_asset(event_name) {
+                AssetContainer::EventScript(_) | AssetContainer::Script(_) => {},
+                _ => return Err(SaveError::MissingAsset(event_name.clone())),
+            }
+        }
+
+        for quest in saved.quests.iter() {
+            match asset_database.get_asset(&quest.name) {
+                AssetContainer::Quest(_) => {},
+                _ => return Err(SaveError::MissingAsset(quest.name.clone())),
+            }
+        }
+
+        for shop_name in saved.shop_sales.keys() {
+            match asset_database.get_asset(shop_name) {
+                AssetContainer::Shop(_) => {},
+                _ => return Err(SaveError::MissingAsset(shop_name.clone())),
+            }
+        }
+
+        for item_name in saved.inventory.iter().map(|(item_name, _)| item_name).chain(worn_items) {
+            match asset_database.get_asset(item_name) {
+                AssetContainer::Item(_) => {},
+                _ => return Err(SaveError::MissingAsset(item_name.clone())),
+            }
+        }
+    }
+
+    world.write_resource::<CurrentMap>().name = saved.current_map.clone();
+    world.write_resource::<GameFlags>().flags = saved.flags.clone();
+    world.write_resource::<Inventory>().items = saved.inventory.clone();
+    world.write_resource::<ShopSales>().sold = saved.shop_sales.clone();
+    world.write_resource::<QuestLog>().quests = saved.quests.iter().map(|quest| QuestProgress {
+        name: quest.name.clone(),
+        stage: quest.stage,
+        progress: quest.progress.clone(),
+        completed: quest.completed,
+    }).collect();
+
+    if let Some((dialogue_name, node_id)) = &saved.dialogue {
+        let mut active_dialogue = world.write_resource::<ActiveDialogue>();
+        active_dialogue.dialogue_name = Some(dialogue_name.clone());
+        active_dialogue.node_id = node_id.clone();
+    }
+    world.write_resource::<Camera>().set_zoom(saved.camera_zoom);
+
+    {
+        let mut event_queue = world.write_resource::<EventQueue>();
+        let mut events = saved.events.iter();
+        event_queue.running = events.next().map(|(event_name, step)| RunningEvent::new(event_name.clone(), *step));
+        event_queue.waiting = events.map(|(event_name, _)| event_name.clone()).collect();
+    }
+
+    let mut restored = vec![];
+
+    for saved_entity in saved.entities.iter() {
+        let mut builder = world.create_entity();
+
+        if let Some((x, y)) = saved_entity.position {
+            builder = builder.with(PositionComponent { map_pos: Point::new(x, y) });
+        }
+
+        if let Some(visual) = &saved_entity.visual {
+            builder = builder.with(VisualComponent {
+                sprite_sheet_name: visual.sprite_sheet.clone(),
+                sprite_location: visual.sprite,
+            });
+        }
+
+        if let Some(animation) = &saved_entity.animation {
+            builder = builder.with(AnimationComponent {
+                name: animation.name.clone(),
+                index: animation.index,
+                frame_duration: animation.frame_duration,
+                timer: animation.timer,
+            });
+        }
+
+        if let Some(speed) = saved_entity.player_control_speed {
+            builder = builder.with(PlayerControlComponent { speed });
+        }
+
+        if saved_entity.movement {
+            builder = builder.with(MovementComponent::new());
+        }
+
+        if let Some(collider) = &saved_entity.collider {
+            builder = builder.with(ColliderComponent {
+                offset: Vector::new(collider.offset.0, collider.offset.1),
+                width: collider.size.0,
+                height: collider.size.1,
+            });
+        }
+
+        if let Some(dialogue) = &saved_entity.interaction_dialogue {
+            builder = builder.with(InteractionComponent { dialogue: dialogue.clone() });
+        }
+
+        if let Some(character) = &saved_entity.character {
+            let stats = &character.stats;
+            builder = builder
+                .with(CharacterComponent { name: character.name.clone(), class: character.class.clone() })
+                .with(LevelComponent { level: character.level, experience: character.experience })
+                .with(BaseStatsComponent { stats: Stats {
+                    max_hp: stats.max_hp,
+                    max_mp: stats.max_mp,
+                    attack: stats.attack,
+                    defense: stats.defense,
+                    magic: stats.magic,
+                    speed: stats.speed,
+                }})
+                .with(VitalsComponent { hp: character.hp, mp: character.mp })
+                .with(EquipmentComponent { equipped: character.equipment.clone() });
+        }
+
+        if let Some(name) = &saved_entity.name {
+            builder = builder.with(NameComponent { name: name.clone() });
+        }
+
+        if let Some(trigger) = &saved_entity.trigger {
+            let condition = TriggerCondition::parse(&trigger.on, trigger.condition.as_deref());
+
+            match condition {
+                Ok(condition) => builder = builder.with(TriggerComponent {
+                    condition,
+                    event: trigger.event.clone(),
+                    size: trigger.size,
+                    last_state: trigger.last_state,
+                }),
+                Err(err) => warn!("[Saving] A saved trigger could not be restored. {}.", err),
+            }
+        }
+
+        if let Some(warp) = &saved_entity.warp {
+            builder = builder.with(WarpComponent {
+                map: warp.map.clone(),
+                arrive_at: warp.arrive_at.clone(),
+                size: warp.size,
+                last_state: warp.last_state,
+            });
+        }
+
+        let entity = builder.build();
+        restored.push(entity);
+
+        if saved_entity.camera_target {
+            world.write_resource::<Camera>().target = Some(entity);
+        }
+    }
+
+    {
+        let mut party = world.write_resource::<Party>();
+        party.active = saved.party.iter().filter_map(|place| restored.get(*place).copied()).collect();
+        party.reserve = saved.reserve.iter().filter_map(|place| restored.get(*place).copied()).collect();
+    }
+
+    //saves from before the party was recorded only had player controlled characters
+    gather_party(world);
+
+    //only the current map's entities are ever saved, everyone outside the party belongs to it
+    if let Some(map_name) = &saved.current_map {
+        let party = world.read_resource::<Party>();
+        let mut maps = world.write_storage::<MapComponent>();
+        for entity in restored.into_iter().filter(|entity| !party.is_member(*entity)) {
+            let _ = maps.insert(entity, MapComponent { map: map_name.clone() });
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use specs::{World, WorldExt, Entity, Join};
+
+use coffee::graphics::Point;
+
+use super::{PositionComponent, MapComponent, CurrentMap, Party, Camera, GameClock, SceneManager, MapChange};
+use super::spawning::spawn_map_entities;
+
+use crate::assets::{AssetDatabase, AssetContainer, MapArrival};
+
+
+//moves the scene manager's transition along and swaps the maps over once the screen has faded out
+pub fn update_scene(world: &mut World) {
+    let seconds = world.read_resource::<GameClock>().delta_seconds;
+    let change = world.write_resource::<SceneManager>().advance(seconds);
+
+    if let Some(change) = change {
+        change_map(world, &change);
+    }
+}
+
+
+//moves the party to another map. Entities that belong to the old map are removed and the new
+//  map's entities are spawned, anything that belongs to no map like the party stays. The party
+//  arrives where the change asks, or at the new map's player spawn, or otherwise stays put.
+pub fn change_map(world: &mut World, change: &MapChange) {
+    let arrival_object = match world.write_resource::<AssetDatabase>().get_asset(&change.map) {
+        AssetContainer::Tilemap(tilemap) => match &change.arrival {
+            MapArrival::Object(object_name) => {
+                let object = tilemap.objects.iter().find(|object| object.name == *object_name);
+                if object.is_none() {
+                    warn!("[Scene] Map {} has no object named {} to arrive at.", change.map, object_name);
+                }
+                object.map(|object| Point::new(object.area.x, object.area.y))
+            },
+            _ => None,
+        },
+        _ => {
+            warn!("[Scene] {} {} {}.",
+                  "Requested tilemap",
+                  change.map,
+                  "does not exist",
+            );
+            return;
+        }
+    };
+
+    let old_map = world.write_resource::<CurrentMap>().name.replace(change.map.clone());
+    if let Some(old_map) = old_map {
+        unload_map(world, &old_map);
+    }
+
+    let map_arrival = spawn_map_entities(world);
+    let arrival = match &change.arrival {
+        MapArrival::Position(position) => Some(*position),
+        _ => arrival_object.or(map_arrival),
+    };
+
+    let mut party = world.write_resource::<Party>();
+    if let Some(arrival) = arrival {
+        let mut positions = world.write_storage::<PositionComponent>();
+        for member in party.active.iter() {
+            let _ = positions.insert(*member, PositionComponent { map_pos: arrival });
+        }
+    }
+    party.clear_trail();
+
+    //anything else the camera followed was left behind on the old map
+    let mut camera = world.write_resource::<Camera>();
+    if camera.target.map(|target| !party.is_member(target)).unwrap_or(false) {
+        camera.target = None;
+    }
+    camera.snap();
+}
+
+
+//removes every entity that belongs to the given map. Party members never belong to a map but
+//  are checked anyway so a stray tag can't take someone out of the party.
+fn unload_map(world: &mut World, map_name: &str) {
+    let leaving: Vec<Entity> = {
+        let party = world.read_resource::<Party>();
+        let maps = world.read_storage::<MapComponent>();
+        (&world.entities(), &maps).join()
+            .filter(|(entity, map)| map.map == map_name && !party.is_member(*entity))
+            .map(|(entity, _)| entity)
+            .collect()
+    };
+
+    if let Err(err) = world.delete_entities(&leaving) {
+        warn!("[Scene] Could not clear map {}. {}", map_name, err);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specs::Builder;
+    use coffee::graphics::Rectangle;
+    use std::collections::HashMap;
+    use super::super::{WarpComponent, NameComponent, register_components};
+    use crate::assets::{Tilemap, MapObject};
+
+    fn map_object(name: &str, kind: &str, x: f32, y: f32, properties: &[(&str, &str)]) -> MapObject {
+        MapObject {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            area: Rectangle { x, y, width: 32.0, height: 32.0 },
+            properties: properties.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect::<HashMap<_, _>>(),
+        }
+    }
+
+    //a town with a door into a house, the party standing in the town
+    fn world_in_town() -> (World, Entity) {
+        let mut town = Tilemap::new(32.0, 32.0);
+        town.objects.push( map_object("house door", "warp", 100.0, 100.0, &[("map", "house"), ("arrive at", "entrance")]) );
+        let mut house = Tilemap::new(32.0, 32.0);
+        house.objects.push( map_object("entrance", "marker", 50.0, 60.0, &[]) );
+        house.objects.push( map_object("way out", "warp", 50.0, 100.0, &[("map", "town")]) );
+
+        let mut asset_database = AssetDatabase::new();
+        asset_database.add_asset("town".to_string(), AssetContainer::Tilemap(town));
+        asset_database.add_asset("house".to_string(), AssetContainer::Tilemap(house));
+
+        let mut world = World::new();
+        register_components(&mut world);
+        world.insert(asset_database);
+        world.insert(CurrentMap { name: Some("town".to_string()) });
+        world.insert(Party::new());
+        world.insert(Camera::new());
+
+        spawn_map_entities(&mut world);
+
+        let leader = world.create_entity().with(PositionComponent { map_pos: Point::new(0.0, 0.0) }).build();
+        world.write_resource::<Party>().active.push(leader);
+
+        (world, leader)
+    }
+
+    fn names_on(world: &World, map_name: &str) -> Vec<String> {
+        let maps = world.read_storage::<MapComponent>();
+        let names = world.read_storage::<NameComponent>();
+        (&maps, &names).join().filter(|(map, _)| map.map == map_name).map(|(_, name)| name.name.clone()).collect()
+    }
+
+    #[test]
+    fn map_entities_belong_to_their_map() {
+        let (world, _) = world_in_town();
+
+        assert_eq!(names_on(&world, "town"), vec!["house door".to_string()]);
+        let warps = world.read_storage::<WarpComponent>();
+        let warp = warps.join().next().unwrap();
+        assert_eq!((warp.map.as_str(), warp.arrive_at.as_deref()), ("house", Some("entrance")));
+    }
+
+    #[test]
+    fn changing_maps_keeps_the_party_and_unloads_the_old_map() {
+        let (mut world, leader) = world_in_town();
+        let bystander = world.create_entity().build();
+
+        change_map(&mut world, &MapChange { map: "house".to_string(), arrival: MapArrival::Object("entrance".to_string()) });
+
+        assert_eq!(world.read_resource::<CurrentMap>().name.as_deref(), Some("house"));
+        assert!(names_on(&world, "town").is_empty());
+        assert_eq!(names_on(&world, "house"), vec!["way out".to_string()]);
+
+        assert!(world.is_alive(leader));
+        assert!(world.is_alive(bystander));
+        assert_eq!(world.read_storage::<PositionComponent>().get(leader).unwrap().map_pos, Point::new(50.0, 60.0));
+    }
+
+    #[test]
+    fn changing_to_a_missing_map_stays_put() {
+        let (mut world, _) = world_in_town();
+
+        change_map(&mut world, &MapChange { map: "cave".to_string(), arrival: MapArrival::MapSpawn });
+
+        assert_eq!(world.read_resource::<CurrentMap>().name.as_deref(), Some("town"));
+        assert_eq!(names_on(&world, "town"), vec!["house door".to_string()]);
+    }
+}
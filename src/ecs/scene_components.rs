@@ -0,0 +1,46 @@
+use specs::{Component, VecStorage};
+
+use coffee::graphics::{Point, Rectangle};
+
+
+//For entities that belong to a single map, like everything spawned from a map's objects. They
+//  are removed when the party leaves the map. Entities without one, like the party, stay.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct MapComponent {
+    pub map: String, //name of the tilemap asset
+}
+
+
+//For doors and map edges that move the party to another map when the leader walks in. The
+//  warp covers 'size' pixels from the entity's position.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct WarpComponent {
+    pub map: String, //name of the tilemap asset to go to
+    pub arrive_at: Option<String>, //object on the new map the party arrives at, the map's player spawn when None
+    pub size: (f32, f32),
+    pub last_state: Option<bool>, //whether the leader was inside last update, None before the first check
+}
+
+impl WarpComponent {
+
+    pub fn new(map: String, arrive_at: Option<String>, size: (f32, f32)) -> WarpComponent {
+        WarpComponent {
+            map,
+            arrive_at,
+            size,
+            last_state: None,
+        }
+    }
+
+    //area covered when the entity is at the given position
+    pub fn area_at(&self, position: Point) -> Rectangle<f32> {
+        Rectangle {
+            x: position.x,
+            y: position.y,
+            width: self.size.0,
+            height: self.size.1,
+        }
+    }
+}
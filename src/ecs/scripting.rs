@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use coffee::graphics::Point;
+
+use rhai::{Engine, Dynamic, Array, EvalAltResult, INT, FLOAT};
+
+use crate::assets::{Script, EventCommand, MapArrival, Variable, sandboxed_engine, resolve_relative_path};
+
+
+//pixels per second scripts move things at when they don't say
+const DEFAULT_MOVE_SPEED: f32 = 60.0;
+
+
+//What a script can see of the world while it runs.
+pub struct ScriptInput {
+    pub flags: HashMap<String, Variable>,
+    pub entities: Vec<(String, Point)>, //named entities and where they are, the party leader is "leader"
+    pub items: Vec<(String, u32)>, //what the party carries
+}
+
+
+//What a script asked for once it finished. Commands are run by the event system as if they
+//  were part of the event the script was started from.
+pub struct ScriptOutput {
+    pub flag_changes: Vec<(String, Variable)>, //in the order the script made them
+    pub commands: Vec<EventCommand>,
+    pub spawns: Vec<ScriptSpawn>,
+    pub despawns: Vec<String>, //names of entities to remove
+}
+
+//a prefab a script wants on the map
+pub struct ScriptSpawn {
+    pub prefab: String,
+    pub name: Option<String>,
+    pub position: Point,
+}
+
+
+//everything a running script reads and writes. Scripts never touch the world themselves.
+#[derive(Default)]
+struct ScriptContext {
+    file: PathBuf,
+    flags: HashMap<String, Variable>,
+    flag_changes: Vec<(String, Variable)>,
+    entities: Vec<(String, Point)>,
+    items: Vec<(String, u32)>,
+    commands: Vec<EventCommand>,
+    spawns: Vec<ScriptSpawn>,
+    despawns: Vec<String>,
+}
+
+
+//Runs script assets with the game's API registered. Only one script runs at a time.
+pub struct ScriptRunner {
+    engine: Engine,
+    context: Arc<Mutex<ScriptContext>>,
+}
+
+impl ScriptRunner {
+
+    pub fn new() -> ScriptRunner {
+        let context = Arc::new(Mutex::new(ScriptContext::default()));
+
+        let mut engine = sandboxed_engine();
+        register_api(&mut engine, &context);
+
+        ScriptRunner {
+            engine,
+            context,
+        }
+    }
+
+    //runs a script to the end. A script that fails part way changes nothing.
+    // returns: what the script asked for, or the error with the file and line it happened on
+    pub fn run(&self, script: &Script, input: ScriptInput) -> Result<ScriptOutput, String> {
+        *lock(&self.context) = ScriptContext {
+            file: script.file.clone(),
+            flags: input.flags,
+            entities: input.entities,
+            items: input.items,
+            ..ScriptContext::default()
+        };
+
+        let result = self.engine.run_ast(&script.ast);
+        let context = std::mem::take(&mut *lock(&self.context));
+
+        match result {
+            Ok(()) => Ok( ScriptOutput {
+                flag_changes: context.flag_changes,
+                commands: context.commands,
+                spawns: context.spawns,
+                despawns: context.despawns,
+            }),
+            Err(err) => Err( format!("{}: {}", script.file.display(), err) ),
+        }
+    }
+}
+
+
+//a script that panicked part way can't leave the context in a state worth refusing
+fn lock(context: &Arc<Mutex<ScriptContext>>) -> MutexGuard<'_, ScriptContext> {
+    context.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+//scripts can write positions and times as whole numbers or decimals
+fn number(value: &Dynamic) -> Result<f32, Box<EvalAltResult>> {
+    value.as_float().map(|value| value as f32)
+         .or_else(|_| value.as_int().map(|value| value as f32))
+         .map_err(|_| format!("Expected a number but got {}", value.type_name()).into())
+}
+
+
+//functions scripts can call
+fn register_api(engine: &mut Engine, context: &Arc<Mutex<ScriptContext>>) {
+
+    //flags keep the type they were given, flags that were never set are false
+    let ctx = context.clone();
+    engine.register_fn("flag", move |flag: &str| -> Dynamic {
+        match lock(&ctx).flags.get(flag) {
+            Some(Variable::Bool(value)) => Dynamic::from(*value),
+            Some(Variable::Int(value)) => Dynamic::from(*value as INT),
+            Some(Variable::Text(value)) => Dynamic::from(value.clone()),
+            None => Dynamic::from(false),
+        }
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("set_flag", move |flag: &str, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let value = if let Ok(value) = value.as_bool() {
+            Variable::Bool(value)
+        } else if let Ok(value) = value.as_int() {
+            Variable::Int(value)
+        } else if value.is_string() {
+            Variable::Text(value.to_string())
+        } else {
+            return Err( format!("Flags hold true or false, whole numbers, or text, not {}", value.type_name()).into() );
+        };
+
+        let mut context = lock(&ctx);
+        context.flags.insert(flag.to_string(), value.clone());
+        context.flag_changes.push( (flag.to_string(), value) );
+        Ok(())
+    });
+
+
+    //entities on the map
+    let ctx = context.clone();
+    engine.register_fn("entities", move || -> Array {
+        lock(&ctx).entities.iter().map(|(name, _)| Dynamic::from(name.clone())).collect()
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("position", move |name: &str| -> Dynamic {
+        match lock(&ctx).entities.iter().find(|(entity, _)| entity == name) {
+            Some((_, position)) => Dynamic::from_array( vec![Dynamic::from(position.x as FLOAT),
+                                                             Dynamic::from(position.y as FLOAT)] ),
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("spawn_entity", move |prefab: &str, x: Dynamic, y: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let position = Point::new(number(&x)?, number(&y)?);
+        lock(&ctx).spawns.push( ScriptSpawn { prefab: prefab.to_string(), name: None, position } );
+        Ok(())
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("spawn_entity", move |prefab: &str, name: &str, x: Dynamic, y: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let position = Point::new(number(&x)?, number(&y)?);
+        lock(&ctx).spawns.push( ScriptSpawn { prefab: prefab.to_string(), name: Some(name.to_string()), position } );
+        Ok(())
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("despawn_entity", move |name: &str| lock(&ctx).despawns.push(name.to_string()));
+
+    let ctx = context.clone();
+    engine.register_fn("move_entity", move |name: &str, x: Dynamic, y: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let to = Point::new(number(&x)?, number(&y)?);
+        lock(&ctx).commands.push( EventCommand::MoveEntity { entity: name.to_string(), to, speed: DEFAULT_MOVE_SPEED } );
+        Ok(())
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("move_entity", move |name: &str, x: Dynamic, y: Dynamic, speed: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let to = Point::new(number(&x)?, number(&y)?);
+        let speed = number(&speed)?.max(1.0);
+        lock(&ctx).commands.push( EventCommand::MoveEntity { entity: name.to_string(), to, speed } );
+        Ok(())
+    });
+
+
+    //items
+    let ctx = context.clone();
+    engine.register_fn("item_count", move |item: &str| -> INT {
+        lock(&ctx).items.iter().find(|(name, _)| name == item).map(|(_, count)| INT::from(*count)).unwrap_or(0)
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("give_item", move |item: &str, count: INT| {
+        let count = count.clamp(0, INT::from(u32::MAX)) as u32;
+        lock(&ctx).commands.push( EventCommand::GiveItem { item: item.to_string(), count } );
+    });
+
+
+    //things that take time run after the script, in the order they were asked for
+    let ctx = context.clone();
+    engine.register_fn("show_dialogue", move |dialogue: &str| {
+        lock(&ctx).commands.push( EventCommand::ShowDialogue(dialogue.to_string()) );
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("start_battle", move |encounter: &str| {
+        lock(&ctx).commands.push( EventCommand::StartBattle(encounter.to_string()) );
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("start_quest", move |quest: &str| {
+        lock(&ctx).commands.push( EventCommand::StartQuest(quest.to_string()) );
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("open_shop", move |shop: &str| {
+        lock(&ctx).commands.push( EventCommand::OpenShop(shop.to_string()) );
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("wait", move |seconds: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let seconds = number(&seconds)?.max(0.0);
+        lock(&ctx).commands.push( EventCommand::Wait(seconds) );
+        Ok(())
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("change_map", move |map: &str| {
+        lock(&ctx).commands.push( EventCommand::ChangeMap { map: map.to_string(), arrival: MapArrival::MapSpawn } );
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("change_map", move |map: &str, arrive_at: &str| {
+        let arrival = MapArrival::Object(arrive_at.to_string());
+        lock(&ctx).commands.push( EventCommand::ChangeMap { map: map.to_string(), arrival } );
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("change_map", move |map: &str, x: Dynamic, y: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let arrival = MapArrival::Position( Point::new(number(&x)?, number(&y)?) );
+        lock(&ctx).commands.push( EventCommand::ChangeMap { map: map.to_string(), arrival } );
+        Ok(())
+    });
+
+    //sound files are relative to the script like they are to yaml files. Naming an entity plays
+    //  the sound from where it stands.
+    let ctx = context.clone();
+    engine.register_fn("play_sound", move |file: &str| play_sound(&ctx, file, None));
+
+    let ctx = context.clone();
+    engine.register_fn("play_sound", move |file: &str, at: &str| play_sound(&ctx, file, Some(at.to_string())));
+}
+
+fn play_sound(context: &Arc<Mutex<ScriptContext>>, file: &str, at: Option<String>) -> Result<(), Box<EvalAltResult>> {
+    let mut context = lock(context);
+    let clip = resolve_relative_path(&context.file, file)
+                   .and_then(|path| path.into_os_string().into_string().ok())
+                   .ok_or_else(|| format!("Could not resolve sound path {}", file))?;
+    context.commands.push( EventCommand::PlaySound { clip, at } );
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(source: &str) -> Script {
+        let mut ast = sandboxed_engine().compile(source).expect("test script should compile");
+        ast.set_source("scripts/test.rhai");
+        Script { file: PathBuf::from("scripts/test.rhai"), ast }
+    }
+
+    fn input() -> ScriptInput {
+        ScriptInput {
+            flags: vec![("gold".to_string(), Variable::Int(40))].into_iter().collect(),
+            entities: vec![("leader".to_string(), Point::new(10.0, 20.0))],
+            items: vec![("potion".to_string(), 3)],
+        }
+    }
+
+    #[test]
+    fn scripts_read_the_world_and_queue_commands() {
+        let runner = ScriptRunner::new();
+        let output = runner.run(&script(r#"
+            let at = position("leader");
+            if item_count("potion") == 3 && at[0] == 10.0 {
+                set_flag("checked", true);
+            }
+            show_dialogue("hello");
+            move_entity("leader", 5, 6.5);
+            spawn_entity("villager", "keeper", 1, 2);
+            despawn_entity("villager");
+        "#), input()).unwrap();
+
+        assert_eq!(output.flag_changes, vec![("checked".to_string(), Variable::Bool(true))]);
+        assert_eq!(output.commands.len(), 2);
+        assert!(matches!(&output.commands[0], EventCommand::ShowDialogue(name) if name == "hello"));
+        assert!(matches!(&output.commands[1], EventCommand::MoveEntity { to, speed, .. }
+                         if *to == Point::new(5.0, 6.5) && *speed == DEFAULT_MOVE_SPEED));
+        assert_eq!(output.spawns[0].name.as_deref(), Some("keeper"));
+        assert_eq!(output.despawns, vec!["villager".to_string()]);
+    }
+
+    #[test]
+    fn scripts_can_return_early() {
+        let runner = ScriptRunner::new();
+        let output = runner.run(&script(r#"
+            set_flag("before", true);
+            return;
+            set_flag("after", true);
+        "#), input()).unwrap();
+
+        assert_eq!(output.flag_changes, vec![("before".to_string(), Variable::Bool(true))]);
+    }
+
+    #[test]
+    fn flags_keep_their_type() {
+        let runner = ScriptRunner::new();
+        let output = runner.run(&script(r#"
+            set_flag("gold", flag("gold") + 10);
+            set_flag("hero", "Sara");
+            if flag("gold") == 50 && !flag("never set") { set_flag("rich", true); }
+        "#), input()).unwrap();
+
+        assert_eq!(output.flag_changes, vec![("gold".to_string(), Variable::Int(50)),
+                                             ("hero".to_string(), Variable::Text("Sara".to_string())),
+                                             ("rich".to_string(), Variable::Bool(true))]);
+        assert!(runner.run(&script("set_flag(\"list\", [1, 2]);"), input()).is_err());
+    }
+
+    #[test]
+    fn errors_name_the_file_and_line() {
+        let runner = ScriptRunner::new();
+        let err = runner.run(&script("set_flag(\"a\", true);\nwait(\"soon\");"), input()).err().unwrap();
+
+        assert!(err.starts_with("scripts/test.rhai"), "{}", err);
+        assert!(err.contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped() {
+        let runner = ScriptRunner::new();
+        assert!(runner.run(&script("loop { }"), input()).is_err());
+    }
+
+    #[test]
+    fn scripts_cannot_load_modules() {
+        assert!(ScriptRunner::new().run(&script("import \"other\" as other;"), input()).is_err());
+    }
+}
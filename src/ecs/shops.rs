@@ -0,0 +1,287 @@
+use specs::{World, WorldExt};
+
+use coffee::graphics::{Image, Rectangle};
+
+use super::{Inventory, GameFlags, ShopSales, Party, CharacterComponent, EquipmentComponent};
+use super::inventory::{find_item, item_icon};
+
+use crate::assets::{AssetDatabase, AssetContainer, Item, ItemCategory, Shop, StatModifiers};
+
+
+//An item as shown in a shop, either one the shop sells or one the party could sell.
+pub struct ShopEntry {
+    pub name: String,
+    pub description: String,
+    pub icon: Option<(Image, Rectangle<u16>)>,
+    pub price: u32, //gold for one
+    pub available: Option<u32>, //how many the shop or party has to trade, None when the shop never runs out
+    pub carried: u32, //how many the party already has
+    pub comparison: Vec<String>, //for equipment, how it compares to what each party member wears
+}
+
+
+//What the shop screen shows.
+pub struct ShopView {
+    pub title: String,
+    pub gold: u32,
+    pub buying: Vec<ShopEntry>, //what the shop sells
+    pub selling: Vec<ShopEntry>, //what the shop will buy from the party
+}
+
+
+//looks up a shop asset, logging when it is missing
+fn find_shop(asset_database: &mut AssetDatabase, shop_name: &str) -> Option<Shop> {
+    match asset_database.get_asset(&shop_name.to_string()) {
+        AssetContainer::Shop(shop) => Some(shop.clone()),
+        _ => {
+            warn!("[Shops] {} {} {}.",
+                  "Requested shop",
+                  shop_name,
+                  "does not exist",
+            );
+            None
+        }
+    }
+}
+
+
+//key items belong to the story and items worth nothing aren't wanted
+fn can_sell(item: &Item) -> bool {
+    item.category != ItemCategory::KeyItem && item.price > 0
+}
+
+
+//buys items from a shop with the party's gold
+// returns: what happened or why nothing was bought
+pub fn buy_item(world: &World, shop_name: &str, item_name: &str, count: u32) -> Result<String, String> {
+    if count == 0 { return Err( "Nothing was bought.".to_string() ); }
+
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let shop = find_shop(&mut asset_database, shop_name).ok_or_else(|| format!("{} is closed.", shop_name))?;
+    let stock = shop.stock_of(item_name).ok_or_else(|| format!("{} isn't sold here.", item_name))?;
+    let item = find_item(&mut asset_database, item_name).ok_or_else(|| format!("{} isn't sold here.", item_name))?;
+    let price = shop.buy_price(item.price);
+
+    let mut sales = world.write_resource::<ShopSales>();
+    if let Some(limit) = stock.limit {
+        let left = limit.saturating_sub(sales.sold(shop_name, item_name));
+        if left < count {
+            return Err( match left {
+                0 => format!("{} is sold out.", item_name),
+                _ => format!("There are only {} {} left.", left, item_name),
+            });
+        }
+    }
+
+    let mut inventory = world.write_resource::<Inventory>();
+    if item.stack_limit.saturating_sub(inventory.count(item_name)) < count {
+        return Err( format!("The party can't carry that many {}.", item_name) );
+    }
+
+    let mut flags = world.write_resource::<GameFlags>();
+    let cost = price.saturating_mul(count);
+    if flags.gold() < cost {
+        return Err( format!("That costs {} gold but the party only has {}.", cost, flags.gold()) );
+    }
+
+    let gold = flags.gold() - cost;
+    flags.set_gold(gold);
+    inventory.add(item_name, count, item.stack_limit);
+    sales.record(shop_name, item_name, count);
+
+    Ok( format!("Bought {} {} for {} gold.", count, item_name, cost) )
+}
+
+
+//sells items from the party's inventory to a shop
+// returns: what happened or why nothing was sold
+pub fn sell_item(world: &World, shop_name: &str, item_name: &str, count: u32) -> Result<String, String> {
+    if count == 0 { return Err( "Nothing was sold.".to_string() ); }
+
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let item = find_item(&mut asset_database, item_name).ok_or_else(|| format!("{} can't be sold.", item_name))?;
+    if !can_sell(&item) {
+        return Err( format!("{} can't be sold.", item_name) );
+    }
+
+    let price = find_shop(&mut asset_database, shop_name).map(|shop| shop.sell_price(item.price))
+        .ok_or_else(|| format!("{} is closed.", shop_name))?;
+
+    if !world.write_resource::<Inventory>().remove(item_name, count) {
+        return Err( format!("The party doesn't have {} {}.", count, item_name) );
+    }
+
+    let mut flags = world.write_resource::<GameFlags>();
+    let earned = price.saturating_mul(count);
+    let gold = flags.gold().saturating_add(earned);
+    flags.set_gold(gold);
+
+    Ok( format!("Sold {} {} for {} gold.", count, item_name, earned) )
+}
+
+
+//what the shop screen shows for a shop
+pub fn shop_view(world: &World, shop_name: &str) -> Option<ShopView> {
+    let mut asset_database = world.write_resource::<AssetDatabase>();
+    let shop = find_shop(&mut asset_database, shop_name)?;
+
+    let inventory = world.read_resource::<Inventory>();
+    let sales = world.read_resource::<ShopSales>();
+
+    let buying = shop.stock.iter().filter_map(|stock| {
+        let item = find_item(&mut asset_database, &stock.item)?;
+        Some( ShopEntry {
+            icon: item_icon(&mut asset_database, &item),
+            price: shop.buy_price(item.price),
+            available: stock.limit.map(|limit| limit.saturating_sub(sales.sold(shop_name, &stock.item))),
+            carried: inventory.count(&stock.item),
+            comparison: compare_equipment(world, &mut asset_database, &item),
+            name: item.name,
+            description: item.description,
+        })
+    }).collect();
+
+    let selling = inventory.items.iter().filter_map(|(item_name, count)| {
+        let item = match asset_database.get_asset(item_name) {
+            AssetContainer::Item(item) if can_sell(item) => item.clone(),
+            _ => return None,
+        };
+
+        Some( ShopEntry {
+            icon: item_icon(&mut asset_database, &item),
+            price: shop.sell_price(item.price),
+            available: Some(*count),
+            carried: *count,
+            comparison: compare_equipment(world, &mut asset_database, &item),
+            name: item.name,
+            description: item.description,
+        })
+    }).collect();
+
+    Some( ShopView {
+        title: shop.title,
+        gold: world.read_resource::<GameFlags>().gold(),
+        buying,
+        selling,
+    })
+}
+
+
+//for each party member, how a piece of equipment compares to what they wear in its slot
+fn compare_equipment(world: &World, asset_database: &mut AssetDatabase, item: &Item) -> Vec<String> {
+    let equipment = match &item.equipment {
+        Some(equipment) => equipment,
+        None => return vec![],
+    };
+
+    let characters = world.read_storage::<CharacterComponent>();
+    let worn = world.read_storage::<EquipmentComponent>();
+
+    world.read_resource::<Party>().members().into_iter().filter_map(|member| {
+        let character = characters.get(member)?;
+        if !equipment.allows_class(&character.class) {
+            return Some( format!("{}: can't wear", character.name) );
+        }
+
+        let current = worn.get(member)
+            .and_then(|worn| worn.equipped.get(&equipment.slot))
+            .and_then(|worn_name| match asset_database.get_asset(worn_name) {
+                AssetContainer::Item(worn_item) => worn_item.equipment.as_ref().map(|worn| worn.modifiers),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let difference = equipment.modifiers.compared_to(&current);
+        Some( match difference == StatModifiers::default() {
+            true => format!("{}: no change", character.name),
+            false => format!("{}: {}", character.name, difference.describe()),
+        })
+    }).collect()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::ShopStock;
+
+    fn item(name: &str, category: ItemCategory, price: u32) -> Item {
+        Item {
+            name: name.to_string(),
+            description: String::new(),
+            icon: None,
+            category,
+            stack_limit: 5,
+            price,
+            effect: None,
+            equipment: None,
+        }
+    }
+
+    fn world_with_shop(gold: u32) -> World {
+        let mut asset_database = AssetDatabase::new();
+        asset_database.add_asset("Potion".to_string(), AssetContainer::Item(item("Potion", ItemCategory::Consumable, 10)));
+        asset_database.add_asset("Old Key".to_string(), AssetContainer::Item(item("Old Key", ItemCategory::KeyItem, 50)));
+        asset_database.add_asset("village shop".to_string(), AssetContainer::Shop( Shop {
+            title: "Village Shop".to_string(),
+            stock: vec![ ShopStock { item: "Potion".to_string(), limit: Some(3) } ],
+            buy_multiplier: 2.0,
+            sell_multiplier: 0.5,
+        }));
+
+        let mut world = World::new();
+        world.insert(asset_database);
+        world.insert(Inventory::new());
+        world.insert(ShopSales::new());
+        world.insert(GameFlags::new());
+        world.write_resource::<GameFlags>().set_gold(gold);
+
+        world
+    }
+
+    fn gold(world: &World) -> u32 {
+        world.read_resource::<GameFlags>().gold()
+    }
+
+    #[test]
+    fn buying_costs_gold_and_stops_when_sold_out() {
+        let world = world_with_shop(100);
+
+        assert_eq!(buy_item(&world, "village shop", "Potion", 2).unwrap(), "Bought 2 Potion for 40 gold.");
+        assert_eq!(gold(&world), 60);
+        assert_eq!(world.read_resource::<Inventory>().count("Potion"), 2);
+
+        assert!(buy_item(&world, "village shop", "Potion", 2).is_err());
+        assert!(buy_item(&world, "village shop", "Potion", 1).is_ok());
+        assert!(buy_item(&world, "village shop", "Potion", 1).is_err());
+        assert_eq!(world.read_resource::<ShopSales>().sold("village shop", "Potion"), 3);
+        assert_eq!(gold(&world), 40);
+    }
+
+    #[test]
+    fn buying_needs_enough_gold_and_room() {
+        let world = world_with_shop(30);
+        assert!(buy_item(&world, "village shop", "Potion", 2).is_err());
+        assert!(buy_item(&world, "village shop", "Old Key", 1).is_err());
+        assert_eq!(gold(&world), 30);
+
+        world.write_resource::<Inventory>().add("Potion", 5, 5);
+        assert!(buy_item(&world, "village shop", "Potion", 1).is_err());
+        assert_eq!((gold(&world), world.read_resource::<ShopSales>().sold("village shop", "Potion")), (30, 0));
+    }
+
+    #[test]
+    fn selling_pays_the_shop_price_but_not_for_key_items() {
+        let world = world_with_shop(0);
+        world.write_resource::<Inventory>().add("Potion", 3, 5);
+        world.write_resource::<Inventory>().add("Old Key", 1, 5);
+
+        assert_eq!(sell_item(&world, "village shop", "Potion", 2).unwrap(), "Sold 2 Potion for 10 gold.");
+        assert!(sell_item(&world, "village shop", "Potion", 2).is_err());
+        assert!(sell_item(&world, "village shop", "Old Key", 1).is_err());
+
+        assert_eq!(gold(&world), 10);
+        assert_eq!(world.read_resource::<Inventory>().count("Old Key"), 1);
+    }
+}
@@ -0,0 +1,197 @@
+use specs::{World, WorldExt, Builder, Entity};
+
+use coffee::graphics::Point;
+
+use super::{PositionComponent, VisualComponent, AnimationComponent, PlayerControlComponent,
+            MovementComponent, ColliderComponent, InteractionComponent, NameComponent, TriggerComponent,
+            MapComponent, WarpComponent, CurrentMap, Party};
+use super::character_components;
+
+use crate::assets::{AssetDatabase, AssetContainer, PrefabComponent, TriggerCondition};
+
+
+//creates an entity from a prefab asset. A given position replaces the one in the prefab.
+pub fn spawn_prefab(world: &mut World, prefab_name: &str, position: Option<Point>) -> Option<Entity> {
+
+    //copy the component data out so the database isn't borrowed while building the entity
+    let components = match world.write_resource::<AssetDatabase>().get_asset(&prefab_name.to_string()) {
+        AssetContainer::EntityPrefab(prefab) => prefab.components.clone(),
+        _ => {
+            warn!("[Spawning] {} {} {}.",
+                  "Requested entity prefab",
+                  prefab_name,
+                  "does not exist",
+            );
+            return None;
+        }
+    };
+
+    //characters need other assets looked up before the world is borrowed for building
+    let mut character = None;
+    for component in components.iter() {
+        if let PrefabComponent::Character { character: character_name, level } = component {
+            character = character_components(&mut world.write_resource::<AssetDatabase>(), character_name, *level);
+        }
+    }
+
+    let mut builder = world.create_entity();
+    let mut has_position = false;
+
+    for component in components {
+        builder = match component {
+            PrefabComponent::Position { map_pos } => {
+                has_position = true;
+                builder.with(PositionComponent { map_pos: position.unwrap_or(map_pos) })
+            },
+            PrefabComponent::Visual { sprite_sheet_name, sprite_location } => 
+                builder.with(VisualComponent { sprite_sheet_name, sprite_location }),
+            PrefabComponent::Animation { frame_duration } => 
+                builder.with(AnimationComponent::new(frame_duration)),
+            PrefabComponent::PlayerControl { speed } => 
+                builder.with(PlayerControlComponent { speed }),
+            PrefabComponent::Movement => 
+                builder.with(MovementComponent::new()),
+            PrefabComponent::Interaction { dialogue } => 
+                builder.with(InteractionComponent { dialogue }),
+            PrefabComponent::Character { .. } => match character.take() {
+                Some(character) => builder.with(character.character)
+                                          .with(character.level)
+                                          .with(character.base_stats)
+                                          .with(character.vitals)
+                                          .with(character.equipment),
+                None => builder,
+            },
+            PrefabComponent::Collider { offset, width, height } => 
+                builder.with(ColliderComponent { offset, width, height }),
+            PrefabComponent::Trigger { condition, event, size } =>
+                builder.with(TriggerComponent::new(condition, event, size)),
+        };
+    }
+
+    //a spawn position still places prefabs that didn't list a position component
+    if let (false, Some(map_pos)) = (has_position, position) {
+        builder = builder.with(PositionComponent { map_pos });
+    }
+
+    Some(builder.build())
+}
+
+
+//spawns the entities placed on the current map. Any 'spawn' object with a 'prefab' property
+//  creates that prefab at the object's position, named after the object. 'trigger' objects run
+//  their 'event' script 'on' "enter", "interact", "map load", or "flag change" of their 'flag'.
+//  'warp' objects send the party to their 'map', arriving at the object named by 'arrive at'.
+//  Once there is a party, player controlled prefabs are left out and their spawn is where the
+//  party arrives instead. Everything spawned belongs to the map and is removed when it is left.
+// returns: where the map places the party
+pub fn spawn_map_entities(world: &mut World) -> Option<Point> {
+
+    let map_name = match &world.read_resource::<CurrentMap>().name {
+        Some(map_name) => map_name.clone(),
+        None => return None,
+    };
+
+    let has_party = world.read_resource::<Party>().leader().is_some();
+
+    let (spawns, arrival, triggers, warps) = {
+        let mut asset_database = world.write_resource::<AssetDatabase>();
+
+        let objects: Vec<_> = match asset_database.get_asset(&map_name) {
+            AssetContainer::Tilemap(tilemap) => tilemap.objects.iter()
+                .map(|object| (object.name.clone(), object.kind.clone(), object.area, object.properties.clone()))
+                .collect(),
+            _ => {
+                warn!("[Spawning] {} {} {}.",
+                      "Requested tilemap",
+                      map_name,
+                      "does not exist",
+                );
+                return None;
+            }
+        };
+
+        let spawns: Vec<(String, String, Point)> = objects.iter()
+            .filter(|(_, kind, _, _)| kind == "spawn")
+            .filter_map(|(name, _, area, properties)| {
+                properties.get("prefab")
+                          .map(|prefab_name| (name.clone(), prefab_name.clone(), Point::new(area.x, area.y)))
+            })
+            .collect();
+
+        let mut triggers = vec![];
+        for (name, _, area, properties) in objects.iter().filter(|(_, kind, _, _)| kind == "trigger") {
+            let condition = TriggerCondition::parse(properties.get("on").map(|on| on.as_str()).unwrap_or("enter"),
+                                                    properties.get("flag").map(|flag| flag.as_str()));
+
+            match (condition, properties.get("event")) {
+                (Ok(condition), Some(event)) => triggers.push( (name.clone(), Point::new(area.x, area.y),
+                                                 TriggerComponent::new(condition, event.clone(), (area.width, area.height))) ),
+                (Err(err), _) => warn!("[Spawning] Trigger {} on map {} was skipped. {}.", name, map_name, err),
+                (_, None) => warn!("[Spawning] Trigger {} on map {} was skipped. It has no 'event' to run.", name, map_name),
+            }
+        }
+
+        let mut warps = vec![];
+        for (name, _, area, properties) in objects.iter().filter(|(_, kind, _, _)| kind == "warp") {
+            match properties.get("map") {
+                Some(map) => warps.push( (name.clone(), Point::new(area.x, area.y),
+                                          WarpComponent::new(map.clone(), properties.get("arrive at").cloned(),
+                                                             (area.width, area.height))) ),
+                None => warn!("[Spawning] Warp {} on map {} was skipped. It has no 'map' to go to.", name, map_name),
+            }
+        }
+
+        //the party is already somewhere so the player's own spawn only says where they arrive
+        let mut spawns_left = vec![];
+        let mut arrival = None;
+        for (name, prefab_name, position) in spawns {
+            let player_controlled = match asset_database.get_asset(&prefab_name) {
+                AssetContainer::EntityPrefab(prefab) => prefab.components.iter()
+                    .any(|component| matches!(component, PrefabComponent::PlayerControl { .. })),
+                _ => false,
+            };
+
+            if has_party && player_controlled {
+                arrival = arrival.or(Some(position));
+            } else {
+                spawns_left.push( (name, prefab_name, position) );
+            }
+        }
+
+        (spawns_left, arrival, triggers, warps)
+    };
+
+    for (name, prefab_name, position) in spawns {
+        if let Some(entity) = spawn_prefab(world, &prefab_name, Some(position)) {
+            let _ = world.write_storage::<MapComponent>().insert(entity, MapComponent { map: map_name.clone() });
+            if !name.is_empty() {
+                let _ = world.write_storage::<NameComponent>().insert(entity, NameComponent { name });
+            }
+        }
+    }
+
+    for (name, position, trigger) in triggers {
+        let mut builder = world.create_entity()
+                               .with(PositionComponent { map_pos: position })
+                               .with(MapComponent { map: map_name.clone() })
+                               .with(trigger);
+        if !name.is_empty() {
+            builder = builder.with(NameComponent { name });
+        }
+        builder.build();
+    }
+
+    for (name, position, warp) in warps {
+        let mut builder = world.create_entity()
+                               .with(PositionComponent { map_pos: position })
+                               .with(MapComponent { map: map_name.clone() })
+                               .with(warp);
+        if !name.is_empty() {
+            builder = builder.with(NameComponent { name });
+        }
+        builder.build();
+    }
+
+    arrival
+}
+
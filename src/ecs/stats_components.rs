@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use specs::{Component, VecStorage};
+
+use crate::assets::Stats;
+
+
+//For entities made from a character asset. The class decides how their stats grow.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct CharacterComponent {
+    pub name: String,
+    pub class: String, //name of the class asset
+}
+
+
+//For characters that gain experience and levels
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct LevelComponent {
+    pub level: u32,
+    pub experience: u32, //total gained so far
+}
+
+
+//Stats of a character at their current level before anything else modifies them
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct BaseStatsComponent {
+    pub stats: Stats,
+}
+
+
+//HP and MP a character has left. Carries over between battles.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct VitalsComponent {
+    pub hp: u32,
+    pub mp: u32,
+}
+
+
+//What a character is wearing. Each equipment slot holds the name of an item asset.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct EquipmentComponent {
+    pub equipped: HashMap<String, String>,
+}
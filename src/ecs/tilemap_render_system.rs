@@ -0,0 +1,80 @@
+use specs::{WriteExpect, ReadExpect, System};
+
+use coffee::graphics::Point;
+
+use super::resources::{CurrentMap, ViewArea, TileBatches, TileBatch};
+
+use crate::assets::{AssetDatabase, AssetContainer, Tilemap};
+
+//the tilemap render system gathers the visible tiles of the current map into tile batches, one
+//  for each layer and tileset. They are drawn before everything else so the tiles end up
+//  underneath every entity.
+pub struct TilemapRenderSystem;
+
+impl<'a> System<'a> for TilemapRenderSystem {
+    type SystemData = ( WriteExpect<'a, AssetDatabase>,
+                        ReadExpect<'a, CurrentMap>,
+                        ReadExpect<'a, ViewArea>,
+                        WriteExpect<'a, TileBatches>);
+
+    fn run(&mut self, (mut asset_database, current_map, view_area, mut tile_batches): Self::SystemData) {
+
+        tile_batches.batches.clear();
+
+        let map_name = match &current_map.name {
+            Some(map_name) => map_name,
+            None => return, //nothing to draw
+        };
+
+        //gather the tiles first since the tilesets live in the same database
+        let tiles: Vec<(usize, String, Point, u16)> = match asset_database.get_asset(map_name) {
+            AssetContainer::Tilemap(tilemap) => {
+                tilemap.visible_tiles(&view_area.area)
+                       .into_iter()
+                       .filter_map(|(layer, position, index)| {
+                           tilemap.tileset_for(index)
+                                  .map(|(tileset, local_index)| (layer, tileset.sheet_name.clone(), position, local_index))
+                       })
+                       .collect()
+            },
+
+            _ => {
+                warn!("[Tilemap Render System] {} {} {}.",
+                      "Requested tilemap",
+                      map_name,
+                      "does not exist",
+                );
+                return;
+            }
+        };
+
+        for (layer, sheet_name, position, index) in tiles {
+            let columns = match asset_database.get_asset(&sheet_name) {
+                AssetContainer::Spritesheet(tileset) => tileset.columns,
+
+                _ => {
+                    warn!("[Tilemap Render System] {} {} {} {}.",
+                          "Tileset",
+                          sheet_name,
+                          "is missing or not a sprite sheet for tilemap",
+                          map_name,
+                    );
+                    return;
+                }
+            };
+
+            let sprite_pos = Tilemap::index_to_sprite_pos(index, columns);
+
+            //tiles come a layer at a time so only batches of the current layer can be added to
+            let same_batch = tile_batches.batches.iter_mut()
+                                         .rev()
+                                         .take_while(|batch| batch.layer == layer)
+                                         .find(|batch| batch.sheet_name == sheet_name);
+
+            match same_batch {
+                Some(batch) => batch.sprites.push((position, sprite_pos)),
+                None => tile_batches.batches.push( TileBatch { layer, sheet_name, sprites: vec![(position, sprite_pos)] } ),
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use specs::{ReadExpect, WriteExpect, ReadStorage, WriteStorage, System, World, ReaderId};
+
+use coffee::graphics::Rectangle;
+
+use super::position_component::PositionComponent;
+use super::physics_components::ColliderComponent;
+use super::event_components::TriggerComponent;
+use super::resources::{Party, GameFlags, Inventory, EventQueue};
+
+use crate::assets::{TriggerCondition, areas_overlap};
+
+
+//queues the event scripts of triggers whose condition just became true. Map load triggers go
+//  off the first time they are checked, area triggers when the party leader walks in, and flag
+//  triggers when a flag changes and their condition becomes true. Interact triggers are set off
+//  by the interaction system instead.
+pub struct TriggerSystem {
+    flag_changes: Option<ReaderId<String>>, //None until set up, every update counts as a change then
+}
+
+impl TriggerSystem {
+
+    pub fn new() -> TriggerSystem {
+        TriggerSystem {
+            flag_changes: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for TriggerSystem {
+    type SystemData = ( WriteExpect<'a, EventQueue>,
+                        ReadExpect<'a, Party>,
+                        ReadExpect<'a, GameFlags>,
+                        ReadExpect<'a, Inventory>,
+                        ReadStorage<'a, PositionComponent>,
+                        ReadStorage<'a, ColliderComponent>,
+                        WriteStorage<'a, TriggerComponent>);
+
+    fn setup(&mut self, world: &mut World) {
+        use specs::SystemData;
+        Self::SystemData::setup(world);
+
+        self.flag_changes = Some( world.fetch_mut::<GameFlags>().track_changes() );
+    }
+
+    fn run(&mut self, (mut event_queue, party, flags, inventory, position, collider, mut trigger): Self::SystemData) {
+        use specs::Join;
+
+        let leader_area = leader_area(&party, &position, &collider);
+
+        //flag conditions only need checking again after a flag changed
+        let flags_changed = match &mut self.flag_changes {
+            Some(reader) => flags.changes(reader).count() > 0,
+            None => true,
+        };
+
+        for (trigger, trigger_position) in (&mut trigger, position.maybe()).join() {
+            let state = match &trigger.condition {
+                TriggerCondition::MapLoad => true,
+                TriggerCondition::Enter => match (trigger_position, &leader_area) {
+                    (Some(trigger_position), Some(leader_area)) =>
+                        areas_overlap(&trigger.area_at(trigger_position.map_pos), leader_area),
+                    _ => false,
+                },
+                TriggerCondition::FlagChange(condition) => {
+                    if !flags_changed && trigger.last_state.is_some() { continue; }
+                    flags.meets(std::slice::from_ref(condition), &inventory)
+                },
+                TriggerCondition::Interact => continue,
+            };
+
+            //map load triggers only need to have never been checked, the others need a change
+            //  so standing in an area or a flag that was already set doesn't count
+            let fire = match &trigger.condition {
+                TriggerCondition::MapLoad => trigger.last_state.is_none(),
+                _ => state && trigger.last_state == Some(false),
+            };
+            trigger.last_state = Some(state);
+
+            if fire {
+                event_queue.push(&trigger.event);
+            }
+        }
+    }
+}
+
+
+//area the party leader takes up on the map, a single pixel when they have no collider
+pub fn leader_area(party: &Party, position: &ReadStorage<PositionComponent>, collider: &ReadStorage<ColliderComponent>)
+    -> Option<Rectangle<f32>> {
+    let leader = party.leader()?;
+    let leader_position = position.get(leader)?.map_pos;
+    Some( match collider.get(leader) {
+        Some(collider) => collider.area_at(leader_position),
+        None => Rectangle { x: leader_position.x, y: leader_position.y, width: 1.0, height: 1.0 },
+    })
+}
@@ -0,0 +1,54 @@
+use specs::{ReadExpect, WriteExpect, ReadStorage, WriteStorage, System};
+
+use super::position_component::PositionComponent;
+use super::physics_components::ColliderComponent;
+use super::scene_components::WarpComponent;
+use super::trigger_system::leader_area;
+use super::resources::{Party, EventQueue, SceneManager, MapChange};
+
+use crate::assets::{MapArrival, areas_overlap};
+
+
+//asks the scene manager to move the party when the leader walks into a warp. Like area
+//  triggers a warp needs the leader to walk in, so arriving on top of one doesn't send the
+//  party straight back. Nothing happens while the map is already changing, and an event script
+//  walking the leader through a warp doesn't count.
+pub struct WarpSystem;
+
+impl<'a> System<'a> for WarpSystem {
+    type SystemData = ( WriteExpect<'a, SceneManager>,
+                        ReadExpect<'a, Party>,
+                        ReadExpect<'a, EventQueue>,
+                        ReadStorage<'a, PositionComponent>,
+                        ReadStorage<'a, ColliderComponent>,
+                        WriteStorage<'a, WarpComponent>);
+
+    fn run(&mut self, (mut scene, party, event_queue, position, collider, mut warp): Self::SystemData) {
+        use specs::Join;
+
+        if scene.is_changing() { return; }
+        let scripted = event_queue.is_running();
+
+        let leader_area = leader_area(&party, &position, &collider);
+
+        for (warp, warp_position) in (&mut warp, &position).join() {
+            let inside = match &leader_area {
+                Some(leader_area) => areas_overlap(&warp.area_at(warp_position.map_pos), leader_area),
+                None => false,
+            };
+
+            let entered = inside && warp.last_state == Some(false) && !scripted;
+            warp.last_state = Some(inside);
+
+            if entered {
+                scene.request( MapChange {
+                    map: warp.map.clone(),
+                    arrival: match &warp.arrive_at {
+                        Some(object_name) => MapArrival::Object(object_name.clone()),
+                        None => MapArrival::MapSpawn,
+                    },
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use super::game_state::{GameState, StateChange, StateResult, TICKS_PER_SECOND};
+
+use coffee::{
+    graphics::{Frame, Window, Color},
+    ui::{button, Button, Column, Row, Element, Text, Image as ImageWidget},
+    Timer
+};
+
+use super::UIAction;
+
+use super::main_menu_state::MainMenuState;
+
+use crate::assets::audio::MusicPlaylist;
+use crate::battle::{Battle, BattleCommand, BattleOutcome, Side, SKILL_MP_COST};
+use crate::ecs::CombatantSprites;
+
+
+//seconds an enemy waits before acting so the player can follow what happens
+const ENEMY_TURN_DELAY: f32 = 0.8;
+
+
+//what the player is currently picking
+#[derive(Clone, Copy)]
+enum Selection {
+    Command,
+    AttackTarget,
+    SkillTarget,
+    Item,
+    ItemTarget(usize),
+}
+
+
+//Turn based fight between the party and an encounter's enemies. Pushed over the playing state
+//  and hands the result back to it when the battle is over.
+pub struct BattleState {
+    battle: Battle,
+    sprites: CombatantSprites,
+    selection: Selection,
+    enemy_timer: f32,
+    state_change: Option<StateChange>,
+    music: Option<MusicPlaylist>, //None carries on with the map's music
+
+    attack_button: button::State,
+    skill_button: button::State,
+    item_button: button::State,
+    defend_button: button::State,
+    flee_button: button::State,
+    cancel_button: button::State,
+    continue_button: button::State,
+    target_buttons: Vec<button::State>,
+    item_buttons: Vec<button::State>,
+}
+
+impl BattleState {
+
+    pub fn new(battle: Battle, sprites: CombatantSprites, music: Option<MusicPlaylist>) -> BattleState {
+        BattleState {
+            target_buttons: battle.combatants.iter().map(|_| button::State::new()).collect(),
+            item_buttons: battle.items.iter().map(|_| button::State::new()).collect(),
+            battle,
+            sprites,
+            selection: Selection::Command,
+            enemy_timer: ENEMY_TURN_DELAY,
+            state_change: None,
+            music,
+
+            attack_button: button::State::new(),
+            skill_button: button::State::new(),
+            item_button: button::State::new(),
+            defend_button: button::State::new(),
+            flee_button: button::State::new(),
+            cancel_button: button::State::new(),
+            continue_button: button::State::new(),
+        }
+    }
+
+    fn perform(&mut self, command: BattleCommand) {
+        self.battle.perform(command);
+        self.selection = Selection::Command;
+        self.enemy_timer = ENEMY_TURN_DELAY;
+    }
+}
+
+impl GameState for BattleState {
+
+    fn update(&mut self, _window: &Window) {
+        if self.battle.outcome.is_some() || self.battle.is_party_turn() { return; }
+
+        //enemies act on their own after a short pause
+        self.enemy_timer -= 1.0 / f32::from(TICKS_PER_SECOND);
+        if self.enemy_timer <= 0.0 {
+            let command = self.battle.choose_enemy_command();
+            self.perform(command);
+        }
+    }
+
+
+    fn draw(&mut self, frame: &mut Frame, _timer: &Timer) {
+        // Clear the current frame
+        frame.clear(Color::from_rgb(20, 20, 40));
+    }
+
+
+    fn react(&mut self, message: UIAction, _window: &mut Window) -> Option< Box<dyn GameState> > {
+        let party_turn = self.battle.is_party_turn() && self.battle.outcome.is_none();
+
+        match message {
+            UIAction::BattleAttack if party_turn => self.selection = Selection::AttackTarget,
+            UIAction::BattleSkill if party_turn => self.selection = Selection::SkillTarget,
+            UIAction::BattleItem if party_turn => self.selection = Selection::Item,
+            UIAction::BattleUseItem(item) if party_turn => self.selection = Selection::ItemTarget(item),
+            UIAction::BattleDefend if party_turn => self.perform(BattleCommand::Defend),
+            UIAction::BattleFlee if party_turn => self.perform(BattleCommand::Flee),
+            UIAction::BattleCancel => self.selection = Selection::Command,
+            UIAction::BattleTarget(target) if party_turn => {
+                match self.selection {
+                    Selection::AttackTarget => self.perform(BattleCommand::Attack(target)),
+                    Selection::SkillTarget => self.perform(BattleCommand::Skill(target)),
+                    Selection::ItemTarget(item) => self.perform(BattleCommand::Item(item, target)),
+                    _ => {},
+                }
+            },
+            UIAction::BattleContinue => match self.battle.outcome {
+                //a defeated party ends the game
+                Some(BattleOutcome::Defeat) => return Option::Some( Box::new(MainMenuState::new()) ),
+                Some(_) => self.state_change = Some(StateChange::Pop( StateResult::Battle(self.battle.result()) )),
+                None => {},
+            },
+            _ => {},
+        };
+
+        Option::None
+    }
+
+
+    fn layout(&mut self, window: &Window) -> Element<'_, UIAction> {
+        let battle = &self.battle;
+        let sprite_size = (window.height()/8.0) as u32;
+
+        //who can be picked with the target buttons right now
+        let target_side = match self.selection {
+            Selection::AttackTarget | Selection::SkillTarget => Some(Side::Enemies),
+            _ => None,
+        };
+
+        let mut enemy_row = Row::new().spacing(20);
+        let mut party_row = Row::new().spacing(20);
+
+        let combatants = battle.combatants.iter().zip(self.sprites.iter()).zip(self.target_buttons.iter_mut());
+        for (index, ((combatant, sprite), target_state)) in combatants.enumerate() {
+            let mut card = Column::new()
+                .width( (window.width()/6.0) as u32 )
+                .spacing(5);
+
+            if let Some((image, source)) = sprite {
+                card = card.push( ImageWidget::new(image).clip(*source).width(sprite_size).height(sprite_size) );
+            }
+
+            let name = if battle.current() == Some(index) { format!("> {}", combatant.name) } else { combatant.name.clone() };
+            card = card
+                .push( Text::new(&name) )
+                .push( Text::new(&format!("HP {}/{}", combatant.hp, combatant.stats.max_hp)) );
+
+            if combatant.side == Side::Party {
+                card = card.push( Text::new(&format!("MP {}/{}", combatant.mp, combatant.stats.max_mp)) );
+            }
+
+            let can_target = match self.selection {
+                Selection::ItemTarget(item) => battle.can_use_item(item, index),
+                _ => target_side == Some(combatant.side) && combatant.is_alive(),
+            };
+
+            if can_target {
+                card = card.push(
+                    Button::new(target_state, "Target")
+                        .fill_width()
+                        .on_press(UIAction::BattleTarget(index))
+                );
+            }
+
+            match combatant.side {
+                Side::Enemies => enemy_row = enemy_row.push(card),
+                Side::Party => party_row = party_row.push(card),
+            }
+        }
+
+        let mut log = Column::new().spacing(5).height( (window.height()/4.0) as u32 );
+        for line in battle.log.iter() {
+            log = log.push( Text::new(line) );
+        }
+
+        //bottom panel changes with what the player is doing
+        let mut commands = Column::new()
+            .width( (window.width()/4.0) as u32 )
+            .spacing(10);
+
+        if battle.outcome.is_some() {
+            let result = battle.result();
+            if result.outcome == BattleOutcome::Victory {
+                commands = commands.push( Text::new(&format!("Gained {} experience.", result.experience)) );
+                for item in result.loot.iter() {
+                    commands = commands.push( Text::new(&format!("Found {}.", item)) );
+                }
+                for level_up in battle.level_ups() {
+                    commands = commands.push( Text::new(&level_up) );
+                }
+            }
+
+            let continue_text = if result.outcome == BattleOutcome::Defeat { "Return to Main Menu" } else { "Continue" };
+            commands = commands.push(
+                Button::new(&mut self.continue_button, continue_text)
+                    .fill_width()
+                    .on_press(UIAction::BattleContinue)
+            );
+
+        } else if battle.is_party_turn() {
+            match self.selection {
+                Selection::Command => {
+                    let actor = battle.current().map(|index| &battle.combatants[index]);
+                    let can_use_skill = actor.map(|actor| actor.mp >= SKILL_MP_COST).unwrap_or(false);
+                    let has_items = battle.items.iter().any(|item| item.quantity > 0);
+
+                    //buttons without an action are shown disabled
+                    let skill = Button::new(&mut self.skill_button, &format!("Skill ({} MP)", SKILL_MP_COST)).fill_width();
+                    let item = Button::new(&mut self.item_button, "Item").fill_width();
+                    let flee = Button::new(&mut self.flee_button, "Flee").fill_width();
+
+                    commands = commands
+                        .push( Button::new(&mut self.attack_button, "Attack")
+                                .fill_width()
+                                .on_press(UIAction::BattleAttack) )
+                        .push( if can_use_skill { skill.on_press(UIAction::BattleSkill) } else { skill } )
+                        .push( if has_items { item.on_press(UIAction::BattleItem) } else { item } )
+                        .push( Button::new(&mut self.defend_button, "Defend")
+                                .fill_width()
+                                .on_press(UIAction::BattleDefend) )
+                        .push( if battle.can_flee { flee.on_press(UIAction::BattleFlee) } else { flee } );
+                },
+
+                Selection::Item => {
+                    for (index, (item, state)) in battle.items.iter().zip(self.item_buttons.iter_mut()).enumerate() {
+                        if item.quantity == 0 { continue; }
+
+                        commands = commands.push(
+                            Button::new(state, &format!("{} x{}", item.name, item.quantity))
+                                .fill_width()
+                                .on_press(UIAction::BattleUseItem(index))
+                        );
+                    }
+                    commands = commands.push(
+                        Button::new(&mut self.cancel_button, "Back").fill_width().on_press(UIAction::BattleCancel)
+                    );
+                },
+
+                _ => {
+                    commands = commands
+                        .push( Text::new("Choose a target.") )
+                        .push( Button::new(&mut self.cancel_button, "Back").fill_width().on_press(UIAction::BattleCancel) );
+                },
+            }
+        }
+
+        Column::new()
+            .padding( (window.width()/32.0) as u32 )
+            .spacing(20)
+            .push(enemy_row)
+            .push(log)
+            .push(
+                Row::new()
+                    .spacing(40)
+                    .push(party_row)
+                    .push(commands)
+            )
+            .into()
+    }
+
+
+    fn take_state_change(&mut self) -> Option<StateChange> {
+        self.state_change.take()
+    }
+
+    fn music(&self) -> Option<&MusicPlaylist> {
+        self.music.as_ref()
+    }
+}
@@ -0,0 +1,129 @@
+use super::game_state::GameState;
+
+use coffee::{
+    graphics::{Frame, Window, Color, Gpu},
+    ui::{button, Button, Column, Row, Element, Text, Image},
+    Timer
+};
+
+use super::UIAction;
+
+use super::main_menu_state::MainMenuState;
+use super::playing_state::PlayingState;
+
+use crate::assets::{CampaignMetadata, load_all_campaign_metadata};
+use std::path::PathBuf;
+
+
+//Lists every campaign found under the campaigns folder so the player can choose which one to play.
+pub struct CampaignSelectState {
+    campaigns: Vec<CampaignMetadata>,
+    selected_campaign: Option<usize>,
+
+    campaign_buttons: Vec<button::State>,
+    start_button: button::State,
+    back_button: button::State,
+}
+
+impl CampaignSelectState {
+
+    pub fn new(gpu: &mut Gpu) -> CampaignSelectState {
+        let path: PathBuf = ["campaigns"].iter().collect();
+        let campaigns = load_all_campaign_metadata(path.to_str().unwrap(), gpu);
+
+        if campaigns.is_empty() {
+            warn!("[Campaign Select] No campaigns were found in {}.", path.to_str().unwrap());
+        }
+
+        CampaignSelectState {
+            campaign_buttons: campaigns.iter().map(|_| button::State::new()).collect(),
+            //preselect the first campaign so start works right away
+            selected_campaign: if campaigns.is_empty() { None } else { Some(0) },
+            campaigns,
+
+            start_button: button::State::new(),
+            back_button: button::State::new(),
+        }
+    }
+}
+
+impl GameState for CampaignSelectState {
+
+    fn draw(&mut self, frame: &mut Frame, _timer: &Timer) {
+        // Clear the current frame
+        frame.clear(Color::BLACK);
+    }
+
+
+    fn react(&mut self, message: UIAction, window: &mut Window) -> Option< Box<dyn GameState> > {
+        match message {
+            UIAction::SelectCampaign(index) if index < self.campaigns.len() => {
+                self.selected_campaign = Some(index);
+            },
+            UIAction::StartCampaign => {
+                if let Some(campaign) = self.selected_campaign.and_then(|index| self.campaigns.get(index)) {
+                    return Option::Some(
+                        Box::new(PlayingState::new( window.gpu(), campaign ))
+                    )
+                }
+            },
+            UIAction::ReturnToMainMenu => return Option::Some( Box::new(MainMenuState::new()) ),
+            _ => {},
+        };
+
+        Option::None
+    }
+
+
+    fn layout(&mut self, window: &Window) -> Element<'_, UIAction> {
+        //list of campaigns on the left
+        let mut campaign_list = Column::new()
+            .width( (window.width()/4.0) as u32)
+            .spacing(10)
+            .push( Text::new("Campaigns").size(40) );
+
+        for (index, (campaign, state)) in self.campaigns.iter().zip(self.campaign_buttons.iter_mut()).enumerate() {
+            campaign_list = campaign_list.push(
+                Button::new(state, &campaign.name)
+                    .fill_width()
+                    .on_press(UIAction::SelectCampaign(index))
+            );
+        }
+
+        campaign_list = campaign_list.push(
+            Button::new(&mut self.back_button, "Back")
+                .fill_width()
+                .on_press(UIAction::ReturnToMainMenu)
+        );
+
+        //details of the selected campaign on the right
+        let mut details = Column::new()
+            .width( (window.width()/2.0) as u32)
+            .spacing(10);
+
+        let campaigns = &self.campaigns;
+        if let Some(campaign) = self.selected_campaign.and_then(|index| campaigns.get(index)) {
+            if let Some(title_image) = &campaign.title_image {
+                details = details.push(
+                    Image::new(title_image).height( (window.height()/3.0) as u32 )
+                );
+            }
+
+            details = details
+                .push( Text::new(&campaign.name).size(40) )
+                .push( Text::new(&campaign.description) )
+                .push( Text::new(&format!("Version: {}", campaign.version)) )
+                .push( Text::new(&format!("Author: {}", campaign.author)) )
+                .push( Button::new(&mut self.start_button, "Start Campaign")
+                        .on_press(UIAction::StartCampaign)
+                );
+        }
+
+        Row::new()
+            .padding( (window.width()/16.0) as u32 )
+            .spacing( (window.width()/16.0) as u16 )
+            .push(campaign_list)
+            .push(details)
+            .into()
+    }
+}
@@ -0,0 +1,151 @@
+use super::game_state::GameState;
+
+use coffee::{
+    graphics::{Frame, Window, Color},
+    ui::{button, Button, Column, Row, Element, Text},
+    Timer
+};
+
+use super::UIAction;
+
+use super::main_menu_state::MainMenuState;
+
+use crate::input::{GameInput, InputAction, InputBindings};
+use crate::settings::{Settings, load_settings, save_settings};
+
+
+//Lets the player change which keys and gamepad buttons do what. Picking an action waits for
+//  the next key or button pressed, which replaces the action's other keys or buttons.
+pub struct ControlsState {
+    settings: Settings,
+    waiting_for: Option<InputAction>, //action the next key or button press is bound to
+    status: String,
+
+    action_buttons: Vec<button::State>,
+    cancel_button: button::State,
+    reset_button: button::State,
+    back_button: button::State,
+}
+
+impl ControlsState {
+
+    pub fn new() -> ControlsState {
+        ControlsState {
+            settings: load_settings(),
+            waiting_for: None,
+            status: String::new(),
+
+            action_buttons: InputAction::ALL.iter().map(|_| button::State::new()).collect(),
+            cancel_button: button::State::new(),
+            reset_button: button::State::new(),
+            back_button: button::State::new(),
+        }
+    }
+
+    //controls are saved as soon as they change so leaving the screen can't lose them
+    fn save(&mut self, done: String) {
+        self.status = match save_settings(&self.settings) {
+            Ok(()) => done,
+            Err(error) => {
+                warn!("[Controls] {}", error);
+                error
+            }
+        };
+    }
+}
+
+impl GameState for ControlsState {
+
+    fn interact(&mut self, input: &mut GameInput, _window: &mut Window) {
+        let action = match self.waiting_for {
+            Some(action) => action,
+            None => return,
+        };
+
+        if let Some(binding) = input.last_pressed() {
+            self.waiting_for = None;
+            self.settings.controls.rebind(action, binding);
+            self.save( format!("{} is now {}.", action.name(), self.settings.controls.describe(action)) );
+        }
+    }
+
+
+    fn draw(&mut self, frame: &mut Frame, _timer: &Timer) {
+        // Clear the current frame
+        frame.clear(Color::BLACK);
+    }
+
+
+    fn react(&mut self, message: UIAction, _window: &mut Window) -> Option< Box<dyn GameState> > {
+        match message {
+            UIAction::RebindAction(index) => {
+                if let Some(action) = InputAction::ALL.get(index) {
+                    self.waiting_for = Some(*action);
+                    self.status.clear();
+                }
+            },
+            UIAction::CancelRebind => self.waiting_for = None,
+            UIAction::ResetControls => {
+                self.waiting_for = None;
+                self.settings.controls = InputBindings::new();
+                self.save( "The default controls are back.".to_string() );
+            },
+            UIAction::ReturnToMainMenu => return Option::Some( Box::new(MainMenuState::new()) ),
+            _ => {},
+        };
+
+        Option::None
+    }
+
+
+    fn layout(&mut self, window: &Window) -> Element<'_, UIAction> {
+        let mut controls = Column::new()
+            .width( (window.width()/2.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Controls").size(40) )
+            .push( Text::new("Pick an action, then press the key or gamepad button to use for it.") );
+
+        for (index, (action, state)) in InputAction::ALL.iter().zip(self.action_buttons.iter_mut()).enumerate() {
+            let label = match self.waiting_for == Some(*action) {
+                true => format!("{}: press a key or button...", action.name()),
+                false => format!("{}: {}", action.name(), self.settings.controls.describe(*action)),
+            };
+
+            controls = controls.push(
+                Button::new(state, &label)
+                    .fill_width()
+                    .on_press(UIAction::RebindAction(index))
+            );
+        }
+
+        if self.waiting_for.is_some() {
+            controls = controls.push(
+                Button::new(&mut self.cancel_button, "Cancel")
+                    .fill_width()
+                    .on_press(UIAction::CancelRebind)
+            );
+        }
+
+        if !self.status.is_empty() {
+            controls = controls.push( Text::new(&self.status) );
+        }
+
+        controls = controls
+            .push( Button::new(&mut self.reset_button, "Reset to Defaults")
+                    .fill_width()
+                    .on_press(UIAction::ResetControls)
+            )
+            .push( Button::new(&mut self.back_button, "Back")
+                    .fill_width()
+                    .on_press(UIAction::ReturnToMainMenu)
+            );
+
+        Row::new()
+            .push(
+                //Adds in some horizontal spacing.
+                Column::new().width( (window.width()/8.0) as u32)
+            )
+            .push(controls)
+            .into()
+    }
+}
@@ -0,0 +1,109 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Row, Element, Text, Image},
+};
+
+use super::UIAction;
+
+use crate::ecs::DialogueView;
+use crate::settings::TextSpeed;
+
+
+//Box along the bottom of the screen showing what is being said along with the player's answers.
+//  Text is written out a few characters at a time at the player's text speed, and the answers
+//  only show once it is all there.
+pub struct DialogueBox {
+    text_speed: TextSpeed,
+    text: String, //line being written out, to notice when the next one starts
+    written: f32, //characters of the line written out so far
+
+    choice_buttons: Vec<button::State>,
+    continue_button: button::State,
+}
+
+impl DialogueBox {
+
+    pub fn new(text_speed: TextSpeed) -> DialogueBox {
+        DialogueBox {
+            text_speed,
+            text: String::new(),
+            written: 0.0,
+
+            choice_buttons: vec![],
+            continue_button: button::State::new(),
+        }
+    }
+
+    //writes out more of the line
+    pub fn advance(&mut self, seconds: f32) {
+        self.written = match self.text_speed.characters_per_second() {
+            Some(speed) => self.written + speed * seconds,
+            None => f32::INFINITY,
+        };
+    }
+
+    pub fn is_writing(&self) -> bool {
+        (self.written as usize) < self.text.chars().count()
+    }
+
+    //shows the rest of the line straight away
+    pub fn finish_writing(&mut self) {
+        self.written = f32::INFINITY;
+    }
+
+    //forgets the line so the same words in a later conversation are written out again
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.written = 0.0;
+    }
+
+
+    pub fn layout(&mut self, window: &Window, view: DialogueView) -> Element<'_, UIAction> {
+        if view.text != self.text {
+            self.text = view.text.clone();
+            self.written = 0.0;
+            self.advance(0.0);
+        }
+
+        let written: String = view.text.chars().take(self.written as usize).collect();
+        let mut text_column = Column::new()
+            .spacing(10)
+            .push( Text::new(&view.speaker).size(30) )
+            .push( Text::new(&written) );
+
+        //nodes without choices just continue, continuing early shows the whole line
+        if view.choices.is_empty() || self.is_writing() {
+            text_column = text_column.push(
+                Button::new(&mut self.continue_button, "Continue")
+                    .on_press(UIAction::AdvanceDialogue)
+            );
+
+        } else {
+            while self.choice_buttons.len() < view.choices.len() {
+                self.choice_buttons.push(button::State::new());
+            }
+
+            for (number, ((index, text), state)) in view.choices.iter().zip(self.choice_buttons.iter_mut()).enumerate() {
+                text_column = text_column.push(
+                    Button::new(state, &format!("{}. {}", number + 1, text))
+                        .fill_width()
+                        .on_press(UIAction::ChooseDialogueOption(*index))
+                );
+            }
+        }
+
+        let mut dialogue_row = Row::new()
+            .spacing(20);
+
+        if let Some((image, source)) = &view.portrait {
+            let portrait_size = (window.height()/8.0) as u32;
+            dialogue_row = dialogue_row.push(
+                Image::new(image).clip(*source).width(portrait_size).height(portrait_size)
+            );
+        }
+
+        dialogue_row
+            .push(text_column)
+            .into()
+    }
+}
@@ -0,0 +1,151 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Row, Element, Text},
+};
+
+use super::UIAction;
+
+use crate::ecs::EquipView;
+
+
+//Shows what a party member is wearing along with their stats. Items from the inventory they
+//  can wear are listed so they can be put on, worn items can be taken off.
+pub struct EquipMenu {
+    member: usize,
+    status: String,
+
+    member_buttons: Vec<button::State>,
+    slot_buttons: Vec<button::State>,
+    option_buttons: Vec<button::State>,
+    close_button: button::State,
+}
+
+impl EquipMenu {
+
+    pub fn new() -> EquipMenu {
+        EquipMenu {
+            member: 0,
+            status: String::new(),
+
+            member_buttons: vec![],
+            slot_buttons: vec![],
+            option_buttons: vec![],
+            close_button: button::State::new(),
+        }
+    }
+
+    //place in the party of the member being looked at
+    pub fn member(&self) -> usize {
+        self.member
+    }
+
+    pub fn select_member(&mut self, member: usize) {
+        self.member = member;
+        self.status.clear();
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+
+    pub fn layout(&mut self, window: &Window, members: Vec<String>, view: Option<EquipView>) -> Element<'_, UIAction> {
+        while self.member_buttons.len() < members.len() {
+            self.member_buttons.push(button::State::new());
+        }
+
+        let mut member_list = Column::new()
+            .width( (window.width()/5.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Equipment").size(40) );
+
+        for (index, (name, state)) in members.iter().zip(self.member_buttons.iter_mut()).enumerate() {
+            let label = if index == self.member { format!("> {}", name) } else { name.clone() };
+            member_list = member_list.push(
+                Button::new(state, &label)
+                    .fill_width()
+                    .on_press(UIAction::SelectEquipMember(index))
+            );
+        }
+
+        member_list = member_list.push(
+            Button::new(&mut self.close_button, "Close")
+                .fill_width()
+                .on_press(UIAction::CloseEquipMenu)
+        );
+
+        let view = match view {
+            Some(view) => view,
+            None => return Row::new()
+                .padding( (window.width()/32.0) as u32 )
+                .push(member_list)
+                .push( Text::new("Nobody in the party can wear equipment.") )
+                .into(),
+        };
+
+        while self.slot_buttons.len() < view.slots.len() {
+            self.slot_buttons.push(button::State::new());
+        }
+        while self.option_buttons.len() < view.options.len() {
+            self.option_buttons.push(button::State::new());
+        }
+
+        //what is worn and the stats that come from it
+        let mut worn = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new(&view.name).size(30) );
+
+        for (index, ((slot, item), state)) in view.slots.iter().zip(self.slot_buttons.iter_mut()).enumerate() {
+            let label = format!("{}: {}", slot, item.as_deref().unwrap_or("-"));
+            let button = Button::new(state, &label).fill_width();
+
+            //worn items are taken off by pressing their slot
+            worn = worn.push( if item.is_some() { button.on_press(UIAction::UnequipSlot(index)) } else { button } );
+        }
+
+        let (base, stats) = (view.base_stats, view.stats);
+        let stat_lines = [("HP", base.max_hp, stats.max_hp), ("MP", base.max_mp, stats.max_mp),
+                          ("Attack", base.attack, stats.attack), ("Defense", base.defense, stats.defense),
+                          ("Magic", base.magic, stats.magic), ("Speed", base.speed, stats.speed)];
+
+        for (stat, base, total) in stat_lines.iter() {
+            let line = if total == base {
+                format!("{} {}", stat, total)
+            } else {
+                format!("{} {} ({:+})", stat, total, i64::from(*total) - i64::from(*base))
+            };
+            worn = worn.push( Text::new(&line) );
+        }
+
+        if !self.status.is_empty() {
+            worn = worn.push( Text::new(&self.status) );
+        }
+
+        //items from the inventory that can be put on
+        let mut options = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Can wear:") );
+
+        if view.options.is_empty() {
+            options = options.push( Text::new("Nothing in the inventory.") );
+        }
+
+        for (index, ((item_name, summary), state)) in view.options.iter().zip(self.option_buttons.iter_mut()).enumerate() {
+            options = options.push(
+                Button::new(state, &format!("{} ({})", item_name, summary))
+                    .fill_width()
+                    .on_press(UIAction::EquipItem(index))
+            );
+        }
+
+        Row::new()
+            .padding( (window.width()/32.0) as u32 )
+            .spacing( (window.width()/32.0) as u16 )
+            .push(member_list)
+            .push(worn)
+            .push(options)
+            .into()
+    }
+}
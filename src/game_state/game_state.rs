@@ -1,18 +1,37 @@
 use coffee::{
     graphics::{Frame, Window},
     ui::{Element, Row},
-    input::KeyboardAndMouse,
     Timer
 };
 
 use super::UIAction;
 
+use crate::assets::audio::MusicPlaylist;
+use crate::battle::BattleResult;
+use crate::input::GameInput;
+
+
+//how many times per second the game updates. Systems get their time step from this.
+pub const TICKS_PER_SECOND: u16 = 60;
+
+
+//Ways a state can hand control to another one besides replacing itself from react.
+pub enum StateChange {
+    Push(Box<dyn GameState>), //pauses this state and runs a new one on top of it, like a battle over the map
+    Pop(StateResult), //ends this state and resumes the one it was pushed over
+}
+
+//what a finished state hands back to the state it was pushed over
+pub enum StateResult {
+    Battle(BattleResult),
+}
+
 
 //The game state trait lets us handle different stages of the application in a modular
 //  way by simply changing to a new state as needed.
 pub trait GameState {
     //These 3 functions handle general input, output, and updating data
-    fn interact(&mut self, _kbm: &mut KeyboardAndMouse, _window: &mut Window) {}
+    fn interact(&mut self, _input: &mut GameInput, _window: &mut Window) {}
     fn   update(&mut self, _window: &Window) {}
     fn     draw(&mut self, _frame: &mut Frame, _timer: &Timer) {}
 
@@ -21,4 +40,12 @@ pub trait GameState {
     fn layout(&mut self, _window: &Window) -> Element<UIAction> { Row::new().into() }
 
     fn is_finished(&self) -> bool { false }
+
+    //checked after every update and UI action
+    fn take_state_change(&mut self) -> Option<StateChange> { Option::None }
+    //called when a state pushed over this one ends
+    fn resume(&mut self, _result: StateResult) {}
+
+    //the music to play while this state runs, None carries on with whatever is playing
+    fn music(&self) -> Option<&MusicPlaylist> { Option::None }
 }
@@ -0,0 +1,137 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Row, Element, Text, Image},
+};
+
+use super::UIAction;
+
+use crate::ecs::{InventoryEntry, ItemTarget};
+
+
+//Lists the items the party carries. Picking an item shows its details and, when it can be
+//  used, a button for each party member to use it on.
+pub struct InventoryScreen {
+    selected: Option<usize>,
+    status: String,
+
+    item_buttons: Vec<button::State>,
+    target_buttons: Vec<button::State>,
+    close_button: button::State,
+}
+
+impl InventoryScreen {
+
+    pub fn new() -> InventoryScreen {
+        InventoryScreen {
+            selected: None,
+            status: String::new(),
+
+            item_buttons: vec![],
+            target_buttons: vec![],
+            close_button: button::State::new(),
+        }
+    }
+
+    pub fn select(&mut self, item: usize) {
+        self.selected = Some(item);
+        self.status.clear();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    //shown under the item details, like what happened when an item was used
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+
+    pub fn layout(&mut self, window: &Window, entries: Vec<InventoryEntry>, targets: Vec<ItemTarget>)
+       -> Element<'_, UIAction> {
+
+        let icon_size = (window.height()/24.0) as u32;
+
+        //the last item of a kind may have just been used up
+        if self.selected.map(|selected| selected >= entries.len()).unwrap_or(false) {
+            self.selected = None;
+        }
+
+        while self.item_buttons.len() < entries.len() {
+            self.item_buttons.push(button::State::new());
+        }
+        while self.target_buttons.len() < targets.len() {
+            self.target_buttons.push(button::State::new());
+        }
+
+        let mut item_list = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Items").size(40) );
+
+        if entries.is_empty() {
+            item_list = item_list.push( Text::new("The party isn't carrying anything.") );
+        }
+
+        for (index, (entry, state)) in entries.iter().zip(self.item_buttons.iter_mut()).enumerate() {
+            let mut row = Row::new().spacing(10);
+
+            if let Some((image, source)) = &entry.icon {
+                row = row.push( Image::new(image).clip(*source).width(icon_size).height(icon_size) );
+            }
+
+            item_list = item_list.push(
+                row.push(
+                    Button::new(state, &format!("{} x{}", entry.name, entry.count))
+                        .fill_width()
+                        .on_press(UIAction::SelectItem(index))
+                )
+            );
+        }
+
+        item_list = item_list.push(
+            Button::new(&mut self.close_button, "Close")
+                .fill_width()
+                .on_press(UIAction::CloseInventory)
+        );
+
+        let mut details = Column::new()
+            .width( (window.width()/2.5) as u32 )
+            .spacing(10);
+
+        if let Some(entry) = self.selected.and_then(|selected| entries.get(selected)) {
+            if let Some((image, source)) = &entry.icon {
+                details = details.push( Image::new(image).clip(*source).width(icon_size*2).height(icon_size*2) );
+            }
+
+            details = details
+                .push( Text::new(&entry.name).size(30) )
+                .push( Text::new(&format!("{} - worth {} gold", entry.category, entry.price)) )
+                .push( Text::new(&entry.description) );
+
+            if entry.usable {
+                details = details.push( Text::new("Use on:") );
+
+                for (index, (target, state)) in targets.iter().zip(self.target_buttons.iter_mut()).enumerate() {
+                    details = details.push(
+                        Button::new(state, &format!("{}  HP {}/{}  MP {}/{}",
+                                                    target.name, target.hp, target.max_hp, target.mp, target.max_mp))
+                            .fill_width()
+                            .on_press(UIAction::UseItemOn(index))
+                    );
+                }
+            }
+        }
+
+        if !self.status.is_empty() {
+            details = details.push( Text::new(&self.status) );
+        }
+
+        Row::new()
+            .padding( (window.width()/32.0) as u32 )
+            .spacing( (window.width()/16.0) as u16 )
+            .push(item_list)
+            .push(details)
+            .into()
+    }
+}
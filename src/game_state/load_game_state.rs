@@ -0,0 +1,71 @@
+use super::game_state::GameState;
+
+use coffee::{
+    graphics::{Frame, Window, Color},
+    ui::{Column, Row, Element},
+    Timer
+};
+
+use super::UIAction;
+
+use super::main_menu_state::MainMenuState;
+use super::playing_state::PlayingState;
+use super::save_slot_menu::SaveSlotMenu;
+
+use crate::save_game::read_save_slot;
+
+
+//Lets the player pick a save slot to continue from the main menu.
+pub struct LoadGameState {
+    slot_menu: SaveSlotMenu,
+}
+
+impl LoadGameState {
+
+    pub fn new() -> LoadGameState {
+        LoadGameState {
+            slot_menu: SaveSlotMenu::new(),
+        }
+    }
+}
+
+impl GameState for LoadGameState {
+
+    fn draw(&mut self, frame: &mut Frame, _timer: &Timer) {
+        // Clear the current frame
+        frame.clear(Color::BLACK);
+    }
+
+
+    fn react(&mut self, message: UIAction, window: &mut Window) -> Option< Box<dyn GameState> > {
+        match message {
+            UIAction::LoadFromSlot(slot) => {
+                let loaded = read_save_slot(slot)
+                    .and_then(|save| PlayingState::from_save(window.gpu(), &save));
+
+                match loaded {
+                    Ok(playing_state) => return Option::Some( Box::new(playing_state) ),
+                    Err(error) => {
+                        warn!("[Load Game] Could not load save slot {}: {}", slot + 1, error);
+                        self.slot_menu.set_status(error.to_string());
+                    }
+                }
+            },
+            UIAction::ReturnToMainMenu => return Option::Some( Box::new(MainMenuState::new()) ),
+            _ => {},
+        };
+
+        Option::None
+    }
+
+
+    fn layout(&mut self, window: &Window) -> Element<'_, UIAction> {
+        Row::new()
+            .push(
+                //Adds in some horizontal spacing.
+                Column::new().width( (window.width()/8.0) as u32)
+            )
+            .push( self.slot_menu.layout(window, false, "Back", UIAction::ReturnToMainMenu) )
+            .into()
+    }
+}
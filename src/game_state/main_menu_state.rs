@@ -8,18 +8,24 @@ use coffee::{
 
 use super::UIAction;
 
-use super::playing_state::PlayingState;
+use super::{CampaignSelectState, LoadGameState, ControlsState};
+use super::options_menu::OptionsMenu;
 
-use crate::assets::audio::{AudioClip, ClipCategory, Playlist};
+use crate::assets::audio::{AudioClip, ClipCategory, MusicPlaylist, MusicTrack};
 use std::path::PathBuf;
 
 pub struct MainMenuState {
     start_button: button::State,
     load_button: button::State,
+    controls_button: button::State,
+    options_button: button::State,
     quit_button: button::State,
 
+    //shown in place of the main menu so the music carries on while volumes change
+    options_menu: Option<OptionsMenu>,
+
     quit_requested: bool, 
-    music_playlist: Playlist,
+    music: MusicPlaylist,
 }
 
 impl MainMenuState {
@@ -27,21 +33,25 @@ impl MainMenuState {
 
     pub fn new() -> MainMenuState {
         //TODO building this playlist is a bit rough. Refine it via a loading function for builtin resources.
-        let clip1_path: PathBuf = ["builtin", "eclipse.mp3"].iter().collect();
-        let clip1 = AudioClip::new( clip1_path, ClipCategory::Music );
-
-        let clip2_path: PathBuf = ["builtin", "in-love.mp3"].iter().collect();
-        let clip2 = AudioClip::new(clip2_path, ClipCategory::Music );
+        let tracks = ["eclipse.mp3", "in-love.mp3"].iter().map(|file| {
+            let path: PathBuf = ["builtin", file].iter().collect();
+            MusicTrack::new( AudioClip::new(path, ClipCategory::Music) )
+        }).collect();
 
-        let playlist = Playlist::new(vec![clip1, clip2]);
+        //every main menu asks for the same playlist by name so going back to it doesn't restart the music
+        let music = MusicPlaylist::new("main menu".to_string(), tracks);
 
         MainMenuState {
             start_button: button::State::new(),
             load_button: button::State::new(),
+            controls_button: button::State::new(),
+            options_button: button::State::new(),
             quit_button: button::State::new(),
 
+            options_menu: None,
+
             quit_requested: false,
-            music_playlist: playlist,
+            music,
         }
     }
 
@@ -58,14 +68,34 @@ impl GameState for MainMenuState {
     fn react(&mut self, message: UIAction, window: &mut Window) -> Option< Box<dyn GameState> > {
         match message {
             UIAction::NewGame => {
-                //TODO temp until we get campaign selection working.
-                let path: PathBuf = ["campaigns", "TestGame"].iter().collect();
                 return Option::Some(
-                    Box::new(PlayingState::new( window.gpu(), path.to_str().unwrap() ))
+                    Box::new(CampaignSelectState::new( window.gpu() ))
                 )
             },
-            UIAction::LoadGame => warn!("Load game triggered"), //TODO Implement game loading
+            UIAction::LoadGame => return Option::Some( Box::new(LoadGameState::new()) ),
+            UIAction::OpenControls => return Option::Some( Box::new(ControlsState::new()) ),
+            UIAction::OpenOptions => self.options_menu = Some( OptionsMenu::new() ),
+            UIAction::CloseOptions => {
+                if let Some(options_menu) = self.options_menu.take() {
+                    if let Err(error) = options_menu.save() {
+                        warn!("[Main Menu] {}", error);
+                    }
+                }
+            },
+            UIAction::SetVolume(category, level) => {
+                if let Some(options_menu) = &mut self.options_menu { options_menu.set_volume(category, level); }
+            },
+            UIAction::SetFullscreen(fullscreen) => {
+                if let Some(options_menu) = &mut self.options_menu { options_menu.set_fullscreen(fullscreen, window); }
+            },
+            UIAction::SetWindowSize(index) => {
+                if let Some(options_menu) = &mut self.options_menu { options_menu.set_window_size(index); }
+            },
+            UIAction::SetTextSpeed(text_speed) => {
+                if let Some(options_menu) = &mut self.options_menu { options_menu.set_text_speed(text_speed); }
+            },
             UIAction::QuitGame => self.quit_requested = true,
+            _ => {},
         };
 
         Option::None
@@ -73,6 +103,16 @@ impl GameState for MainMenuState {
 
     // The layout logic, describing the different components of the user interface
     fn layout(&mut self, window: &Window) -> Element<UIAction> {
+        if let Some(options_menu) = &mut self.options_menu {
+            return Row::new()
+                .push(
+                    //Adds in some horizontal spacing.
+                    Column::new().width( (window.width()/8.0) as u32)
+                )
+                .push( options_menu.layout(window) )
+                .into();
+        }
+
         Row::new()
             .push(
                 //Adds in some horizontal spacing.
@@ -93,6 +133,14 @@ impl GameState for MainMenuState {
                             .fill_width()
                             .on_press(UIAction::LoadGame),
                     )
+                    .push( Button::new(&mut self.controls_button, "Controls")
+                            .fill_width()
+                            .on_press(UIAction::OpenControls),
+                    )
+                    .push( Button::new(&mut self.options_button, "Options")
+                            .fill_width()
+                            .on_press(UIAction::OpenOptions),
+                    )
                     .push( Button::new(&mut self.quit_button, "Quit Game")
                             .fill_width()
                             .on_press(UIAction::QuitGame),
@@ -101,11 +149,11 @@ impl GameState for MainMenuState {
             .into()
     }
 
-    
-    fn update(&mut self, _window: &Window) {
-        self.music_playlist.maintain_looping();
+
+    fn music(&self) -> Option<&MusicPlaylist> {
+        Some(&self.music)
     }
-    
+
 
     fn is_finished(&self) -> bool {
         self.quit_requested
@@ -2,11 +2,38 @@ mod ui_action;
 pub use ui_action::UIAction;
 
 mod game_state;
-pub use game_state::GameState;
+pub use game_state::{GameState, StateChange, TICKS_PER_SECOND};
 
 mod main_menu_state;
 pub use main_menu_state::MainMenuState;
 
+mod campaign_select_state;
+pub use campaign_select_state::CampaignSelectState;
+
+mod save_slot_menu;
+
+mod dialogue_box;
+
+mod inventory_screen;
+
+mod equip_menu;
+
+mod party_menu;
+
+mod quest_log;
+
+mod shop_screen;
+
+mod battle_state;
+
+mod load_game_state;
+pub use load_game_state::LoadGameState;
+
+mod controls_state;
+pub use controls_state::ControlsState;
+
+mod options_menu;
+
 pub mod playing_state;
 pub use playing_state::PlayingState;
 
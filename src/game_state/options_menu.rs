@@ -0,0 +1,121 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, slider, Button, Slider, Checkbox, Radio, Column, Row, Element, Text},
+};
+
+use super::UIAction;
+
+use crate::assets::audio::{ClipCategory, VolumeLevels, set_volume_levels};
+use crate::settings::{Settings, TextSpeed, WINDOW_SIZES, load_settings, save_settings};
+
+
+//Lets the player change volumes, the window, and how fast dialogue is written out. Volume
+//  changes are heard straight away and everything is saved when the menu is closed.
+pub struct OptionsMenu {
+    settings: Settings,
+
+    volume_sliders: [slider::State; 4], //master first, then each clip category
+    back_button: button::State,
+}
+
+//clip categories in the order their sliders are shown, None is the master volume
+const VOLUME_CONTROLS: [(Option<ClipCategory>, &str); 4] = [
+    (None, "Master"),
+    (Some(ClipCategory::Music), "Music"),
+    (Some(ClipCategory::Voice), "Voices"),
+    (Some(ClipCategory::Effects), "Sound Effects"),
+];
+
+fn level_mut(volume: &mut VolumeLevels, category: Option<ClipCategory>) -> &mut f32 {
+    match category {
+        None => &mut volume.master,
+        Some(ClipCategory::Music) => &mut volume.music,
+        Some(ClipCategory::Voice) => &mut volume.voice,
+        Some(ClipCategory::Effects) => &mut volume.effects,
+    }
+}
+
+impl OptionsMenu {
+
+    pub fn new() -> OptionsMenu {
+        OptionsMenu {
+            settings: load_settings(),
+
+            volume_sliders: [slider::State::new(), slider::State::new(), slider::State::new(), slider::State::new()],
+            back_button: button::State::new(),
+        }
+    }
+
+    //None changes the master volume
+    pub fn set_volume(&mut self, category: Option<ClipCategory>, level: f32) {
+        *level_mut(&mut self.settings.volume, category) = level.clamp(0.0, 1.0);
+        set_volume_levels(self.settings.volume);
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool, window: &mut Window) {
+        if fullscreen != self.settings.fullscreen {
+            window.toggle_fullscreen();
+            self.settings.fullscreen = fullscreen;
+        }
+    }
+
+    pub fn set_window_size(&mut self, index: usize) {
+        if let Some(size) = WINDOW_SIZES.get(index) {
+            self.settings.window_size = *size;
+        }
+    }
+
+    pub fn set_text_speed(&mut self, text_speed: TextSpeed) {
+        self.settings.text_speed = text_speed;
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_settings(&self.settings)
+    }
+
+
+    pub fn layout(&mut self, window: &Window) -> Element<'_, UIAction> {
+        let mut options = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(10)
+            .push( Text::new("Options").size(40) );
+
+        for ((category, label), state) in VOLUME_CONTROLS.iter().zip(self.volume_sliders.iter_mut()) {
+            let category = *category;
+            let level = *level_mut(&mut self.settings.volume, category);
+
+            options = options
+                .push( Text::new(&format!("{} volume: {}%", label, (level * 100.0).round())) )
+                .push( Slider::new(state, 0.0..=1.0, level, move |level| UIAction::SetVolume(category, level)) );
+        }
+
+        options = options.push(
+            Checkbox::new(self.settings.fullscreen, "Fullscreen", UIAction::SetFullscreen)
+        );
+
+        let window_size = self.settings.window_size;
+        let selected_size = WINDOW_SIZES.iter().position(|size| *size == window_size);
+        options = options.push( Text::new("Window size (used the next time the game starts)") );
+        for (index, (width, height)) in WINDOW_SIZES.iter().enumerate() {
+            options = options.push(
+                Radio::new(index, &format!("{} x {}", width, height), selected_size, UIAction::SetWindowSize)
+            );
+        }
+
+        let mut text_speeds = Row::new().spacing(10);
+        for text_speed in TextSpeed::ALL.iter() {
+            text_speeds = text_speeds.push(
+                Radio::new(*text_speed, text_speed.name(), Some(self.settings.text_speed), UIAction::SetTextSpeed)
+            );
+        }
+
+        options
+            .push( Text::new("Text speed") )
+            .push(text_speeds)
+            .push( Button::new(&mut self.back_button, "Back")
+                    .fill_width()
+                    .on_press(UIAction::CloseOptions)
+            )
+            .into()
+    }
+}
@@ -0,0 +1,121 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Row, Element, Text},
+};
+
+use super::UIAction;
+
+use crate::ecs::PartyMemberView;
+
+
+//Lists the active party members and the reserve. Picking one member and then another swaps
+//  them, which is how the leader is changed and how reserve members are brought in.
+pub struct PartyMenu {
+    selected: Option<usize>,
+    status: String,
+
+    member_buttons: Vec<button::State>,
+    move_button: button::State,
+    close_button: button::State,
+}
+
+impl PartyMenu {
+
+    pub fn new() -> PartyMenu {
+        PartyMenu {
+            selected: None,
+            status: String::new(),
+
+            member_buttons: vec![],
+            move_button: button::State::new(),
+            close_button: button::State::new(),
+        }
+    }
+
+    //first pick selects a member, the second one is swapped with them
+    // returns: the two members to swap once both are picked
+    pub fn pick(&mut self, member: usize) -> Option<(usize, usize)> {
+        self.status.clear();
+
+        match self.selected.take() {
+            Some(selected) if selected != member => Some( (selected, member) ),
+            Some(_) => None,
+            None => {
+                self.selected = Some(member);
+                None
+            }
+        }
+    }
+
+    //member moved between the active members and the reserve
+    pub fn take_selected(&mut self) -> Option<usize> {
+        self.selected.take()
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+
+    pub fn layout(&mut self, window: &Window, members: Vec<PartyMemberView>) -> Element<'_, UIAction> {
+        while self.member_buttons.len() < members.len() {
+            self.member_buttons.push(button::State::new());
+        }
+
+        let mut active = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Party").size(40) );
+
+        let mut reserve = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Reserve").size(40) );
+
+        for (index, (member, state)) in members.iter().zip(self.member_buttons.iter_mut()).enumerate() {
+            let marker = if self.selected == Some(index) { "> " } else { "" };
+            let role = if member.leader { " (leader)" } else { "" };
+            let label = format!("{}{}{}  Lv {}  HP {}/{}  MP {}/{}", marker, member.name, role, member.level,
+                                member.hp, member.max_hp, member.mp, member.max_mp);
+
+            let button = Button::new(state, &label)
+                .fill_width()
+                .on_press(UIAction::SelectPartyMember(index));
+
+            if member.active {
+                active = active.push(button);
+            } else {
+                reserve = reserve.push(button);
+            }
+        }
+
+        //the selected member can also be moved without swapping
+        let move_text = match self.selected.and_then(|selected| members.get(selected)) {
+            Some(member) if member.active => "Send to Reserve",
+            Some(_) => "Bring into Party",
+            None => "Pick someone to move",
+        };
+        let move_button = Button::new(&mut self.move_button, move_text).fill_width();
+
+        let mut commands = Column::new()
+            .width( (window.width()/5.0) as u32 )
+            .spacing(10)
+            .push( Text::new("Pick two members to swap them.") )
+            .push( if self.selected.is_some() { move_button.on_press(UIAction::MovePartyMember) } else { move_button } )
+            .push( Button::new(&mut self.close_button, "Close")
+                    .fill_width()
+                    .on_press(UIAction::ClosePartyMenu) );
+
+        if !self.status.is_empty() {
+            commands = commands.push( Text::new(&self.status) );
+        }
+
+        Row::new()
+            .padding( (window.width()/32.0) as u32 )
+            .spacing( (window.width()/32.0) as u16 )
+            .push(active)
+            .push(reserve)
+            .push(commands)
+            .into()
+    }
+}
@@ -1,25 +1,89 @@
-use super::game_state::GameState;
+use super::game_state::{GameState, StateChange, StateResult, TICKS_PER_SECOND};
 
 use coffee::{
-    graphics::{Gpu, Color, Frame, Window},
-    input::KeyboardAndMouse,
+    graphics::{Gpu, Color, Frame, Window, Rectangle, Mesh, Shape},
     input::keyboard::KeyCode,
+    ui::{button, Button, Column, Row, Element},
     Timer
 };
 
-use crate::assets::{AssetDatabase, AssetContainer, load_campaign_data};
+use super::UIAction;
+
+use super::main_menu_state::MainMenuState;
+use super::save_slot_menu::SaveSlotMenu;
+use super::dialogue_box::DialogueBox;
+use super::inventory_screen::InventoryScreen;
+use super::equip_menu::EquipMenu;
+use super::party_menu::PartyMenu;
+use super::quest_log::QuestLogScreen;
+use super::shop_screen::ShopScreen;
+use super::battle_state::BattleState;
+
+use crate::assets::{AssetDatabase, AssetContainer, CampaignMetadata, load_campaign_data, load_all_campaign_metadata};
+use crate::assets::audio::{SoundEffects, MusicPlaylist};
+use crate::save_game::{SaveFile, SaveError, SAVE_VERSION, write_save_slot, read_save_slot};
+use std::path::PathBuf;
+use std::collections::HashSet;
+
+use crate::input::{GameInput, InputAction};
+use crate::settings::{Settings, load_settings};
 
 use crate::ecs as ecs;
 
 use specs::{World, WorldExt, Dispatcher};
 
 
-//controller input values used by different ECS Systems
+//what the player is doing with the controls, used by different ECS Systems. Held actions last
+//  while their keys are held down, pressed ones are kept until a system takes them.
 pub struct ControlData {
-    pub move_left: bool,
-    pub move_right: bool,
-    pub move_up: bool,
-    pub move_down: bool,
+    held: HashSet<InputAction>,
+    pressed: HashSet<InputAction>,
+}
+
+impl ControlData {
+
+    pub fn new() -> ControlData {
+        ControlData {
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+        }
+    }
+
+    pub fn is_held(&self, action: InputAction) -> bool {
+        self.held.contains(&action)
+    }
+
+    pub fn hold(&mut self, action: InputAction, held: bool) {
+        if held {
+            let _ = self.held.insert(action);
+        } else {
+            let _ = self.held.remove(&action);
+        }
+    }
+
+    pub fn press(&mut self, action: InputAction) {
+        let _ = self.pressed.insert(action);
+    }
+
+    //true once for every press so two systems can't both act on it
+    pub fn take_pressed(&mut self, action: InputAction) -> bool {
+        self.pressed.remove(&action)
+    }
+
+    pub fn clear(&mut self) {
+        self.held.clear();
+        self.pressed.clear();
+    }
+}
+
+
+//Menus opened over the map for looking after the party. The world is paused while one is open.
+enum Overlay {
+    Inventory(InventoryScreen),
+    Equipment(EquipMenu),
+    Party(PartyMenu),
+    Quests(QuestLogScreen),
+    Shop(ShopScreen),
 }
 
 
@@ -30,30 +94,185 @@ pub struct PlayingState {
     render_dispatcher: Dispatcher<'static, 'static>,
     input_dispatcher: Dispatcher<'static, 'static>,
     data_dispatcher: Dispatcher<'static, 'static>,
+
+    //campaign being played, recorded in save files
+    campaign_name: String,
+    campaign_version: String,
+
+    //in game menu for saving and loading. The world is paused while it is open.
+    game_menu: Option<SaveSlotMenu>,
+    overlay: Option<Overlay>,
+    dialogue_box: DialogueBox,
+    menu_button: button::State,
+    items_button: button::State,
+    equip_button: button::State,
+    party_button: button::State,
+    quests_button: button::State,
+    main_menu_button: button::State,
+
+    //controls and text speed, read from the settings file
+    settings: Settings,
+
+    //set when the map hands over to a battle
+    state_change: Option<StateChange>,
+
+    //the map's music, or the campaign's menu music while a menu is open
+    music: MusicPlaylist,
+    battle_music: Option<String>,
+    menu_music: Option<String>,
 }
 
 impl PlayingState {
 
-    pub fn new(gpu: &mut Gpu, game_data_path: &str) -> PlayingState {
+    //starts a campaign from the beginning
+    pub fn new(gpu: &mut Gpu, campaign: &CampaignMetadata) -> PlayingState {
+        let mut world = PlayingState::create_world(gpu, campaign);
+
+        //add in the entities placed on the starting map, player controlled characters lead the party
+        ecs::spawn_map_entities(&mut world);
+        ecs::gather_party(&world);
+
+        for prefab_name in campaign.starting_party.iter() {
+            if let Some(member) = ecs::spawn_prefab(&mut world, prefab_name, None) {
+                ecs::join_party(&world, member);
+            }
+        }
+
+        for (item_name, count) in campaign.starting_items.iter() {
+            ecs::add_item(&world, item_name, *count);
+        }
+
+        if campaign.starting_gold > 0 {
+            world.write_resource::<ecs::GameFlags>().set_gold(campaign.starting_gold);
+        }
+
+        PlayingState::from_world(world, campaign)
+    }
+
+
+    //continues a campaign from a save file
+    pub fn from_save(gpu: &mut Gpu, save: &SaveFile) -> Result<PlayingState, SaveError> {
+        let path: PathBuf = ["campaigns"].iter().collect();
+        let campaign = load_all_campaign_metadata(path.to_str().unwrap(), gpu)
+            .into_iter()
+            .find(|campaign| campaign.name == save.campaign)
+            .ok_or_else(|| SaveError::MissingCampaign(save.campaign.clone()))?;
+
+        if campaign.version != save.campaign_version {
+            warn!("[Playing State] Save was made with version {} of {} but version {} is installed.",
+                  save.campaign_version, campaign.name, campaign.version);
+        }
+
+        let mut world = PlayingState::create_world(gpu, &campaign);
+
+        world.write_resource::<ecs::GameClock>().elapsed_seconds = save.play_time;
+        ecs::restore_world(&mut world, &save.world)?;
+
+        Ok(PlayingState::from_world(world, &campaign))
+    }
+
+
+    //loads a campaign's assets and sets up a world without any entities
+    fn create_world(gpu: &mut Gpu, campaign: &CampaignMetadata) -> World {
         let mut asset_db = AssetDatabase::new();
 
-        load_campaign_data(game_data_path, gpu, &mut asset_db);
+        load_campaign_data(campaign.path_str(), gpu, &mut asset_db);
 
-        //setup world with all components we need and add in some entities
+        if campaign.start_map.is_none() {
+            warn!("[Playing State] Campaign {} has no 'start map' so no map will be shown.", campaign.name);
+        }
+
+        //setup world with all components we need
         let mut world = World::new();
         ecs::register_components(&mut world);
-        ecs::create_test_entities(&mut world);
 
         //insert none ECS data into the world
         world.insert(asset_db); 
-        world.insert(ControlData { move_left: false, move_right: false, move_up: false, move_down: false });
+        world.insert(ControlData::new());
+        world.insert(ecs::CurrentMap { name: campaign.start_map.clone() });
+        world.insert(ecs::ViewArea { area: Rectangle { x: 0.0, y: 0.0, width: 0.0, height: 0.0 } });
+        world.insert(ecs::TileBatches { batches: vec![] });
+        world.insert(ecs::Camera::new());
+        world.insert(ecs::GameClock::new());
+        world.insert(ecs::GameFlags::new());
+        world.insert(ecs::ActiveDialogue::new());
+        world.insert(ecs::PendingBattle { encounter: None });
+        world.insert(ecs::Inventory::new());
+        world.insert(ecs::EquipmentSlots { slots: campaign.equipment_slots.clone() });
+        world.insert(ecs::Party::new());
+        world.insert(ecs::EventQueue::new());
+        world.insert(ecs::SceneManager::new());
+        world.insert(SoundEffects::new());
+        world.insert(ecs::GameEvents::new());
+        world.insert(ecs::QuestLog::new());
+        world.insert(ecs::PendingShop { shop: None });
+        world.insert(ecs::ShopSales::new());
+
+        world
+    }
+
 
+    fn from_world(mut world: World, campaign: &CampaignMetadata) -> PlayingState {
+        //systems that follow changes start following them here
+        let mut data_dispatcher = ecs::build_data_dispatcher();
+        data_dispatcher.setup(&mut world);
+
+        let settings = load_settings();
 
         PlayingState {
             world,
             render_dispatcher: ecs::build_render_dispatcher(),
             input_dispatcher: ecs::build_input_handling_dispatcher(),
-            data_dispatcher: ecs::build_data_dispatcher(),
+            data_dispatcher,
+
+            campaign_name: campaign.name.clone(),
+            campaign_version: campaign.version.clone(),
+
+            game_menu: None,
+            overlay: None,
+            dialogue_box: DialogueBox::new(settings.text_speed),
+            menu_button: button::State::new(),
+            items_button: button::State::new(),
+            equip_button: button::State::new(),
+            party_button: button::State::new(),
+            quests_button: button::State::new(),
+            main_menu_button: button::State::new(),
+
+            settings,
+
+            state_change: None,
+
+            music: MusicPlaylist::silence(String::new()),
+            battle_music: campaign.battle_music.clone(),
+            menu_music: campaign.menu_music.clone(),
+        }
+    }
+
+
+    //name of the music that should be playing, the campaign's menu music takes over from the
+    //  map's while a menu is open
+    fn wanted_music(&self) -> Option<String> {
+        if self.menu_music.is_some() && (self.game_menu.is_some() || self.overlay.is_some()) {
+            return self.menu_music.clone();
+        }
+
+        let map = self.world.read_resource::<ecs::CurrentMap>().name.clone()?;
+        match self.world.write_resource::<AssetDatabase>().get_asset(&map) {
+            AssetContainer::Tilemap(tilemap) => tilemap.music().cloned(),
+            _ => None,
+        }
+    }
+
+
+    //captures the current game so it can be written to a save slot
+    fn create_save(&self) -> SaveFile {
+        SaveFile {
+            version: SAVE_VERSION,
+            campaign: self.campaign_name.clone(),
+            campaign_version: self.campaign_version.clone(),
+            saved_at: 0, //filled in when written
+            play_time: self.world.read_resource::<ecs::GameClock>().elapsed_seconds,
+            world: ecs::save_world(&self.world),
         }
     }
  
@@ -61,23 +280,151 @@ impl PlayingState {
 
 impl GameState for PlayingState {
 
-    fn interact(&mut self, kbm: &mut KeyboardAndMouse, _window: &mut Window) {
-        let mut world = & self.world;
+    fn interact(&mut self, input: &mut GameInput, _window: &mut Window) {
+        let world = &self.world;
+        let bindings = &self.settings.controls;
+        let pressed = |action| bindings.was_pressed(action, input);
+
+        let talking = world.read_resource::<ecs::ActiveDialogue>().is_active();
+        let scripted = world.read_resource::<ecs::EventQueue>().is_running()
+                       || world.read_resource::<ecs::SceneManager>().is_changing();
+
+        //the menu action closes any open party menu first, otherwise it opens and closes the game menu.
+        //  Menus don't open while an event script is running or the map is changing.
+        if pressed(InputAction::Menu) {
+            if self.overlay.is_some() {
+                self.overlay = None;
+            } else {
+                self.game_menu = match self.game_menu {
+                    Some(_) => None,
+                    None if scripted => None,
+                    None => Some(SaveSlotMenu::new()),
+                };
+            }
+        }
+
+        //cancel only ever closes things
+        if pressed(InputAction::Cancel) {
+            if self.overlay.is_some() {
+                self.overlay = None;
+            } else {
+                self.game_menu = None;
+            }
+        }
+
+        //opens and closes the inventory when nothing else is in the way
+        if pressed(InputAction::Inventory) && self.game_menu.is_none() && !talking && !scripted {
+            self.overlay = match self.overlay {
+                Some(Overlay::Inventory(_)) => None,
+                _ => Some( Overlay::Inventory(InventoryScreen::new()) ),
+            };
+        }
+
+        let confirm_pressed = pressed(InputAction::Confirm);
+
+        //while talking the controls answer the conversation instead of moving the player
+        //  Confirm first shows the rest of a line that is still being written out.
+        if talking && self.game_menu.is_none() {
+            if let Some(view) = ecs::dialogue_view(world) {
+                if self.dialogue_box.is_writing() {
+                    if confirm_pressed { self.dialogue_box.finish_writing(); }
+
+                } else if view.choices.is_empty() {
+                    if confirm_pressed { ecs::advance_dialogue(world, None); }
+
+                } else {
+                    //the number keys pick choices by where they are in the list, so they aren't rebindable
+                    let number_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+                                       KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9];
+
+                    let picked = view.choices.iter().zip(number_keys.iter())
+                                     .find(|(_, key)| input.keyboard().was_key_released(**key))
+                                     .map(|((index, _), _)| *index);
+
+                    if picked.is_some() { ecs::advance_dialogue(world, picked); }
+                }
+            }
+        }
 
         //closure is needed so control_data can go out of scope and be barrowed again when running the system
         {
             let mut control_data = world.write_resource::<ControlData>();
 
-            let kb = kbm.keyboard();
-        
-            control_data.move_left  = kb.is_key_pressed(KeyCode::A) || kb.is_key_pressed(KeyCode::Left);
-            control_data.move_right = kb.is_key_pressed(KeyCode::D) || kb.is_key_pressed(KeyCode::Right);
-            control_data.move_up = kb.is_key_pressed(KeyCode::W) || kb.is_key_pressed(KeyCode::Up);
-            control_data.move_down = kb.is_key_pressed(KeyCode::S) || kb.is_key_pressed(KeyCode::Down);
+            for action in [InputAction::MoveUp, InputAction::MoveDown, InputAction::MoveLeft,
+                           InputAction::MoveRight, InputAction::Run].iter() {
+                control_data.hold(*action, !talking && bindings.is_held(*action, input));
+            }
+
+            //kept until an update handles it
+            if !talking && !scripted && self.game_menu.is_none() && self.overlay.is_none() && confirm_pressed {
+                control_data.press(InputAction::Confirm);
+            }
+        }
+
+        //scrolling the mouse wheel zooms the camera in and out
+        let wheel_movement = input.mouse().wheel_movement().vertical;
+        if wheel_movement != 0.0 {
+            let mut camera = world.write_resource::<ecs::Camera>();
+            let zoom = camera.zoom * (1.0 + wheel_movement * 0.1);
+            camera.set_zoom(zoom);
         }
 
+    }
+
+
+    //advances the world by one fixed step. Input handling runs first so movement uses the latest controls.
+    fn update(&mut self, _window: &Window) {
+        //the music is looked up again only when it changes
+        let wanted_music = self.wanted_music();
+        if wanted_music.as_deref().unwrap_or("") != self.music.name {
+            self.music = find_music(&mut self.world.write_resource::<AssetDatabase>(), wanted_music);
+        }
+
+        if self.game_menu.is_some() || self.overlay.is_some() { return; }
+
+        let mut world = &self.world;
+
+        //coffee runs updates at a fixed rate so every update is the same length, see GameClock
+        world.write_resource::<ecs::GameClock>().advance( 1.0 / f32::from(TICKS_PER_SECOND) );
+
         //run all systems related to input handling
         self.input_dispatcher.dispatch(&mut world);
+
+        //run systems that update the world state
+        self.data_dispatcher.dispatch(world);
+
+        //the dialogue box writes out what is being said
+        match world.read_resource::<ecs::ActiveDialogue>().is_active() {
+            true => self.dialogue_box.advance( 1.0 / f32::from(TICKS_PER_SECOND) ),
+            false => self.dialogue_box.clear(),
+        }
+
+        //entities scripts spawned or removed during the update
+        self.world.maintain();
+
+        //something on the map started a battle
+        let encounter = self.world.write_resource::<ecs::PendingBattle>().encounter.take();
+        if let Some(encounter) = encounter {
+            if let Some((battle, sprites)) = ecs::create_battle(&self.world, &encounter) {
+                let mut asset_database = self.world.write_resource::<AssetDatabase>();
+                let music = match asset_database.get_asset(&encounter) {
+                    AssetContainer::Encounter(encounter) => encounter.music.clone(),
+                    _ => None,
+                }.or_else(|| self.battle_music.clone());
+
+                let music = music.map(|music| find_music(&mut asset_database, Some(music)));
+                self.state_change = Some(StateChange::Push( Box::new(BattleState::new(battle, sprites, music)) ));
+            }
+        }
+
+        //an event script opened a shop, the script carries on once it is closed
+        let shop = self.world.write_resource::<ecs::PendingShop>().shop.take();
+        if let Some(shop) = shop {
+            self.overlay = Some( Overlay::Shop(ShopScreen::new(shop)) );
+        }
+
+        //a warp or an event script is moving the party to another map
+        ecs::update_scene(&mut self.world);
     }
 
 
@@ -87,11 +434,35 @@ impl GameState for PlayingState {
 
 
         let mut world = & self.world;
+
+        //the camera needs to know how much of the map fits on screen
+        let screen_size = (frame.width(), frame.height());
+        world.write_resource::<ecs::Camera>().screen_size = screen_size;
+
         self.render_dispatcher.dispatch(&mut world);
 
 
         let mut asset_database = world.write_resource::<AssetDatabase>();
 
+        //everything in the world is drawn through the camera
+        let mut target = frame.as_target();
+        let mut camera_target = target.transform( world.read_resource::<ecs::Camera>().transformation() );
+
+
+        //the current map has to be drawn first so it ends up underneath everything else. Each
+        //  batch is drawn on its own so tiles on higher layers cover the ones below.
+        let tile_batches = std::mem::take( &mut world.write_resource::<ecs::TileBatches>().batches );
+
+        for tile_batch in tile_batches.iter() {
+            if let AssetContainer::Spritesheet(tileset) = asset_database.get_asset(&tile_batch.sheet_name) {
+                for (position, (row, column)) in tile_batch.sprites.iter() {
+                    tileset.add_to_batch(*position, *row, *column);
+                }
+                tileset.batch.draw( &mut camera_target );
+                tileset.batch.clear();
+            }
+        }
+
 
         //TODO this isn't good. We should only iterate over assets that need to be drawn
         for (_, asset_container) in asset_database.get_asset_iter_mut() {
@@ -102,13 +473,280 @@ impl GameState for PlayingState {
                 //  container has an object that is renderable. Then just grab the batch and draw it.
                 //  I think Coffee implements a drawable trait so maybe we should just use that.
                 AssetContainer::Spritesheet(spritesheet) => {
-                    spritesheet.batch.draw( &mut frame.as_target() );
+                    spritesheet.batch.draw( &mut camera_target );
                     spritesheet.batch.clear();
                 },
 
-                _ => return
+                _ => continue
             };
 
         }
+
+        //fading out to and in from a map change covers everything
+        let fade = world.read_resource::<ecs::SceneManager>().fade();
+        if fade > 0.0 {
+            let mut mesh = Mesh::new();
+            mesh.fill( Shape::Rectangle( Rectangle { x: 0.0, y: 0.0, width: screen_size.0, height: screen_size.1 } ),
+                       Color { r: 0.0, g: 0.0, b: 0.0, a: fade } );
+            mesh.draw( &mut target );
+        }
+    }
+
+
+    fn react(&mut self, message: UIAction, window: &mut Window) -> Option< Box<dyn GameState> > {
+        match message {
+            UIAction::OpenGameMenu => self.game_menu = Some(SaveSlotMenu::new()),
+            UIAction::CloseGameMenu => self.game_menu = None,
+            UIAction::SaveToSlot(slot) => {
+                let mut save = self.create_save();
+                let status = match write_save_slot(slot, &mut save) {
+                    Ok(()) => format!("Saved to slot {}", slot + 1),
+                    Err(error) => {
+                        warn!("[Playing State] Could not save to slot {}: {}", slot + 1, error);
+                        error.to_string()
+                    }
+                };
+
+                if let Some(game_menu) = &mut self.game_menu {
+                    game_menu.refresh();
+                    game_menu.set_status(status);
+                }
+            },
+            UIAction::LoadFromSlot(slot) => {
+                let loaded = read_save_slot(slot)
+                    .and_then(|save| PlayingState::from_save(window.gpu(), &save));
+
+                match loaded {
+                    Ok(playing_state) => return Option::Some( Box::new(playing_state) ),
+                    Err(error) => {
+                        warn!("[Playing State] Could not load save slot {}: {}", slot + 1, error);
+                        if let Some(game_menu) = &mut self.game_menu {
+                            game_menu.set_status(error.to_string());
+                        }
+                    }
+                }
+            },
+            UIAction::OpenInventory => self.overlay = Some( Overlay::Inventory(InventoryScreen::new()) ),
+            UIAction::OpenEquipMenu => self.overlay = Some( Overlay::Equipment(EquipMenu::new()) ),
+            UIAction::OpenPartyMenu => self.overlay = Some( Overlay::Party(PartyMenu::new()) ),
+            UIAction::OpenQuestLog => self.overlay = Some( Overlay::Quests(QuestLogScreen::new()) ),
+            UIAction::CloseInventory | UIAction::CloseEquipMenu | UIAction::ClosePartyMenu
+            | UIAction::CloseQuestLog | UIAction::CloseShop => self.overlay = None,
+            UIAction::SelectItem(index) => {
+                if let Some(Overlay::Inventory(inventory_screen)) = &mut self.overlay {
+                    inventory_screen.select(index);
+                }
+            },
+            UIAction::UseItemOn(target_index) => {
+                let world = &self.world;
+                if let Some(Overlay::Inventory(inventory_screen)) = &mut self.overlay {
+                    let item = inventory_screen.selected()
+                        .and_then(|selected| ecs::inventory_view(world).into_iter().nth(selected));
+                    let target = ecs::item_targets(world).into_iter().nth(target_index);
+
+                    if let (Some(item), Some(target)) = (item, target) {
+                        let status = match ecs::use_item(world, &item.name, target.entity) {
+                            Ok(message) => message,
+                            Err(message) => message,
+                        };
+                        inventory_screen.set_status(status);
+                    }
+                }
+            },
+            UIAction::SelectEquipMember(member) => {
+                if let Some(Overlay::Equipment(equip_menu)) = &mut self.overlay {
+                    equip_menu.select_member(member);
+                }
+            },
+            UIAction::EquipItem(_) | UIAction::UnequipSlot(_) => {
+                let world = &self.world;
+                if let Some(Overlay::Equipment(equip_menu)) = &mut self.overlay {
+                    if let Some(view) = ecs::equip_view(world, equip_menu.member()) {
+                        let changed = match message {
+                            UIAction::EquipItem(index) => view.options.get(index)
+                                .map(|(item_name, _)| ecs::equip_item(world, view.entity, item_name)),
+                            UIAction::UnequipSlot(index) => view.slots.get(index)
+                                .map(|(slot, _)| ecs::unequip_slot(world, view.entity, slot)),
+                            _ => None,
+                        };
+
+                        match changed {
+                            Some(Ok(status)) | Some(Err(status)) => equip_menu.set_status(status),
+                            None => {},
+                        }
+                    }
+                }
+            },
+            UIAction::SelectPartyMember(member) => {
+                if let Some(Overlay::Party(party_menu)) = &mut self.overlay {
+                    if let Some((first, second)) = party_menu.pick(member) {
+                        ecs::swap_party_members(&self.world, first, second);
+                    }
+                }
+            },
+            UIAction::MovePartyMember => {
+                if let Some(Overlay::Party(party_menu)) = &mut self.overlay {
+                    if let Some(member) = party_menu.take_selected() {
+                        if let Err(status) = ecs::move_party_member(&self.world, member) {
+                            party_menu.set_status(status);
+                        }
+                    }
+                }
+            },
+            UIAction::SelectQuest(quest) => {
+                if let Some(Overlay::Quests(quest_log)) = &mut self.overlay {
+                    quest_log.select(quest);
+                }
+            },
+            UIAction::ShowShopStock | UIAction::ShowPartyItems => {
+                if let Some(Overlay::Shop(shop_screen)) = &mut self.overlay {
+                    shop_screen.show_selling( matches!(message, UIAction::ShowPartyItems) );
+                }
+            },
+            UIAction::SelectShopItem(index) => {
+                if let Some(Overlay::Shop(shop_screen)) = &mut self.overlay {
+                    shop_screen.select(index);
+                }
+            },
+            UIAction::ChangeShopQuantity(change) => {
+                if let Some(Overlay::Shop(shop_screen)) = &mut self.overlay {
+                    shop_screen.change_quantity(change);
+                }
+            },
+            UIAction::TradeShopItem => {
+                let world = &self.world;
+                if let Some(Overlay::Shop(shop_screen)) = &mut self.overlay {
+                    let view = ecs::shop_view(world, shop_screen.shop());
+                    let entry = view.and_then(|view| {
+                        let entries = if shop_screen.is_selling() { view.selling } else { view.buying };
+                        shop_screen.selected().and_then(|selected| entries.into_iter().nth(selected))
+                    });
+
+                    if let Some(entry) = entry {
+                        let traded = match shop_screen.is_selling() {
+                            true => ecs::sell_item(world, shop_screen.shop(), &entry.name, shop_screen.quantity()),
+                            false => ecs::buy_item(world, shop_screen.shop(), &entry.name, shop_screen.quantity()),
+                        };
+
+                        match traded {
+                            Ok(status) | Err(status) => shop_screen.set_status(status),
+                        }
+                    }
+                }
+            },
+            UIAction::AdvanceDialogue if self.dialogue_box.is_writing() => self.dialogue_box.finish_writing(),
+            UIAction::AdvanceDialogue => ecs::advance_dialogue(&self.world, None),
+            UIAction::ChooseDialogueOption(index) => ecs::advance_dialogue(&self.world, Some(index)),
+            UIAction::ReturnToMainMenu => return Option::Some( Box::new(MainMenuState::new()) ),
+            _ => {},
+        };
+
+        Option::None
+    }
+
+
+    fn layout(&mut self, window: &Window) -> Element<'_, UIAction> {
+        let world = &self.world;
+        match &mut self.overlay {
+            Some(Overlay::Inventory(inventory_screen)) => 
+                return inventory_screen.layout(window, ecs::inventory_view(world), ecs::item_targets(world)),
+            Some(Overlay::Equipment(equip_menu)) => {
+                let members = ecs::item_targets(world).into_iter().map(|member| member.name).collect();
+                return equip_menu.layout(window, members, ecs::equip_view(world, equip_menu.member()));
+            },
+            Some(Overlay::Party(party_menu)) => return party_menu.layout(window, ecs::party_view(world)),
+            Some(Overlay::Quests(quest_log)) => return quest_log.layout(window, ecs::quest_log_view(world)),
+            Some(Overlay::Shop(shop_screen)) => {
+                let view = ecs::shop_view(world, shop_screen.shop());
+                return shop_screen.layout(window, view);
+            },
+            None => {},
+        }
+
+        match &mut self.game_menu {
+            Some(game_menu) => Row::new()
+                .spacing( (window.width()/16.0) as u16 )
+                .push(
+                    //Adds in some horizontal spacing.
+                    Column::new().width( (window.width()/16.0) as u32)
+                )
+                .push( game_menu.layout(window, true, "Resume", UIAction::CloseGameMenu) )
+                .push( Button::new(&mut self.main_menu_button, "Quit to Main Menu")
+                        .on_press(UIAction::ReturnToMainMenu)
+                )
+                .into(),
+            None => {
+                let mut hud = Column::new().padding(10);
+
+                //menus can't be opened while an event script is running or the map is changing
+                if !world.read_resource::<ecs::EventQueue>().is_running()
+                   && !world.read_resource::<ecs::SceneManager>().is_changing() {
+                    hud = hud.push( Row::new()
+                            .spacing(10)
+                            .push( Button::new(&mut self.menu_button, "Menu")
+                                    .on_press(UIAction::OpenGameMenu)
+                            )
+                            .push( Button::new(&mut self.items_button, "Items")
+                                    .on_press(UIAction::OpenInventory)
+                            )
+                            .push( Button::new(&mut self.equip_button, "Equip")
+                                    .on_press(UIAction::OpenEquipMenu)
+                            )
+                            .push( Button::new(&mut self.party_button, "Party")
+                                    .on_press(UIAction::OpenPartyMenu)
+                            )
+                            .push( Button::new(&mut self.quests_button, "Quests")
+                                    .on_press(UIAction::OpenQuestLog)
+                            )
+                    );
+                }
+
+                //the dialogue box sits along the bottom of the screen
+                if let Some(view) = ecs::dialogue_view(&self.world) {
+                    hud = hud
+                        .push( Column::new().height( (window.height()*0.6) as u32 ) )
+                        .push( self.dialogue_box.layout(window, view) );
+                }
+
+                hud.into()
+            },
+        }
+    }
+
+
+    fn take_state_change(&mut self) -> Option<StateChange> {
+        self.state_change.take()
+    }
+
+
+    fn music(&self) -> Option<&MusicPlaylist> {
+        Some(&self.music)
+    }
+
+
+    fn resume(&mut self, result: StateResult) {
+        match result {
+            StateResult::Battle(battle_result) => ecs::apply_battle_result(&self.world, &battle_result),
+        }
+
+        //keys held when the battle started may have been let go since
+        self.world.write_resource::<ControlData>().clear();
+    }
+}
+
+
+//the music asset with this name, silence when there is no name or no such asset
+fn find_music(asset_database: &mut AssetDatabase, name: Option<String>) -> MusicPlaylist {
+    let name = match name {
+        Some(name) => name,
+        None => return MusicPlaylist::silence(String::new()),
+    };
+
+    match asset_database.get_asset(&name) {
+        AssetContainer::Music(playlist) => playlist.clone(),
+        _ => {
+            warn!("[Playing State] Requested music {} does not exist.", name);
+            MusicPlaylist::silence(name)
+        }
     }
 }
@@ -0,0 +1,100 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Row, Element, Text},
+};
+
+use super::UIAction;
+
+use crate::ecs::QuestEntry;
+
+
+//Lists the quests the party has taken on. Picking one shows what it is about and what is left
+//  to do for the stage the party is on.
+pub struct QuestLogScreen {
+    selected: Option<usize>,
+
+    quest_buttons: Vec<button::State>,
+    close_button: button::State,
+}
+
+impl QuestLogScreen {
+
+    pub fn new() -> QuestLogScreen {
+        QuestLogScreen {
+            selected: None,
+
+            quest_buttons: vec![],
+            close_button: button::State::new(),
+        }
+    }
+
+    pub fn select(&mut self, quest: usize) {
+        self.selected = Some(quest);
+    }
+
+
+    pub fn layout(&mut self, window: &Window, entries: Vec<QuestEntry>) -> Element<'_, UIAction> {
+        while self.quest_buttons.len() < entries.len() {
+            self.quest_buttons.push(button::State::new());
+        }
+
+        let mut quest_list = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new("Quests").size(40) );
+
+        if entries.is_empty() {
+            quest_list = quest_list.push( Text::new("The party hasn't taken on any quests.") );
+        }
+
+        for (index, (entry, state)) in entries.iter().zip(self.quest_buttons.iter_mut()).enumerate() {
+            let label = if entry.completed { format!("{} (done)", entry.title) } else { entry.title.clone() };
+
+            quest_list = quest_list.push(
+                Button::new(state, &label)
+                    .fill_width()
+                    .on_press(UIAction::SelectQuest(index))
+            );
+        }
+
+        quest_list = quest_list.push(
+            Button::new(&mut self.close_button, "Close")
+                .fill_width()
+                .on_press(UIAction::CloseQuestLog)
+        );
+
+        let mut details = Column::new()
+            .width( (window.width()/2.5) as u32 )
+            .spacing(10);
+
+        if let Some(entry) = self.selected.and_then(|selected| entries.get(selected)) {
+            details = details
+                .push( Text::new(&entry.title).size(30) )
+                .push( Text::new(&entry.description) );
+
+            if entry.completed {
+                details = details.push( Text::new("Complete!") );
+            } else {
+                if !entry.stage.is_empty() {
+                    details = details.push( Text::new(&entry.stage) );
+                }
+
+                for (objective, done, needed) in entry.objectives.iter() {
+                    let line = match (*done >= *needed, *needed > 1) {
+                        (true, _) => format!("{} - done", objective),
+                        (false, true) => format!("{} - {}/{}", objective, done, needed),
+                        (false, false) => objective.clone(),
+                    };
+                    details = details.push( Text::new(&line) );
+                }
+            }
+        }
+
+        Row::new()
+            .padding( (window.width()/32.0) as u32 )
+            .spacing( (window.width()/16.0) as u16 )
+            .push(quest_list)
+            .push(details)
+            .into()
+    }
+}
@@ -0,0 +1,85 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Element, Text},
+};
+
+use super::UIAction;
+
+use crate::save_game::{SlotSummary, summarize_save_slots};
+
+
+//List of save slots shared by the load game screen and the in game menu. Each slot has a load
+//  button and, when saving is allowed, a save button.
+pub struct SaveSlotMenu {
+    slots: Vec<SlotSummary>,
+    status: Option<String>, //result of the last save or load, shown under the slots
+
+    save_buttons: Vec<button::State>,
+    load_buttons: Vec<button::State>,
+    back_button: button::State,
+}
+
+impl SaveSlotMenu {
+
+    pub fn new() -> SaveSlotMenu {
+        let slots = summarize_save_slots();
+
+        SaveSlotMenu {
+            save_buttons: slots.iter().map(|_| button::State::new()).collect(),
+            load_buttons: slots.iter().map(|_| button::State::new()).collect(),
+            slots,
+            status: None,
+
+            back_button: button::State::new(),
+        }
+    }
+
+    //reads the slots again, needed after a save changes one
+    pub fn refresh(&mut self) {
+        self.slots = summarize_save_slots();
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+
+    pub fn layout(&mut self, window: &Window, allow_saving: bool, back_text: &str, back_action: UIAction) 
+    -> Element<'_, UIAction> {
+        let mut menu = Column::new()
+            .width( (window.width()/3.0) as u32)
+            .spacing(10)
+            .push( Text::new(if allow_saving { "Save or Load" } else { "Load Game" }).size(40) );
+
+        let slots = self.slots.iter().zip(self.save_buttons.iter_mut().zip(self.load_buttons.iter_mut()));
+
+        for (index, (slot, (save_state, load_state))) in slots.enumerate() {
+            menu = menu.push( Text::new(&slot.description) );
+
+            if allow_saving {
+                menu = menu.push(
+                    Button::new(save_state, "Save")
+                        .fill_width()
+                        .on_press(UIAction::SaveToSlot(index))
+                );
+            }
+
+            //buttons without an action are shown disabled
+            let load_button = Button::new(load_state, "Load").fill_width();
+            menu = menu.push(
+                if slot.loadable { load_button.on_press(UIAction::LoadFromSlot(index)) } else { load_button }
+            );
+        }
+
+        if let Some(status) = &self.status {
+            menu = menu.push( Text::new(status) );
+        }
+
+        menu.push(
+                Button::new(&mut self.back_button, back_text)
+                    .fill_width()
+                    .on_press(back_action)
+            )
+            .into()
+    }
+}
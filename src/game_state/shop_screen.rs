@@ -0,0 +1,207 @@
+use coffee::{
+    graphics::Window,
+    ui::{button, Button, Column, Row, Element, Text, Image},
+};
+
+use super::UIAction;
+
+use crate::ecs::{ShopView, ShopEntry};
+
+
+//Lets the party buy a shop's items or sell their own. Picking an item shows how it compares to
+//  what the party has, and how many to trade is picked before buying or selling.
+pub struct ShopScreen {
+    shop: String, //name of the shop asset
+    selling: bool, //showing the party's items instead of the shop's
+    selected: Option<usize>,
+    quantity: u32,
+    status: String,
+
+    buy_tab_button: button::State,
+    sell_tab_button: button::State,
+    item_buttons: Vec<button::State>,
+    fewer_button: button::State,
+    more_button: button::State,
+    trade_button: button::State,
+    close_button: button::State,
+}
+
+impl ShopScreen {
+
+    pub fn new(shop: String) -> ShopScreen {
+        ShopScreen {
+            shop,
+            selling: false,
+            selected: None,
+            quantity: 1,
+            status: String::new(),
+
+            buy_tab_button: button::State::new(),
+            sell_tab_button: button::State::new(),
+            item_buttons: vec![],
+            fewer_button: button::State::new(),
+            more_button: button::State::new(),
+            trade_button: button::State::new(),
+            close_button: button::State::new(),
+        }
+    }
+
+    pub fn shop(&self) -> &str {
+        &self.shop
+    }
+
+    pub fn is_selling(&self) -> bool {
+        self.selling
+    }
+
+    pub fn show_selling(&mut self, selling: bool) {
+        self.selling = selling;
+        self.selected = None;
+        self.quantity = 1;
+        self.status.clear();
+    }
+
+    pub fn select(&mut self, item: usize) {
+        self.selected = Some(item);
+        self.quantity = 1;
+        self.status.clear();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+
+    //never below one, the most that can be traded is worked out when the screen is drawn
+    pub fn change_quantity(&mut self, change: i32) {
+        self.quantity = (i64::from(self.quantity) + i64::from(change)).clamp(1, i64::from(u32::MAX)) as u32;
+    }
+
+    //shown under the item details, like what was just bought
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+
+    pub fn layout(&mut self, window: &Window, view: Option<ShopView>) -> Element<'_, UIAction> {
+        let view = match view {
+            Some(view) => view,
+            None => return Column::new()
+                .padding( (window.width()/32.0) as u32 )
+                .spacing(10)
+                .push( Text::new("The shop is closed.") )
+                .push( Button::new(&mut self.close_button, "Close").on_press(UIAction::CloseShop) )
+                .into(),
+        };
+
+        let icon_size = (window.height()/24.0) as u32;
+        let entries: &Vec<ShopEntry> = if self.selling { &view.selling } else { &view.buying };
+
+        //the last of an item may have just been sold
+        if self.selected.map(|selected| selected >= entries.len()).unwrap_or(false) {
+            self.selected = None;
+        }
+
+        while self.item_buttons.len() < entries.len() {
+            self.item_buttons.push(button::State::new());
+        }
+
+        let mut item_list = Column::new()
+            .width( (window.width()/3.0) as u32 )
+            .spacing(5)
+            .push( Text::new(&view.title).size(40) )
+            .push( Text::new(&format!("Gold: {}", view.gold)) )
+            .push( Row::new()
+                .spacing(10)
+                .push( Button::new(&mut self.buy_tab_button, "Buy").on_press(UIAction::ShowShopStock) )
+                .push( Button::new(&mut self.sell_tab_button, "Sell").on_press(UIAction::ShowPartyItems) )
+            );
+
+        if entries.is_empty() {
+            item_list = item_list.push( Text::new(if self.selling { "The party has nothing to sell." }
+                                                  else { "Everything has been sold." }) );
+        }
+
+        for (index, (entry, state)) in entries.iter().zip(self.item_buttons.iter_mut()).enumerate() {
+            let label = match (self.selling, entry.available) {
+                (false, Some(0)) => format!("{} - sold out", entry.name),
+                (false, Some(left)) => format!("{} - {} gold ({} left)", entry.name, entry.price, left),
+                (true, Some(count)) => format!("{} x{} - {} gold", entry.name, count, entry.price),
+                (_, None) => format!("{} - {} gold", entry.name, entry.price),
+            };
+
+            let mut row = Row::new().spacing(10);
+            if let Some((image, source)) = &entry.icon {
+                row = row.push( Image::new(image).clip(*source).width(icon_size).height(icon_size) );
+            }
+
+            item_list = item_list.push(
+                row.push(
+                    Button::new(state, &label)
+                        .fill_width()
+                        .on_press(UIAction::SelectShopItem(index))
+                )
+            );
+        }
+
+        item_list = item_list.push(
+            Button::new(&mut self.close_button, "Close")
+                .fill_width()
+                .on_press(UIAction::CloseShop)
+        );
+
+        let mut details = Column::new()
+            .width( (window.width()/2.5) as u32 )
+            .spacing(10);
+
+        if let Some(entry) = self.selected.and_then(|selected| entries.get(selected)) {
+            //as many as the shop or party has, and when buying as many as the party can afford
+            let mut most = entry.available.unwrap_or(u32::MAX);
+            if !self.selling {
+                most = most.min( view.gold.checked_div(entry.price).unwrap_or(u32::MAX) );
+            }
+            self.quantity = self.quantity.min(most.max(1));
+
+            if let Some((image, source)) = &entry.icon {
+                details = details.push( Image::new(image).clip(*source).width(icon_size*2).height(icon_size*2) );
+            }
+
+            details = details
+                .push( Text::new(&entry.name).size(30) )
+                .push( Text::new(&entry.description) )
+                .push( Text::new(&format!("The party is carrying {}.", entry.carried)) );
+
+            for line in entry.comparison.iter() {
+                details = details.push( Text::new(line) );
+            }
+
+            let total = entry.price.saturating_mul(self.quantity);
+            details = details
+                .push( Row::new()
+                    .spacing(10)
+                    .push( Button::new(&mut self.fewer_button, "-").on_press(UIAction::ChangeShopQuantity(-1)) )
+                    .push( Text::new(&format!("{}", self.quantity)) )
+                    .push( Button::new(&mut self.more_button, "+").on_press(UIAction::ChangeShopQuantity(1)) )
+                )
+                .push( Button::new(&mut self.trade_button,
+                                   &format!("{} for {} gold", if self.selling { "Sell" } else { "Buy" }, total))
+                        .fill_width()
+                        .on_press(UIAction::TradeShopItem)
+                );
+        }
+
+        if !self.status.is_empty() {
+            details = details.push( Text::new(&self.status) );
+        }
+
+        Row::new()
+            .padding( (window.width()/32.0) as u32 )
+            .spacing( (window.width()/16.0) as u16 )
+            .push(item_list)
+            .push(details)
+            .into()
+    }
+}
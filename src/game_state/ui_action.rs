@@ -1,3 +1,7 @@
+use crate::assets::audio::ClipCategory;
+use crate::settings::TextSpeed;
+
+
 
 
 // Possible UI actions that can be triggered 
@@ -10,4 +14,76 @@ pub enum UIAction {
     NewGame,
     LoadGame,
     QuitGame,
+
+    //campaign selection
+    SelectCampaign(usize),
+    StartCampaign,
+    ReturnToMainMenu,
+
+    //controls
+    OpenControls,
+    RebindAction(usize), //index into InputAction::ALL
+    CancelRebind,
+    ResetControls,
+
+    //options
+    OpenOptions,
+    CloseOptions,
+    SetVolume(Option<ClipCategory>, f32), //None is the master volume
+    SetFullscreen(bool),
+    SetWindowSize(usize), //index into WINDOW_SIZES
+    SetTextSpeed(TextSpeed),
+
+    //saving and loading
+    OpenGameMenu,
+    CloseGameMenu,
+    SaveToSlot(usize),
+    LoadFromSlot(usize),
+
+    //conversations
+    AdvanceDialogue,
+    ChooseDialogueOption(usize),
+
+    //items
+    OpenInventory,
+    CloseInventory,
+    SelectItem(usize),
+    UseItemOn(usize), //index of the party member to use the selected item on
+
+    //equipment
+    OpenEquipMenu,
+    CloseEquipMenu,
+    SelectEquipMember(usize),
+    EquipItem(usize),
+    UnequipSlot(usize),
+
+    //party
+    OpenPartyMenu,
+    ClosePartyMenu,
+    SelectPartyMember(usize),
+    MovePartyMember, //between the active members and the reserve
+
+    //quests
+    OpenQuestLog,
+    CloseQuestLog,
+    SelectQuest(usize),
+
+    //shops
+    CloseShop,
+    ShowShopStock,
+    ShowPartyItems, //what the party could sell
+    SelectShopItem(usize),
+    ChangeShopQuantity(i32), //how many to buy or sell
+    TradeShopItem,
+
+    //battles
+    BattleAttack,
+    BattleSkill,
+    BattleItem,
+    BattleDefend,
+    BattleFlee,
+    BattleUseItem(usize),
+    BattleTarget(usize),
+    BattleCancel,
+    BattleContinue,
 }
@@ -0,0 +1,237 @@
+use std::collections::{HashMap, BTreeMap};
+
+use serde::{Serialize, Deserialize};
+
+use coffee::input::{keyboard::KeyCode, gamepad::Button};
+
+use super::{InputAction, GameInput};
+
+
+//A key or gamepad button an action can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(Button),
+}
+
+//keys that can be bound, named in the settings file the way they are written here
+const BINDABLE_KEYS: [KeyCode; 76] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8,
+    KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Escape, KeyCode::Space, KeyCode::Return, KeyCode::Tab, KeyCode::Back, KeyCode::Delete, KeyCode::Insert,
+    KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::LShift, KeyCode::RShift, KeyCode::LControl, KeyCode::RControl, KeyCode::LAlt, KeyCode::RAlt,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon, KeyCode::Apostrophe,
+    KeyCode::Minus, KeyCode::Equals,
+];
+
+const BINDABLE_BUTTONS: [Button; 17] = [
+    Button::South, Button::East, Button::North, Button::West,
+    Button::LeftTrigger, Button::LeftTrigger2, Button::RightTrigger, Button::RightTrigger2,
+    Button::Select, Button::Start, Button::Mode, Button::LeftThumb, Button::RightThumb,
+    Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight,
+];
+
+//gamepad buttons are written with this in front so "Start" can't be mistaken for a key
+const BUTTON_PREFIX: &str = "Pad ";
+
+impl Binding {
+
+    //how the binding is shown to the player and written in the settings file
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Button(button) => format!("{}{:?}", BUTTON_PREFIX, button),
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Binding> {
+        let name = name.trim();
+        let matches = |debug_name: String, wanted: &str| debug_name.eq_ignore_ascii_case(wanted.trim());
+
+        match name.strip_prefix(BUTTON_PREFIX) {
+            Some(button_name) => BINDABLE_BUTTONS.iter()
+                .find(|button| matches(format!("{:?}", button), button_name))
+                .map(|button| Binding::Button(*button)),
+            None => BINDABLE_KEYS.iter()
+                .find(|key| matches(format!("{:?}", key), name))
+                .map(|key| Binding::Key(*key)),
+        }
+    }
+
+    pub fn is_gamepad(self) -> bool {
+        matches!(self, Binding::Button(_))
+    }
+}
+
+
+//Which keys and buttons do what. An action can have any number of bindings, and each key or
+//  button only ever does one thing. Kept in the settings file as binding names for each action.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, Vec<String>>", into = "BTreeMap<String, Vec<String>>")]
+pub struct InputBindings {
+    bindings: HashMap<InputAction, Vec<Binding>>,
+}
+
+impl InputBindings {
+
+    //the default controls, WASD or the arrow keys to move and a standard gamepad layout
+    pub fn new() -> InputBindings {
+        use InputAction::*;
+        use Binding::{Key, Button as Pad};
+
+        let defaults = vec![
+            (MoveUp, vec![ Key(KeyCode::W), Key(KeyCode::Up), Pad(Button::DPadUp) ]),
+            (MoveDown, vec![ Key(KeyCode::S), Key(KeyCode::Down), Pad(Button::DPadDown) ]),
+            (MoveLeft, vec![ Key(KeyCode::A), Key(KeyCode::Left), Pad(Button::DPadLeft) ]),
+            (MoveRight, vec![ Key(KeyCode::D), Key(KeyCode::Right), Pad(Button::DPadRight) ]),
+            (Run, vec![ Key(KeyCode::LShift), Pad(Button::West) ]),
+            (Confirm, vec![ Key(KeyCode::E), Key(KeyCode::Space), Key(KeyCode::Return), Pad(Button::South) ]),
+            (Cancel, vec![ Key(KeyCode::Back), Pad(Button::East) ]),
+            (Menu, vec![ Key(KeyCode::Escape), Pad(Button::Start) ]),
+            (Inventory, vec![ Key(KeyCode::I), Pad(Button::North) ]),
+        ];
+
+        InputBindings {
+            bindings: defaults.into_iter().collect(),
+        }
+    }
+
+    pub fn bindings_for(&self, action: InputAction) -> &[Binding] {
+        self.bindings.get(&action).map(|bindings| bindings.as_slice()).unwrap_or(&[])
+    }
+
+    //"W, Up, Pad DPadUp" for showing on the controls screen
+    pub fn describe(&self, action: InputAction) -> String {
+        match self.bindings_for(action) {
+            [] => "not bound".to_string(),
+            bindings => bindings.iter().map(|binding| binding.name()).collect::<Vec<String>>().join(", "),
+        }
+    }
+
+    pub fn is_held(&self, action: InputAction, input: &GameInput) -> bool {
+        self.bindings_for(action).iter().any(|binding| input.is_held(*binding))
+    }
+
+    //pressed and let go since the last interaction
+    pub fn was_pressed(&self, action: InputAction, input: &GameInput) -> bool {
+        self.bindings_for(action).iter().any(|binding| input.was_released(*binding))
+    }
+
+    //binds a key to an action in place of its other keys, or a button in place of its other
+    //  buttons. Whatever the binding did before it no longer does.
+    pub fn rebind(&mut self, action: InputAction, binding: Binding) {
+        for bindings in self.bindings.values_mut() {
+            bindings.retain(|bound| *bound != binding);
+        }
+
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|bound| bound.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+
+
+    //builds bindings from the settings file's names. Actions the file leaves out keep their
+    //  defaults and names that can't be read are skipped.
+    fn from_names(names: &BTreeMap<String, Vec<String>>) -> InputBindings {
+        let mut input_bindings = InputBindings::new();
+
+        for (action_name, binding_names) in names.iter() {
+            let action = match InputAction::from_name(action_name) {
+                Some(action) => action,
+                None => {
+                    warn!("[Input] Settings file has controls for {} which isn't an action.", action_name);
+                    continue;
+                }
+            };
+
+            let bindings = binding_names.iter().filter_map(|binding_name| {
+                let binding = Binding::parse(binding_name);
+                if binding.is_none() {
+                    warn!("[Input] Settings file binds {} to {} which isn't a known key or button.",
+                          action_name, binding_name);
+                }
+                binding
+            }).collect();
+
+            input_bindings.bindings.insert(action, bindings);
+        }
+
+        input_bindings
+    }
+
+    fn to_names(&self) -> BTreeMap<String, Vec<String>> {
+        InputAction::ALL.iter().map(|action| (
+            action.name().to_string(),
+            self.bindings_for(*action).iter().map(|binding| binding.name()).collect(),
+        )).collect()
+    }
+}
+
+
+impl From<BTreeMap<String, Vec<String>>> for InputBindings {
+    fn from(names: BTreeMap<String, Vec<String>>) -> InputBindings {
+        InputBindings::from_names(&names)
+    }
+}
+
+impl From<InputBindings> for BTreeMap<String, Vec<String>> {
+    fn from(bindings: InputBindings) -> BTreeMap<String, Vec<String>> {
+        bindings.to_names()
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_names_read_back() {
+        for binding in [Binding::Key(KeyCode::LShift), Binding::Key(KeyCode::Key1), Binding::Button(Button::Start)].iter() {
+            assert_eq!(Binding::parse(&binding.name()), Some(*binding));
+        }
+
+        assert_eq!(Binding::parse(" space "), Some(Binding::Key(KeyCode::Space)));
+        assert_eq!(Binding::parse("Pad south"), Some(Binding::Button(Button::South)));
+        assert_eq!(Binding::parse("Start"), None);
+    }
+
+    #[test]
+    fn rebinding_moves_a_binding_to_one_action() {
+        let mut bindings = InputBindings::new();
+
+        bindings.rebind(InputAction::Confirm, Binding::Key(KeyCode::W));
+        assert_eq!(bindings.bindings_for(InputAction::Confirm),
+                   &[Binding::Button(Button::South), Binding::Key(KeyCode::W)]);
+        assert_eq!(bindings.bindings_for(InputAction::MoveUp),
+                   &[Binding::Key(KeyCode::Up), Binding::Button(Button::DPadUp)]);
+
+        bindings.rebind(InputAction::Confirm, Binding::Button(Button::East));
+        assert_eq!(bindings.bindings_for(InputAction::Confirm),
+                   &[Binding::Key(KeyCode::W), Binding::Button(Button::East)]);
+        assert_eq!(bindings.describe(InputAction::Cancel), "Back");
+    }
+
+    #[test]
+    fn settings_only_change_the_controls_they_name() {
+        let mut names = BTreeMap::new();
+        names.insert("run".to_string(), vec!["RShift".to_string(), "Jump Key".to_string()]);
+        names.insert("Cancel".to_string(), vec![]);
+        names.insert("Fly".to_string(), vec!["F".to_string()]);
+
+        let bindings = InputBindings::from_names(&names);
+        assert_eq!(bindings.bindings_for(InputAction::Run), &[Binding::Key(KeyCode::RShift)]);
+        assert_eq!(bindings.describe(InputAction::Cancel), "not bound");
+        assert_eq!(bindings.bindings_for(InputAction::Menu), InputBindings::new().bindings_for(InputAction::Menu));
+
+        assert_eq!(InputBindings::from_names(&bindings.to_names()).to_names(), bindings.to_names());
+    }
+}
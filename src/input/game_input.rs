@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use coffee::input::{
+    self, Input, ButtonState, KeyboardAndMouse, Keyboard, Mouse,
+    keyboard,
+    gamepad::{self, Axis, Button},
+};
+
+use super::Binding;
+
+
+//how far a stick has to be pushed before it counts as a d-pad press
+const STICK_DEADZONE: f32 = 0.5;
+
+
+//Input handed to the game each interaction. Adds gamepads to coffee's keyboard and mouse
+//  tracking. The left stick acts like the d-pad so movement bindings work with either.
+pub struct GameInput {
+    keyboard_and_mouse: KeyboardAndMouse,
+    held_buttons: HashSet<Button>,
+    stick_buttons: HashSet<Button>, //d-pad directions the left stick is pushed towards
+    released_buttons: HashSet<Button>,
+    last_pressed: Option<Binding>, //first key or button pressed since the last interaction
+}
+
+impl GameInput {
+
+    pub fn keyboard(&self) -> &Keyboard {
+        self.keyboard_and_mouse.keyboard()
+    }
+
+    pub fn mouse(&self) -> &Mouse {
+        self.keyboard_and_mouse.mouse()
+    }
+
+    pub fn is_held(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keyboard().is_key_pressed(key),
+            Binding::Button(button) => self.held_buttons.contains(&button) || self.stick_buttons.contains(&button),
+        }
+    }
+
+    //pressed and let go since the last interaction, like a click
+    pub fn was_released(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keyboard().was_key_released(key),
+            Binding::Button(button) => self.released_buttons.contains(&button),
+        }
+    }
+
+    //used when the player is picking a new binding
+    pub fn last_pressed(&self) -> Option<Binding> {
+        self.last_pressed
+    }
+
+    //pushing the stick past the deadzone presses a d-pad direction, letting it back releases it
+    fn move_stick(&mut self, negative: Button, positive: Button, value: f32) {
+        for (button, pushed) in [(negative, value < -STICK_DEADZONE), (positive, value > STICK_DEADZONE)].iter() {
+            if *pushed {
+                let _ = self.stick_buttons.insert(*button);
+            } else if self.stick_buttons.remove(button) {
+                let _ = self.released_buttons.insert(*button);
+            }
+        }
+    }
+}
+
+impl Input for GameInput {
+
+    fn new() -> GameInput {
+        GameInput {
+            keyboard_and_mouse: KeyboardAndMouse::new(),
+            held_buttons: HashSet::new(),
+            stick_buttons: HashSet::new(),
+            released_buttons: HashSet::new(),
+            last_pressed: None,
+        }
+    }
+
+    fn update(&mut self, event: input::Event) {
+        self.keyboard_and_mouse.update(event);
+
+        match event {
+            input::Event::Keyboard(keyboard::Event::Input { key_code, state: ButtonState::Pressed }) => {
+                self.last_pressed = self.last_pressed.or( Some(Binding::Key(key_code)) );
+            },
+            input::Event::Gamepad { event, .. } => match event {
+                gamepad::Event::ButtonPressed(button) => {
+                    let _ = self.held_buttons.insert(button);
+                    self.last_pressed = self.last_pressed.or( Some(Binding::Button(button)) );
+                },
+                gamepad::Event::ButtonReleased(button) => {
+                    let _ = self.held_buttons.remove(&button);
+                    let _ = self.released_buttons.insert(button);
+                },
+                //gilrs counts up as positive on the y axis
+                gamepad::Event::AxisChanged(Axis::LeftStickX, value) => self.move_stick(Button::DPadLeft, Button::DPadRight, value),
+                gamepad::Event::AxisChanged(Axis::LeftStickY, value) => self.move_stick(Button::DPadDown, Button::DPadUp, value),
+                //a gamepad unplugged mid press shouldn't leave the party walking
+                gamepad::Event::Disconnected => {
+                    self.held_buttons.clear();
+                    self.stick_buttons.clear();
+                },
+                _ => {},
+            },
+            _ => {},
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keyboard_and_mouse.clear();
+        self.released_buttons.clear();
+        self.last_pressed = None;
+    }
+}
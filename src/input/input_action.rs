@@ -0,0 +1,49 @@
+//Things the player can do. Keys and gamepad buttons are bound to these so the rest of the game
+//  never has to know which one was pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Run, //held while moving to move faster
+    Confirm, //interacting with things and moving conversations along
+    Cancel, //closes whatever menu is open
+    Menu, //opens and closes the game menu
+    Inventory,
+}
+
+impl InputAction {
+
+    //every action in the order the controls screen lists them
+    pub const ALL: [InputAction; 9] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::Run,
+        InputAction::Confirm,
+        InputAction::Cancel,
+        InputAction::Menu,
+        InputAction::Inventory,
+    ];
+
+    //shown to the player and used in the settings file
+    pub fn name(self) -> &'static str {
+        match self {
+            InputAction::MoveUp => "Move Up",
+            InputAction::MoveDown => "Move Down",
+            InputAction::MoveLeft => "Move Left",
+            InputAction::MoveRight => "Move Right",
+            InputAction::Run => "Run",
+            InputAction::Confirm => "Confirm",
+            InputAction::Cancel => "Cancel",
+            InputAction::Menu => "Menu",
+            InputAction::Inventory => "Inventory",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<InputAction> {
+        InputAction::ALL.iter().copied().find(|action| action.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
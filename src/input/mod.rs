@@ -0,0 +1,11 @@
+//pull in the things the player can do
+mod input_action;
+pub use input_action::InputAction;
+
+//pull in the keys and buttons bound to each action
+mod bindings;
+pub use bindings::{Binding, InputBindings};
+
+//pull in the input coffee hands the game every interaction
+mod game_input;
+pub use game_input::GameInput;
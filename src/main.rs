@@ -4,31 +4,65 @@ extern crate simplelog;
 mod assets;
 mod game_state;
 mod ecs;
+mod save_game;
+mod battle;
+mod input;
+mod settings;
 
 
 use coffee::{
     graphics::{Frame, Window, WindowSettings},
     load::{Task},
-    input::KeyboardAndMouse,
     ui::{UserInterface, Renderer, Element},
     Game, Timer
 };
 
-use game_state::{UIAction, GameState, MainMenuState};
+use assets::audio::MusicManager;
+use input::GameInput;
+use game_state::{UIAction, GameState, StateChange, MainMenuState, TICKS_PER_SECOND};
 
 
 struct Application {
     current_game_state: Box<dyn GameState>,
+    suspended_game_states: Vec<Box<dyn GameState>>, //states paused under the current one, most recent last
+    music: MusicManager, //kept here so music carries on while states change
+}
+
+impl Application {
+
+    //pushes or pops states when the current one asks for it
+    fn apply_state_change(&mut self) {
+        match self.current_game_state.take_state_change() {
+            Option::Some(StateChange::Push(new_state)) => {
+                let paused_state = std::mem::replace(&mut self.current_game_state, new_state);
+                self.suspended_game_states.push(paused_state);
+            },
+            Option::Some(StateChange::Pop(result)) => {
+                match self.suspended_game_states.pop() {
+                    Option::Some(paused_state) => {
+                        self.current_game_state = paused_state;
+                        self.current_game_state.resume(result);
+                    },
+                    Option::None => warn!("[Application] A state tried to return but nothing is underneath it."),
+                }
+            },
+            Option::None => {},
+        }
+    }
 }
 
 impl Game for Application {
-    type Input = KeyboardAndMouse;
+    type Input = GameInput;
     type LoadingScreen = (); // No loading screen
 
+    const TICKS_PER_SECOND: u16 = TICKS_PER_SECOND;
+
     fn load(_window: &Window) -> Task<Application> {
         Task::succeed(|| {
             Application { 
                 current_game_state: Box::new( MainMenuState::new() ),
+                suspended_game_states: vec![],
+                music: MusicManager::new(),
             }
          })
     }
@@ -36,11 +70,17 @@ impl Game for Application {
     //runs a generic update for data and handling of misc things.
     fn update(&mut self, window: &Window) {
         self.current_game_state.update(window);
+        self.apply_state_change();
+
+        if let Some(playlist) = self.current_game_state.music() {
+            self.music.play(playlist);
+        }
+        self.music.update( 1.0 / f32::from(TICKS_PER_SECOND) );
     }
 
     //handles general input
-    fn interact(&mut self, kbm: &mut KeyboardAndMouse, window: &mut Window) {
-        self.current_game_state.interact(kbm, window);
+    fn interact(&mut self, input: &mut GameInput, window: &mut Window) {
+        self.current_game_state.interact(input, window);
     }
 
     //draws non ui elements to the screen
@@ -63,8 +103,12 @@ impl UserInterface for Application {
     fn react(&mut self, message: UIAction, window: &mut Window) {
         //change our state if the current one requests it 
         match self.current_game_state.react(message, window) {
-            Option::None => return,
-            Option::Some(new_state) => self.current_game_state = new_state,
+            Option::None => self.apply_state_change(),
+            //replacing the state leaves any paused states behind as well
+            Option::Some(new_state) => {
+                self.current_game_state = new_state;
+                self.suspended_game_states.clear();
+            },
         }
     }
 
@@ -89,11 +133,15 @@ fn main() {
         ]
     ).unwrap();
 
+    //volumes apply to everything played from here on, the window is only sized as it opens
+    let settings = settings::load_settings();
+    assets::audio::set_volume_levels(settings.volume);
+
     <Application as UserInterface>::run(WindowSettings {
         title: String::from("A caffeinated game"),
-        size: (1280, 1024),
+        size: settings.window_size,
         resizable: true,
-        fullscreen: false,
+        fullscreen: settings.fullscreen,
         maximized: true,
     })
     .expect("An error occured while starting the game");
@@ -0,0 +1,11 @@
+//pull in the save file format
+mod save_file;
+pub use save_file::{SaveFile, SavedWorld, SavedEntity, SavedVisual, SavedAnimation, SavedCollider, SavedCharacter, SavedStats,
+                    SavedTrigger, SavedWarp, SavedQuest, SAVE_VERSION, OLDEST_SAVE_VERSION};
+
+//pull in reading and writing save slots
+mod save_slots;
+pub use save_slots::{SlotSummary, write_save_slot, read_save_slot, summarize_save_slots};
+
+mod save_error;
+pub use save_error::SaveError;
@@ -0,0 +1,46 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use super::{SAVE_VERSION, OLDEST_SAVE_VERSION};
+
+
+//Reasons a game couldn't be saved or loaded. Shown to the player so they should read well.
+#[derive(Debug)]
+pub enum SaveError {
+    NoSaveDirectory,
+    InvalidSlot(usize),
+    EmptySlot(usize),
+    Io(PathBuf, io::Error),
+    Corrupt(PathBuf, String),
+    UnsupportedVersion(u32),
+    OutdatedVersion(u32),
+    MissingCampaign(String),
+    MissingAsset(String),
+}
+
+impl fmt::Display for SaveError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::NoSaveDirectory => 
+                write!(f, "Could not find a folder to keep save files in"),
+            SaveError::InvalidSlot(slot) => 
+                write!(f, "There is no save slot {}", slot + 1),
+            SaveError::EmptySlot(slot) => 
+                write!(f, "Save slot {} is empty", slot + 1),
+            SaveError::Io(path, error) => 
+                write!(f, "Could not access {}: {}", path.display(), error),
+            SaveError::Corrupt(path, error) => 
+                write!(f, "Save file {} is damaged: {}", path.display(), error),
+            SaveError::UnsupportedVersion(version) => 
+                write!(f, "Save file version {} is newer than this game supports (version {})", version, SAVE_VERSION),
+            SaveError::OutdatedVersion(version) => 
+                write!(f, "Save file version {} is too old to load, the oldest supported is version {}", version, OLDEST_SAVE_VERSION),
+            SaveError::MissingCampaign(campaign) => 
+                write!(f, "The save is for campaign '{}' which is no longer installed", campaign),
+            SaveError::MissingAsset(asset) => 
+                write!(f, "The save uses asset '{}' which no longer exists in the campaign", asset),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::assets::Variable;
+
+
+//Bumped whenever the layout of a save file changes, even when the new fields have serde
+//  defaults, so a save always says which layout it was written with.
+pub const SAVE_VERSION: u32 = 6;
+
+//oldest layout that can still be loaded. Version 1 saves have no party or event state and
+//  would load with nobody to control.
+pub const OLDEST_SAVE_VERSION: u32 = 2;
+
+
+//Everything needed to put a player back exactly where they were in a campaign.
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub campaign: String, //name of the campaign from its campaign.yml
+    pub campaign_version: String, //version of the campaign when the save was made
+    pub saved_at: u64, //seconds since the unix epoch
+    pub play_time: f64, //seconds spent in the game world
+    pub world: SavedWorld,
+}
+
+
+//State of the ECS world. Only data that can't be rebuilt from the campaign's assets is kept.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SavedWorld {
+    pub current_map: Option<String>,
+    #[serde(default = "default_zoom")]
+    pub camera_zoom: f32,
+    pub entities: Vec<SavedEntity>,
+    pub flags: HashMap<String, Variable>,
+    pub dialogue: Option<(String, String)>, //conversation and node the player was in
+    pub inventory: Vec<(String, u32)>, //item names and how many the party carries
+    pub party: Vec<usize>, //places in the entity list of the active party members, leader first
+    pub reserve: Vec<usize>, //places in the entity list of the reserve party members
+    pub events: Vec<(String, usize)>, //event scripts still to run and the command each is on, running one first
+    pub quests: Vec<SavedQuest>, //in the order they were started
+    pub shop_sales: HashMap<String, HashMap<String, u32>>, //how many of each limited item every shop has sold
+}
+
+fn default_zoom() -> f32 { 1.0 }
+
+
+//One entity and the components it had. Components the entity didn't have are left out.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SavedEntity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<(f32, f32)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visual: Option<SavedVisual>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation: Option<SavedAnimation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player_control_speed: Option<f32>,
+    pub movement: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collider: Option<SavedCollider>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interaction_dialogue: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character: Option<SavedCharacter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<SavedTrigger>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warp: Option<SavedWarp>,
+    pub camera_target: bool, //the camera was following this entity
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedVisual {
+    pub sprite_sheet: String,
+    pub sprite: (u16, u16),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedAnimation {
+    pub name: String,
+    pub index: usize,
+    pub frame_duration: f32,
+    pub timer: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedCharacter {
+    pub name: String,
+    pub class: String,
+    pub level: u32,
+    pub experience: u32,
+    pub stats: SavedStats,
+    pub hp: u32,
+    pub mp: u32,
+    #[serde(default)]
+    pub equipment: HashMap<String, String>, //item worn in each slot
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedStats {
+    pub max_hp: u32,
+    pub max_mp: u32,
+    pub attack: u32,
+    pub defense: u32,
+    pub magic: u32,
+    pub speed: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedCollider {
+    pub offset: (f32, f32),
+    pub size: (f32, f32),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTrigger {
+    pub on: String, //condition as written in yaml
+    pub condition: Option<String>, //condition of flag change triggers as written in yaml
+    pub event: String,
+    pub size: (f32, f32),
+    pub last_state: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedWarp {
+    pub map: String,
+    pub arrive_at: Option<String>,
+    pub size: (f32, f32),
+    pub last_state: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedQuest {
+    pub name: String,
+    pub stage: usize,
+    pub progress: Vec<u32>,
+    pub completed: bool,
+}
@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{SaveFile, SaveError, SAVE_VERSION, OLDEST_SAVE_VERSION};
+
+
+//number of save slots offered to the player
+pub const SAVE_SLOT_COUNT: usize = 5;
+
+
+//Short description of a save slot for save and load menus.
+pub struct SlotSummary {
+    pub description: String,
+    pub loadable: bool,
+}
+
+
+//folder save files go in. Each user gets their own under the platform's data folder.
+fn save_directory() -> Result<PathBuf, SaveError> {
+    dirs::data_dir()
+        .map(|data_dir| data_dir.join(env!("CARGO_PKG_NAME")).join("saves"))
+        .ok_or(SaveError::NoSaveDirectory)
+}
+
+fn slot_path(slot: usize) -> Result<PathBuf, SaveError> {
+    if slot >= SAVE_SLOT_COUNT {
+        return Err(SaveError::InvalidSlot(slot));
+    }
+
+    Ok(save_directory()?.join(format!("slot_{}.json", slot + 1)))
+}
+
+
+//writes a save file to a slot, replacing whatever was there. The file is written next to the
+//  slot first so a failed write doesn't destroy the old save.
+pub fn write_save_slot(slot: usize, save: &mut SaveFile) -> Result<(), SaveError> {
+    let path = slot_path(slot)?;
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|error| SaveError::Io(directory.to_path_buf(), error))?;
+    }
+
+    save.version = SAVE_VERSION;
+    save.saved_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+
+    let contents = serde_json::to_string_pretty(save)
+        .map_err(|error| SaveError::Corrupt(path.clone(), error.to_string()))?;
+
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, contents).map_err(|error| SaveError::Io(temp_path.clone(), error))?;
+    fs::rename(&temp_path, &path).map_err(|error| SaveError::Io(path.clone(), error))?;
+
+    Ok(())
+}
+
+
+//reads the save file in a slot
+pub fn read_save_slot(slot: usize) -> Result<SaveFile, SaveError> {
+    let path = slot_path(slot)?;
+
+    if !path.exists() {
+        return Err(SaveError::EmptySlot(slot));
+    }
+
+    let contents = fs::read_to_string(&path).map_err(|error| SaveError::Io(path.clone(), error))?;
+
+    parse_save_file(&path, &contents)
+}
+
+
+//turns the contents of a save file back into a save, checking it is a layout we can read
+fn parse_save_file(path: &Path, contents: &str) -> Result<SaveFile, SaveError> {
+    //check the version before the rest so a different layout gets a clearer error than a parse failure
+    let mut save = serde_json::from_str::<serde_json::Value>(contents)
+        .map_err(|error| SaveError::Corrupt(path.to_path_buf(), error.to_string()))?;
+
+    let version = save.get("version")
+        .and_then(|version| version.as_u64())
+        .ok_or_else(|| SaveError::Corrupt(path.to_path_buf(), "missing version".to_string()))?;
+
+    if version > u64::from(SAVE_VERSION) {
+        return Err(SaveError::UnsupportedVersion(version as u32));
+    }
+
+    if version < u64::from(OLDEST_SAVE_VERSION) {
+        return Err(SaveError::OutdatedVersion(version as u32));
+    }
+
+    if version < 4 {
+        migrate_flag_triggers(&mut save);
+    }
+
+    serde_json::from_value::<SaveFile>(save)
+        .map_err(|error| SaveError::Corrupt(path.to_path_buf(), error.to_string()))
+}
+
+
+//before version 4 flag change triggers saved a [flag, value] pair instead of a condition. Flags
+//  themselves were all true or false, which still reads the same.
+fn migrate_flag_triggers(save: &mut serde_json::Value) {
+    let entities = match save.pointer_mut("/world/entities").and_then(|entities| entities.as_array_mut()) {
+        Some(entities) => entities,
+        None => return,
+    };
+
+    for trigger in entities.iter_mut().filter_map(|entity| entity.get_mut("trigger")).filter_map(|trigger| trigger.as_object_mut()) {
+        let condition = match trigger.remove("flag").and_then(|flag| serde_json::from_value::<(String, bool)>(flag).ok()) {
+            Some((flag, true)) => flag,
+            Some((flag, false)) => format!("!{}", flag),
+            None => continue,
+        };
+        trigger.insert("condition".to_string(), serde_json::Value::String(condition));
+    }
+}
+
+
+//describes every save slot in order
+pub fn summarize_save_slots() -> Vec<SlotSummary> {
+    (0..SAVE_SLOT_COUNT).map(|slot| {
+        match read_save_slot(slot) {
+            Ok(save) => SlotSummary {
+                description: format!("Slot {}: {} - {} - {} played",
+                    slot + 1,
+                    save.campaign,
+                    save.world.current_map.as_deref().unwrap_or("no map"),
+                    format_play_time(save.play_time),
+                ),
+                loadable: true,
+            },
+            Err(SaveError::EmptySlot(_)) => SlotSummary {
+                description: format!("Slot {}: Empty", slot + 1),
+                loadable: false,
+            },
+            Err(error) => {
+                warn!("[Save Game] Could not read save slot {}: {}", slot + 1, error);
+                SlotSummary {
+                    description: format!("Slot {}: Unreadable", slot + 1),
+                    loadable: true, //loading it again shows the player what went wrong
+                }
+            },
+        }
+    }).collect()
+}
+
+
+//hours:minutes:seconds
+fn format_play_time(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save_with_version(version: u32) -> String {
+        format!(r#"{{ "version": {}, "campaign": "Test", "campaign_version": "1", "saved_at": 0,
+                      "play_time": 0.0, "world": {{}} }}"#, version)
+    }
+
+    #[test]
+    fn current_version_loads() {
+        let save = parse_save_file(Path::new("slot_1.json"), &save_with_version(SAVE_VERSION)).unwrap();
+        assert_eq!(save.campaign, "Test");
+    }
+
+    #[test]
+    fn older_versions_are_rejected() {
+        match parse_save_file(Path::new("slot_1.json"), &save_with_version(1)) {
+            Err(SaveError::OutdatedVersion(1)) => {},
+            _ => panic!("version 1 save should be rejected as outdated"),
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        match parse_save_file(Path::new("slot_1.json"), &save_with_version(SAVE_VERSION + 1)) {
+            Err(SaveError::UnsupportedVersion(_)) => {},
+            _ => panic!("newer save should be rejected as unsupported"),
+        }
+    }
+
+    #[test]
+    fn version_3_flag_triggers_become_conditions() {
+        let contents = r#"{ "version": 3, "campaign": "Test", "campaign_version": "1", "saved_at": 0, "play_time": 0.0,
+                            "world": { "flags": { "met villager": true },
+                                       "entities": [ { "trigger": { "on": "flag change", "flag": ["met villager", false],
+                                                                    "event": "greet", "size": [0, 0], "last_state": null } } ] } }"#;
+
+        let save = parse_save_file(Path::new("slot_1.json"), contents).unwrap();
+        let trigger = save.world.entities[0].trigger.as_ref().unwrap();
+        assert_eq!(trigger.condition.as_deref(), Some("!met villager"));
+        assert_eq!(save.world.flags["met villager"], crate::assets::Variable::Bool(true));
+    }
+
+    #[test]
+    fn missing_version_is_corrupt() {
+        match parse_save_file(Path::new("slot_1.json"), "{}") {
+            Err(SaveError::Corrupt(_, _)) => {},
+            _ => panic!("save without a version should be corrupt"),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+//pull in the player's settings and the file they are kept in
+mod user_settings;
+pub use user_settings::{Settings, TextSpeed, WINDOW_SIZES, load_settings, save_settings};
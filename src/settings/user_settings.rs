@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use crate::assets::audio::VolumeLevels;
+use crate::input::InputBindings;
+
+
+//window sizes offered on the options screen
+pub const WINDOW_SIZES: [(u32, u32); 4] = [(1280, 720), (1280, 1024), (1600, 900), (1920, 1080)];
+
+
+//How quickly dialogue text is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextSpeed {
+    Slow,
+    Normal,
+    Fast,
+    Instant,
+}
+
+impl TextSpeed {
+
+    pub const ALL: [TextSpeed; 4] = [TextSpeed::Slow, TextSpeed::Normal, TextSpeed::Fast, TextSpeed::Instant];
+
+    //None when the whole text is shown straight away
+    pub fn characters_per_second(self) -> Option<f32> {
+        match self {
+            TextSpeed::Slow => Some(20.0),
+            TextSpeed::Normal => Some(45.0),
+            TextSpeed::Fast => Some(90.0),
+            TextSpeed::Instant => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextSpeed::Slow => "Slow",
+            TextSpeed::Normal => "Normal",
+            TextSpeed::Fast => "Fast",
+            TextSpeed::Instant => "Instant",
+        }
+    }
+}
+
+
+//Choices the player makes that aren't part of any one game, kept in the user's config folder.
+//  Anything the file leaves out or gets wrong uses its default.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub volume: VolumeLevels,
+    pub window_size: (u32, u32), //used the next time the game starts
+    pub fullscreen: bool,
+    pub text_speed: TextSpeed,
+    pub controls: InputBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            volume: VolumeLevels::default(),
+            window_size: (1280, 1024),
+            fullscreen: false,
+            text_speed: TextSpeed::Normal,
+            controls: InputBindings::new(),
+        }
+    }
+}
+
+
+fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join(env!("CARGO_PKG_NAME")).join("settings.json"))
+}
+
+
+fn parse_settings(contents: &str) -> Result<Settings, String> {
+    serde_json::from_str::<Settings>(contents).map_err(|error| error.to_string())
+}
+
+
+//reads the player's settings, falling back to the defaults when there are none or they can't be read
+pub fn load_settings() -> Settings {
+    let path = match settings_path() {
+        Some(path) if path.exists() => path,
+        _ => return Settings::default(),
+    };
+
+    match fs::read_to_string(&path).map_err(|error| error.to_string()).and_then(|contents| parse_settings(&contents)) {
+        Ok(settings) => settings,
+        Err(error) => {
+            warn!("[Settings] Could not read settings file {}, using the default settings. Error follows: {}",
+                  path.display(), error);
+            Settings::default()
+        }
+    }
+}
+
+
+//writes the player's settings so they are used next time the game starts
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    let path = settings_path().ok_or_else(|| "There is nowhere to keep the settings.".to_string())?;
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|error| format!("Could not save the settings: {}", error))?;
+    }
+
+    let contents = serde_json::to_string_pretty(settings).map_err(|error| error.to_string())?;
+    fs::write(&path, contents).map_err(|error| format!("Could not save the settings: {}", error))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputAction;
+
+    #[test]
+    fn settings_read_back() {
+        let mut settings = Settings::default();
+        settings.volume.music = 0.7;
+        settings.text_speed = TextSpeed::Fast;
+        settings.fullscreen = true;
+
+        let read = parse_settings(&serde_json::to_string(&settings).unwrap()).unwrap();
+        assert_eq!(read.volume, settings.volume);
+        assert_eq!((read.text_speed, read.fullscreen, read.window_size), (TextSpeed::Fast, true, (1280, 1024)));
+        assert_eq!(read.controls.describe(InputAction::Confirm), settings.controls.describe(InputAction::Confirm));
+    }
+
+    #[test]
+    fn missing_settings_use_defaults() {
+        let read = parse_settings(r#"{ "volume": { "effects": 0.5 }, "text_speed": "slow" }"#).unwrap();
+
+        assert_eq!(read.volume, VolumeLevels { effects: 0.5, ..VolumeLevels::default() });
+        assert_eq!(read.text_speed, TextSpeed::Slow);
+        assert!(!read.fullscreen);
+        assert!(parse_settings(r#"{ "text_speed": "ludicrous" }"#).is_err());
+    }
+}